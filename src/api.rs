@@ -0,0 +1,1779 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use heck::ToSnakeCase;
+use quote::ToTokens;
+use syn::{
+    Attribute, Block, Expr, ExprCall, ExprLit, ExprMethodCall, Field, Fields, FnArg,
+    GenericArgument, GenericParam, Generics, ImplItem, Item, Lit, Meta, PathArguments, ReturnType,
+    Signature, Stmt, Type, UnOp,
+    punctuated::Punctuated,
+    token::Comma,
+    visit::{self, Visit},
+};
+
+use crate::{
+    error::{Context, Result},
+    output,
+};
+
+/// Methods of the method routers of axum and the route guards of actix-web.
+const METHODS: [&str; 7] = ["get", "post", "put", "patch", "delete", "head", "options"];
+
+/// Extractors of bodies other than JSON, which the client does not send.
+const OTHER_BODIES: [&str; 9] = [
+    "Form",
+    "Multipart",
+    "Bytes",
+    "String",
+    "Payload",
+    "Request",
+    "Body",
+    "WebSocketUpgrade",
+    "BodyStream",
+];
+
+/// Types the client can use without copying them.
+const PRIMITIVES: [&str; 21] = [
+    "bool", "char", "str", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64",
+    "u128", "usize", "f32", "f64", "String", "Vec", "Option", "Box",
+];
+
+/// Collections of std, qualified when the types are copied.
+const COLLECTIONS: [&str; 5] = ["HashMap", "HashSet", "BTreeMap", "BTreeSet", "VecDeque"];
+
+/// Crates whose types the client depends on, with the features serializing
+/// them, and the types a `use` usually brings in unqualified.
+pub const TYPE_CRATES: [(&str, Option<&str>, &[&str]); 4] = [
+    (
+        "chrono",
+        Some("serde"),
+        &[
+            "DateTime",
+            "Utc",
+            "NaiveDate",
+            "NaiveDateTime",
+            "NaiveTime",
+            "FixedOffset",
+        ],
+    ),
+    ("uuid", Some("serde"), &["Uuid"]),
+    ("rust_decimal", None, &["Decimal"]),
+    ("serde_json", None, &["Value"]),
+];
+
+/// Derives the copied types keep, serde's are added to them.
+const KEPT_DERIVES: [&str; 9] = [
+    "Debug",
+    "Clone",
+    "Copy",
+    "Default",
+    "PartialEq",
+    "Eq",
+    "Hash",
+    "PartialOrd",
+    "Ord",
+];
+
+/// Serde attributes naming functions or types of the server, which change
+/// what goes over the wire.
+const SERDE_PATHS: [&str; 7] = [
+    "with",
+    "serialize_with",
+    "deserialize_with",
+    "from",
+    "try_from",
+    "into",
+    "remote",
+];
+
+/// An endpoint of the server.
+#[derive(Debug, Clone)]
+pub struct Endpoint {
+    pub method: &'static str,
+    /// Full path, with its parameters between braces like `/users/{id}`.
+    pub path: String,
+    /// Name of the handler, `None` for closures.
+    pub handler: Option<String>,
+    /// Last segment of the module of the handler.
+    pub module: Option<String>,
+    pub params: Vec<Param>,
+    pub body: Body,
+    /// Type of the `Query` extractor.
+    pub query: Option<String>,
+    pub response: Response,
+}
+
+/// A parameter of the path of an endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    /// Type of the `Path` extractor for it, `None` when there is none.
+    pub rust_type: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Body {
+    None,
+    Json(String),
+    /// An extractor of another body, e.g. `Multipart`.
+    Other(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {
+    Empty,
+    Text,
+    Bytes,
+    Json(String),
+}
+
+/// A function of the server, free or in an `impl` block.
+struct Function {
+    /// Module the function is declared in, empty for main.rs and lib.rs.
+    module: Vec<String>,
+    sig: Signature,
+    block: Block,
+    attrs: Vec<Attribute>,
+}
+
+/// A struct or enum of the server.
+struct TypeItem {
+    name: String,
+    item: Item,
+}
+
+/// What the body of a router function registers, in order.
+enum Entry {
+    Endpoint(Endpoint),
+    /// The router built by another function, mounted at a path.
+    Mount(usize, String),
+}
+
+/// The sources of a server, read to find its endpoints and the types they
+/// take and answer.
+pub struct Project {
+    functions: Vec<Function>,
+    types: Vec<TypeItem>,
+    /// Top-level modules of the crate.
+    modules: BTreeSet<String>,
+}
+
+/// The types an endpoint needs, copied into the client.
+#[derive(Debug, Default)]
+pub struct Types {
+    /// Copied types, in the order they were reached.
+    pub items: Vec<String>,
+    /// Crates of [`TYPE_CRATES`] the items and signatures use.
+    pub crates: BTreeSet<&'static str>,
+    /// Names of the copied types, or `None` for those that cannot be.
+    copied: BTreeMap<String, Option<String>>,
+}
+
+impl Project {
+    /// Reads the Rust files in `src/` of `project_dir`, binaries in
+    /// `src/bin/` left out.
+    pub fn read(project_dir: &Path) -> Result<Self> {
+        let src = project_dir.join("src");
+        let mut files = Vec::new();
+        collect_files(&src, &mut files)?;
+        files.sort();
+
+        let mut sources = Vec::new();
+        for file in files {
+            let Ok(relative) = file.strip_prefix(&src) else {
+                continue;
+            };
+            if relative.starts_with("bin") {
+                continue;
+            }
+            let text = fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            sources.push((relative.to_path_buf(), text));
+        }
+        Ok(Self::from_sources(sources))
+    }
+
+    /// The project of `sources`, each a path relative to `src/` with its
+    /// text. Files that do not parse are skipped.
+    fn from_sources(sources: Vec<(PathBuf, String)>) -> Self {
+        let mut project = Project {
+            functions: Vec::new(),
+            types: Vec::new(),
+            modules: BTreeSet::new(),
+        };
+        for (relative, text) in sources {
+            let file = match syn::parse_file(&text) {
+                Ok(file) => file,
+                Err(err) => {
+                    output::warn(format!(
+                        "Skipping src/{}, it does not parse: {}",
+                        relative.display(),
+                        err
+                    ));
+                    continue;
+                }
+            };
+            let module = module_of(&relative);
+            if let Some(first) = module.first() {
+                project.modules.insert(first.clone());
+            }
+            project.add_items(&module, &file.items);
+        }
+        project
+    }
+
+    /// Adds the functions and types of `items` in `module`, and of the
+    /// modules declared inline among them. Tests are no part of the server.
+    fn add_items(&mut self, module: &[String], items: &[Item]) {
+        for item in items {
+            match item {
+                Item::Fn(function) if !is_test(&function.attrs) => {
+                    self.functions.push(Function {
+                        module: module.to_vec(),
+                        sig: function.sig.clone(),
+                        block: (*function.block).clone(),
+                        attrs: function.attrs.clone(),
+                    });
+                }
+                Item::Impl(block) if !is_test(&block.attrs) => {
+                    for item in &block.items {
+                        if let ImplItem::Fn(function) = item
+                            && !is_test(&function.attrs)
+                        {
+                            self.functions.push(Function {
+                                module: module.to_vec(),
+                                sig: function.sig.clone(),
+                                block: function.block.clone(),
+                                attrs: function.attrs.clone(),
+                            });
+                        }
+                    }
+                }
+                Item::Mod(inline) if !is_test(&inline.attrs) => {
+                    let Some((_, items)) = &inline.content else {
+                        continue;
+                    };
+                    let name = inline.ident.to_string();
+                    if module.is_empty() {
+                        self.modules.insert(name.clone());
+                    }
+                    self.add_items(&[module, &[name]].concat(), items);
+                }
+                Item::Struct(item) => self.types.push(TypeItem {
+                    name: item.ident.to_string(),
+                    item: Item::Struct(item.clone()),
+                }),
+                Item::Enum(item) => self.types.push(TypeItem {
+                    name: item.ident.to_string(),
+                    item: Item::Enum(item.clone()),
+                }),
+                _ => {}
+            }
+        }
+    }
+
+    /// Endpoints registered by the routers of the `framework` server, in the
+    /// order of their registration.
+    pub fn endpoints(&self, framework: &str) -> Vec<Endpoint> {
+        let entries: Vec<Vec<Entry>> = (0..self.functions.len())
+            .map(|function| self.entries(framework, function))
+            .collect();
+        // A router mounted by another one gets its prefix from there, the
+        // others are mounted at the root
+        let mounted: BTreeSet<usize> = entries
+            .iter()
+            .flatten()
+            .filter_map(|entry| match entry {
+                Entry::Mount(target, _) => Some(*target),
+                Entry::Endpoint(_) => None,
+            })
+            .collect();
+        let mut endpoints = Vec::new();
+        for root in (0..self.functions.len()).filter(|root| !mounted.contains(root)) {
+            collect(&entries, root, "", &mut vec![root], &mut endpoints);
+        }
+        endpoints
+    }
+
+    /// What the body of `function` registers, with the paths relative to
+    /// the router it builds.
+    fn entries(&self, framework: &str, function: usize) -> Vec<Entry> {
+        let mut routes = Routes {
+            project: self,
+            framework,
+            function,
+            prefix: String::new(),
+            entries: Vec::new(),
+        };
+        routes.visit_block(&self.functions[function].block);
+        routes.entries
+    }
+
+    /// Methods and handlers of a method router of axum, like
+    /// `get(list).post(create)`, or of a route of actix-web, like
+    /// `web::get().to(list)`.
+    fn method_router<'a>(
+        &'a self,
+        function: usize,
+        router: &'a Expr,
+    ) -> Vec<(&'static str, &'a Expr)> {
+        let segments = call_chain(router);
+        let mut routes = Vec::new();
+        for (index, (name, args)) in segments.iter().enumerate() {
+            let Some(method) = METHODS.into_iter().find(|method| name == method) else {
+                continue;
+            };
+            match (args.as_slice(), segments.get(index + 1)) {
+                ([], Some((to, next))) if to == "to" && next.len() == 1 => {
+                    routes.push((method, next[0]));
+                }
+                ([handler, ..], _) => routes.push((method, *handler)),
+                _ => {}
+            }
+        }
+        // A function of the project building the method router, with a
+        // layer on top for instance
+        if routes.is_empty()
+            && let Some(builder) = self.resolve(function, router)
+            && builder != function
+            && let Some(tail) = tail_expr(&self.functions[builder].block)
+        {
+            return self.method_router(builder, tail);
+        }
+        routes
+    }
+
+    /// The endpoint of `handler`, a path or a closure, at `path`.
+    fn endpoint(
+        &self,
+        function: usize,
+        method: &'static str,
+        path: String,
+        handler: &Expr,
+    ) -> Endpoint {
+        let names = path_params(&path);
+        let resolved = match handler {
+            Expr::Closure(_) => None,
+            _ => self.resolve(function, handler),
+        };
+        let Some(resolved) = resolved else {
+            return Endpoint {
+                method,
+                path,
+                handler: None,
+                module: None,
+                params: names
+                    .into_iter()
+                    .map(|name| Param {
+                        name,
+                        rust_type: None,
+                    })
+                    .collect(),
+                body: Body::None,
+                query: None,
+                response: closure_response(handler),
+            };
+        };
+
+        let handler = &self.functions[resolved];
+        let mut body = Body::None;
+        let mut query = None;
+        let mut path_type = None;
+        for input in &handler.sig.inputs {
+            let FnArg::Typed(param) = input else {
+                continue;
+            };
+            let Some((outer, inner)) = outer_type(&param.ty) else {
+                continue;
+            };
+            match outer.as_str() {
+                "Json" => body = Body::Json(inner.map(type_text).unwrap_or_default()),
+                "Query" => query = inner.map(type_text),
+                "Path" => path_type = inner,
+                _ if OTHER_BODIES.contains(&outer.as_str()) => body = Body::Other(outer),
+                _ => {}
+            }
+        }
+        let path_types: Vec<String> = match path_type.map(ungrouped) {
+            Some(Type::Tuple(tuple)) if names.len() > 1 => {
+                tuple.elems.iter().map(type_text).collect()
+            }
+            Some(single) if names.len() == 1 => vec![type_text(single)],
+            _ => Vec::new(),
+        };
+        Endpoint {
+            method,
+            handler: Some(handler.sig.ident.to_string()),
+            module: handler.module.last().cloned(),
+            params: names
+                .into_iter()
+                .enumerate()
+                .map(|(index, name)| Param {
+                    name,
+                    rust_type: path_types.get(index).cloned(),
+                })
+                .collect(),
+            body,
+            query,
+            response: self.response(&handler.sig, &handler.block),
+            path,
+        }
+    }
+
+    /// What a handler answers, from its return type and body. The JSON of
+    /// a handler answering `impl Responder` is typed after the struct it
+    /// builds, like `.json(Greeting { .. })`.
+    fn response(&self, sig: &Signature, block: &Block) -> Response {
+        let ReturnType::Type(_, ret) = &sig.output else {
+            return Response::Empty;
+        };
+        let mut ret = ungrouped(ret);
+        if let Some((outer, Some(ok))) = outer_type(ret)
+            && outer == "Result"
+        {
+            ret = ungrouped(ok);
+        }
+        // The body of a tuple comes last, after the status and headers
+        if let Type::Tuple(tuple) = ret
+            && let Some(last) = tuple.elems.last()
+        {
+            ret = ungrouped(last);
+        }
+        let mut answers = Answers::default();
+        answers.visit_block(block);
+        match (ret, outer_type(ret)) {
+            (_, Some((outer, Some(inner)))) if outer == "Json" => Response::Json(type_text(inner)),
+            (_, Some((outer, _))) if outer == "StatusCode" => Response::Empty,
+            (_, Some((outer, _))) if ["String", "Html", "Cow"].contains(&outer.as_str()) => {
+                Response::Text
+            }
+            (Type::Tuple(_), _) => Response::Empty,
+            (Type::Reference(_), _) => Response::Text,
+            _ if !answers.json.is_empty() => self.built_json(&answers.json),
+            _ if answers.bodies.iter().any(|body| is_text(body)) => Response::Text,
+            _ if answers.bodies.is_empty() && answers.finish => Response::Empty,
+            _ => Response::Bytes,
+        }
+    }
+
+    /// JSON of the struct every one of `built` is a literal of, or any JSON.
+    fn built_json(&self, built: &[&Expr]) -> Response {
+        let name = |expr: &&Expr| match expr {
+            Expr::Struct(literal) => literal
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string()),
+            _ => None,
+        };
+        let mut names = built.iter().map(name);
+        match names.next().flatten() {
+            Some(first)
+                if names.all(|other| other.as_ref() == Some(&first))
+                    && self.types.iter().any(|item| item.name == first) =>
+            {
+                Response::Json(first)
+            }
+            _ => Response::Json("serde_json::Value".to_string()),
+        }
+    }
+
+    /// The function an expression like `handlers::index` or
+    /// `users::router()` in `function` refers to.
+    fn resolve(&self, function: usize, expr: &Expr) -> Option<usize> {
+        let path = match expr {
+            Expr::Path(path) if path.qself.is_none() => &path.path,
+            Expr::Call(call) if call.args.is_empty() => match &*call.func {
+                Expr::Path(path) if path.qself.is_none() => &path.path,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let mut segments: Vec<String> = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect();
+        let name = segments.pop()?;
+        let module = &self.functions[function].module;
+        let candidates: Vec<Vec<String>> = match segments.first().map(String::as_str) {
+            Some("crate") => vec![segments[1..].to_vec()],
+            Some("super") => {
+                let parent = &module[..module.len().saturating_sub(1)];
+                vec![[parent, &segments[1..]].concat()]
+            }
+            Some("self") => vec![[module.as_slice(), &segments[1..]].concat()],
+            _ => vec![[module.as_slice(), &segments].concat(), segments.clone()],
+        };
+        let find = |module: &[String]| {
+            self.functions
+                .iter()
+                .position(|candidate| candidate.sig.ident == name && candidate.module == module)
+        };
+        candidates
+            .iter()
+            .find_map(|module| find(module))
+            .or_else(|| {
+                // Brought in by a `use`, found when one function has the name
+                let mut named = self.functions.iter().enumerate().filter(|(_, candidate)| {
+                    candidate.sig.ident == name && candidate.module.ends_with(&segments)
+                });
+                match (named.next(), named.next()) {
+                    (Some((index, _)), None) => Some(index),
+                    _ => None,
+                }
+            })
+    }
+
+    /// `rust_type` as the client names it, with the types of the project it
+    /// needs added to `types`. `None` when one of them cannot be copied: it
+    /// borrows, names a trait or comes from another crate.
+    pub fn client_type(&self, rust_type: &str, types: &mut Types) -> Option<String> {
+        let rust_type: Type = syn::parse_str(rust_type).ok()?;
+        let mut writer = Writer::client(self, &[]);
+        let text = writer.ty(&rust_type)?;
+        for name in &writer.names {
+            self.copy_type(name, types)?;
+        }
+        types.crates.extend(writer.crates);
+        Some(text)
+    }
+
+    /// Copies the struct or enum `name` and the types it uses into `types`,
+    /// `None` when it cannot be copied.
+    fn copy_type(&self, name: &str, types: &mut Types) -> Option<()> {
+        if let Some(copied) = types.copied.get(name) {
+            return copied.as_ref().map(|_| ());
+        }
+        // Types using each other are copied once
+        types
+            .copied
+            .insert(name.to_string(), Some(name.to_string()));
+        let copy = self
+            .types
+            .iter()
+            .find(|item| item.name == name)
+            .and_then(|item| self.copy_item(item));
+        let Some((text, names, crates)) = copy else {
+            types.copied.insert(name.to_string(), None);
+            return None;
+        };
+        let at = types.items.len();
+        types.items.push(text);
+        for used in &names {
+            if self.copy_type(used, types).is_none() {
+                types.items.truncate(at);
+                types.copied.insert(name.to_string(), None);
+                return None;
+            }
+        }
+        types.crates.extend(crates);
+        Some(())
+    }
+
+    /// `item` as the client declares it: public, with the derives of std
+    /// and serde's, the attributes of other crates dropped and its fields
+    /// typed the way [`Project::client_type`] names them. Returns the text
+    /// with the types and crates it uses.
+    fn copy_item(
+        &self,
+        item: &TypeItem,
+    ) -> Option<(String, BTreeSet<String>, BTreeSet<&'static str>)> {
+        let (attrs, generics) = match &item.item {
+            Item::Struct(declared) => (&declared.attrs, &declared.generics),
+            Item::Enum(declared) => (&declared.attrs, &declared.generics),
+            _ => return None,
+        };
+        let params = type_params(generics)?;
+        let mut writer = Writer::client(self, &params);
+        let generics = if params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", params.join(", "))
+        };
+
+        let mut text = docs(attrs, "");
+        text.push_str(&derive_attribute(attrs));
+        text.push('\n');
+        text.push_str(&serde_attributes(attrs, "")?);
+        match &item.item {
+            Item::Struct(declared) => {
+                let header = format!("pub struct {}{}", declared.ident, generics);
+                match &declared.fields {
+                    Fields::Named(fields) => {
+                        writeln!(text, "{} {{", header).ok()?;
+                        for field in &fields.named {
+                            text.push_str(&named_field(field, "    ", true, &mut writer)?);
+                        }
+                        text.push('}');
+                    }
+                    Fields::Unnamed(fields) => {
+                        let fields = tuple_fields(&fields.unnamed, true, &mut writer)?;
+                        write!(text, "{}({});", header, fields).ok()?;
+                    }
+                    Fields::Unit => write!(text, "{};", header).ok()?,
+                }
+            }
+            Item::Enum(declared) => {
+                writeln!(text, "pub enum {}{} {{", declared.ident, generics).ok()?;
+                for variant in &declared.variants {
+                    text.push_str(&docs(&variant.attrs, "    "));
+                    text.push_str(&serde_attributes(&variant.attrs, "    ")?);
+                    let fields = match &variant.fields {
+                        Fields::Named(fields)
+                            if fields.named.iter().all(|field| {
+                                docs(&field.attrs, "").is_empty()
+                                    && !field.attrs.iter().any(is_serde)
+                            }) =>
+                        {
+                            let fields = fields
+                                .named
+                                .iter()
+                                .map(|field| {
+                                    Some(format!(
+                                        "{}: {}",
+                                        field.ident.as_ref()?,
+                                        writer.ty(&field.ty)?
+                                    ))
+                                })
+                                .collect::<Option<Vec<_>>>()?;
+                            format!(" {{ {} }}", fields.join(", "))
+                        }
+                        Fields::Named(fields) => {
+                            let mut block = String::from(" {\n");
+                            for field in &fields.named {
+                                block.push_str(&named_field(
+                                    field,
+                                    "        ",
+                                    false,
+                                    &mut writer,
+                                )?);
+                            }
+                            block.push_str("    }");
+                            block
+                        }
+                        Fields::Unnamed(fields) => {
+                            format!("({})", tuple_fields(&fields.unnamed, false, &mut writer)?)
+                        }
+                        Fields::Unit => String::new(),
+                    };
+                    let discriminant = variant
+                        .discriminant
+                        .as_ref()
+                        .map(|(_, value)| format!(" = {}", expr_text(value)))
+                        .unwrap_or_default();
+                    writeln!(text, "    {}{}{},", variant.ident, fields, discriminant).ok()?;
+                }
+                text.push('}');
+            }
+            _ => return None,
+        }
+        // Types of the item itself are no types to copy
+        writer.names.remove(&item.name);
+        Some((text, writer.names, writer.crates))
+    }
+}
+
+/// Adds the endpoints of the router `function` builds under `prefix`,
+/// followed into the routers it mounts.
+fn collect(
+    entries: &[Vec<Entry>],
+    function: usize,
+    prefix: &str,
+    visited: &mut Vec<usize>,
+    endpoints: &mut Vec<Endpoint>,
+) {
+    for entry in &entries[function] {
+        match entry {
+            Entry::Endpoint(endpoint) => endpoints.push(Endpoint {
+                path: join_paths(prefix, &endpoint.path),
+                ..endpoint.clone()
+            }),
+            Entry::Mount(target, path) => {
+                if visited.contains(target) {
+                    continue;
+                }
+                visited.push(*target);
+                collect(
+                    entries,
+                    *target,
+                    &join_paths(prefix, path),
+                    visited,
+                    endpoints,
+                );
+                visited.pop();
+            }
+        }
+    }
+}
+
+/// Walks the body of a function for the routes it registers. Routers and
+/// scopes built inline are walked under the prefix they are mounted at.
+struct Routes<'a> {
+    project: &'a Project,
+    framework: &'a str,
+    function: usize,
+    prefix: String,
+    entries: Vec<Entry>,
+}
+
+impl<'a> Routes<'a> {
+    /// Registers what the call of a router chain adds.
+    fn call(&mut self, call: &'a ExprMethodCall) {
+        let args: Vec<&'a Expr> = call.args.iter().collect();
+        match (call.method.to_string().as_str(), args.as_slice()) {
+            ("route", [path, router]) if let Some(path) = string_literal(path) => {
+                let path = join_paths(&self.prefix, &path);
+                for (method, handler) in self.project.method_router(self.function, router) {
+                    let endpoint =
+                        self.project
+                            .endpoint(self.function, method, path.clone(), handler);
+                    self.entries.push(Entry::Endpoint(endpoint));
+                }
+            }
+            ("nest", [path, target]) if let Some(path) = string_literal(path) => {
+                self.mount(&path, target);
+            }
+            ("merge" | "configure", [target]) => self.mount("", target),
+            // Handlers with a route attribute are endpoints, the other
+            // services of actix-web are scopes
+            ("service", [target]) if self.framework != "axum" => {
+                let resolved = self.project.resolve(self.function, target);
+                let attribute =
+                    resolved.and_then(|handler| route_attribute(&self.project.functions[handler]));
+                match (resolved, attribute) {
+                    (Some(_), Some((method, path))) => {
+                        let path = join_paths(&self.prefix, &path);
+                        let endpoint = self.project.endpoint(self.function, method, path, target);
+                        self.entries.push(Entry::Endpoint(endpoint));
+                    }
+                    _ => self.mount("", target),
+                }
+            }
+            _ => {
+                for arg in args {
+                    self.visit_expr(arg);
+                }
+            }
+        }
+    }
+
+    /// Mounts the router `target` at `path`: a function of the project, or
+    /// one built inline.
+    fn mount(&mut self, path: &str, target: &'a Expr) {
+        let prefix = join_paths(&self.prefix, path);
+        match self.project.resolve(self.function, target) {
+            Some(function) => self.entries.push(Entry::Mount(function, prefix)),
+            None => {
+                let outer = std::mem::replace(&mut self.prefix, prefix);
+                self.visit_expr(target);
+                self.prefix = outer;
+            }
+        }
+    }
+}
+
+impl<'a> Visit<'a> for Routes<'a> {
+    fn visit_expr_method_call(&mut self, call: &'a ExprMethodCall) {
+        // The calls of a chain in the order they are written, after what
+        // they start from
+        let mut chain = vec![call];
+        let mut root = &*call.receiver;
+        while let Expr::MethodCall(inner) = root {
+            chain.push(inner);
+            root = &inner.receiver;
+        }
+        let outer = self.prefix.clone();
+        match scope_prefix(root) {
+            Some(scope) => self.prefix = join_paths(&outer, &scope),
+            None => self.visit_expr(root),
+        }
+        for call in chain.into_iter().rev() {
+            self.call(call);
+        }
+        self.prefix = outer;
+    }
+}
+
+/// Calls in the body of a handler that tell what it answers.
+#[derive(Default)]
+struct Answers<'a> {
+    /// Arguments of `Json(..)` and `.json(..)`.
+    json: Vec<&'a Expr>,
+    /// Arguments of `.body(..)`.
+    bodies: Vec<&'a Expr>,
+    /// Whether a response is built with `.finish()`.
+    finish: bool,
+}
+
+impl<'a> Visit<'a> for Answers<'a> {
+    fn visit_expr_call(&mut self, call: &'a ExprCall) {
+        if let Expr::Path(path) = &*call.func
+            && path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Json")
+            && let Some(arg) = call.args.first()
+        {
+            self.json.push(arg);
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'a ExprMethodCall) {
+        match (call.method.to_string().as_str(), call.args.first()) {
+            ("json", Some(arg)) => self.json.push(arg),
+            ("body", Some(arg)) => self.bodies.push(arg),
+            ("finish", None) => self.finish = true,
+            _ => {}
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+}
+
+/// Writes types the way rustfmt does. For the client, paths are qualified
+/// and the types of the project they name collected, without one `None`
+/// is returned for types the client cannot name.
+struct Writer<'a> {
+    /// Project the types are written for, `None` to write them as they are.
+    project: Option<&'a Project>,
+    /// Type parameters of the item the types are in.
+    generics: &'a [String],
+    /// Types of the project the written types use.
+    names: BTreeSet<String>,
+    crates: BTreeSet<&'static str>,
+}
+
+impl<'a> Writer<'a> {
+    fn plain() -> Self {
+        Writer {
+            project: None,
+            generics: &[],
+            names: BTreeSet::new(),
+            crates: BTreeSet::new(),
+        }
+    }
+
+    fn client(project: &'a Project, generics: &'a [String]) -> Self {
+        Writer {
+            project: Some(project),
+            generics,
+            ..Writer::plain()
+        }
+    }
+
+    fn ty(&mut self, ty: &Type) -> Option<String> {
+        match ty {
+            Type::Path(path) if path.qself.is_none() => self.path(&path.path),
+            // String slices are owned by the client, other borrows are not
+            Type::Reference(reference) if self.project.is_some() => match &*reference.elem {
+                Type::Path(path) if path.path.is_ident("str") => Some("String".to_string()),
+                _ => None,
+            },
+            Type::Reference(reference) => {
+                let lifetime = reference
+                    .lifetime
+                    .as_ref()
+                    .map(|lifetime| format!("{} ", lifetime))
+                    .unwrap_or_default();
+                let mutability = if reference.mutability.is_some() {
+                    "mut "
+                } else {
+                    ""
+                };
+                Some(format!(
+                    "&{}{}{}",
+                    lifetime,
+                    mutability,
+                    self.ty(&reference.elem)?
+                ))
+            }
+            Type::Tuple(tuple) => {
+                let elems = tuple
+                    .elems
+                    .iter()
+                    .map(|elem| self.ty(elem))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(match elems.as_slice() {
+                    [single] => format!("({},)", single),
+                    _ => format!("({})", elems.join(", ")),
+                })
+            }
+            Type::Array(array) => Some(format!(
+                "[{}; {}]",
+                self.ty(&array.elem)?,
+                expr_text(&array.len)
+            )),
+            Type::Slice(slice) => Some(format!("[{}]", self.ty(&slice.elem)?)),
+            Type::Paren(paren) => self.ty(&paren.elem),
+            Type::Group(group) => self.ty(&group.elem),
+            _ if self.project.is_none() => Some(ty.to_token_stream().to_string()),
+            _ => None,
+        }
+    }
+
+    fn path(&mut self, path: &syn::Path) -> Option<String> {
+        let Some(project) = self.project else {
+            let segments = path
+                .segments
+                .iter()
+                .map(|segment| {
+                    Some(format!(
+                        "{}{}",
+                        segment.ident,
+                        self.args(&segment.arguments)?
+                    ))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            let leading = if path.leading_colon.is_some() {
+                "::"
+            } else {
+                ""
+            };
+            return Some(format!("{}{}", leading, segments.join("::")));
+        };
+
+        // Only the last segment takes generic arguments
+        let last = path.segments.last()?;
+        if path
+            .segments
+            .iter()
+            .rev()
+            .skip(1)
+            .any(|segment| !segment.arguments.is_none())
+        {
+            return None;
+        }
+        let args = self.args(&last.arguments)?;
+        let segments: Vec<String> = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect();
+        let name = last.ident.to_string();
+        let qualified = match segments.as_slice() {
+            [single] if self.generics.contains(single) => single.clone(),
+            [single] if PRIMITIVES.contains(&single.as_str()) => single.clone(),
+            [single] if COLLECTIONS.contains(&single.as_str()) => {
+                format!("std::collections::{}", single)
+            }
+            [first, ..] if ["std", "core", "alloc"].contains(&first.as_str()) => {
+                segments.join("::")
+            }
+            [first, _, ..] if let Some(crate_name) = type_crate(first) => {
+                self.crates.insert(crate_name);
+                segments.join("::")
+            }
+            [single]
+                if let Some((crate_name, _, _)) = TYPE_CRATES
+                    .iter()
+                    .find(|(_, _, types)| types.contains(&single.as_str())) =>
+            {
+                self.crates.insert(crate_name);
+                format!("{}::{}", crate_name, single)
+            }
+            [first, ..]
+                if segments.len() == 1
+                    || ["crate", "super", "self"].contains(&first.as_str())
+                    || project.modules.contains(first) =>
+            {
+                if !project.types.iter().any(|item| item.name == name) {
+                    return None;
+                }
+                self.names.insert(name.clone());
+                name
+            }
+            _ => return None,
+        };
+        Some(format!("{}{}", qualified, args))
+    }
+
+    /// The generic arguments of a path segment, with their brackets.
+    fn args(&mut self, arguments: &PathArguments) -> Option<String> {
+        let PathArguments::AngleBracketed(bracketed) = arguments else {
+            return match arguments {
+                PathArguments::None => Some(String::new()),
+                _ if self.project.is_none() => Some(arguments.to_token_stream().to_string()),
+                _ => None,
+            };
+        };
+        let mut args = Vec::new();
+        for arg in &bracketed.args {
+            args.push(match arg {
+                GenericArgument::Type(ty) => self.ty(ty)?,
+                GenericArgument::Const(value) => expr_text(value),
+                // Lifetimes only come with borrows, which the client owns
+                _ if self.project.is_some() => return None,
+                GenericArgument::Lifetime(lifetime) => lifetime.to_string(),
+                _ => arg.to_token_stream().to_string(),
+            });
+        }
+        Some(format!("<{}>", args.join(", ")))
+    }
+}
+
+fn type_crate(name: &str) -> Option<&'static str> {
+    TYPE_CRATES
+        .iter()
+        .map(|(crate_name, _, _)| *crate_name)
+        .find(|crate_name| *crate_name == name)
+}
+
+/// `ty` as written, the way rustfmt lays it out.
+fn type_text(ty: &Type) -> String {
+    Writer::plain()
+        .ty(ty)
+        .unwrap_or_else(|| ty.to_token_stream().to_string())
+}
+
+/// `ty` without the parentheses and invisible groups around it.
+fn ungrouped(ty: &Type) -> &Type {
+    match ty {
+        Type::Paren(paren) => ungrouped(&paren.elem),
+        Type::Group(group) => ungrouped(&group.elem),
+        _ => ty,
+    }
+}
+
+/// A literal, like a discriminant or an array length, as written.
+fn expr_text(expr: &Expr) -> String {
+    match expr {
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => {
+            format!("-{}", expr_text(&unary.expr))
+        }
+        _ => expr.to_token_stream().to_string(),
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let path = entry.context("Failed to read the sources")?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Module of the file at `relative` to `src/`.
+fn module_of(relative: &Path) -> Vec<String> {
+    let mut module: Vec<String> = relative
+        .iter()
+        .map(|segment| {
+            segment
+                .to_string_lossy()
+                .trim_end_matches(".rs")
+                .to_string()
+        })
+        .collect();
+    if matches!(
+        module.last().map(String::as_str),
+        Some("mod" | "main" | "lib")
+    ) {
+        module.pop();
+    }
+    module
+}
+
+/// Whether `attrs` make a test or a module of tests.
+fn is_test(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("test")
+            || (attr.path().is_ident("cfg")
+                && attr
+                    .parse_args::<Meta>()
+                    .is_ok_and(|meta| meta.path().is_ident("test")))
+    })
+}
+
+/// Method and path of a handler with a route attribute like `#[get("/")]`.
+fn route_attribute(function: &Function) -> Option<(&'static str, String)> {
+    function.attrs.iter().find_map(|attr| {
+        let name = attr.path().segments.last()?.ident.to_string();
+        let method = METHODS.into_iter().find(|method| name == *method)?;
+        let args = attr
+            .parse_args_with(Punctuated::<Expr, Comma>::parse_terminated)
+            .ok()?;
+        Some((method, string_literal(args.first()?)?))
+    })
+}
+
+/// The value of a string literal.
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(literal),
+            ..
+        }) => Some(literal.value()),
+        _ => None,
+    }
+}
+
+/// Whether `body` is text: a string literal or `format!`.
+fn is_text(body: &Expr) -> bool {
+    match body {
+        Expr::Macro(call) => call.mac.path.is_ident("format"),
+        _ => string_literal(body).is_some(),
+    }
+}
+
+/// Prefix of a scope of actix-web, like `web::scope("/api")`.
+fn scope_prefix(expr: &Expr) -> Option<String> {
+    let Expr::Call(call) = expr else {
+        return None;
+    };
+    let Expr::Path(path) = &*call.func else {
+        return None;
+    };
+    if path.path.segments.last()?.ident != "scope" {
+        return None;
+    }
+    string_literal(call.args.first()?)
+}
+
+/// The expression a block ends with.
+fn tail_expr(block: &Block) -> Option<&Expr> {
+    match block.stmts.last()? {
+        Stmt::Expr(expr, None) => Some(expr),
+        _ => None,
+    }
+}
+
+/// `/{prefix}/{path}` without empty segments, `/` for none.
+fn join_paths(prefix: &str, path: &str) -> String {
+    let segments: Vec<&str> = prefix
+        .split('/')
+        .chain(path.split('/'))
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
+/// Names of the parameters of `path`, written `{id}`, `{*rest}`, `:id` or
+/// `*rest`.
+fn path_params(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter_map(param_name)
+        .map(|name| name.to_snake_case())
+        .collect()
+}
+
+/// Name of the parameter the path `segment` is, if any.
+pub fn param_name(segment: &str) -> Option<&str> {
+    let name = segment
+        .strip_prefix('{')
+        .and_then(|segment| segment.strip_suffix('}'))
+        .or_else(|| segment.strip_prefix(':'))
+        .or_else(|| segment.strip_prefix('*'))?;
+    Some(name.trim_start_matches('*'))
+}
+
+/// Segments of a call chain like `web::get().to(list)`: the name of each
+/// call with its arguments, from the first.
+fn call_chain(expr: &Expr) -> Vec<(String, Vec<&Expr>)> {
+    let mut segments = Vec::new();
+    let mut expr = expr;
+    loop {
+        match expr {
+            Expr::MethodCall(call) => {
+                segments.push((call.method.to_string(), call.args.iter().collect()));
+                expr = &call.receiver;
+            }
+            Expr::Call(call) => {
+                if let Expr::Path(path) = &*call.func
+                    && let Some(last) = path.path.segments.last()
+                {
+                    segments.push((last.ident.to_string(), call.args.iter().collect()));
+                }
+                break;
+            }
+            _ => break,
+        }
+    }
+    segments.reverse();
+    segments
+}
+
+/// Last segment of the path of `ty` and its first generic type:
+/// `web::Json<User>` is `Json` of `User`.
+fn outer_type(ty: &Type) -> Option<(String, Option<&Type>)> {
+    let Type::Path(path) = ungrouped(ty) else {
+        return None;
+    };
+    let last = path.path.segments.last()?;
+    let inner = match &last.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        }),
+        _ => None,
+    };
+    Some((last.ident.to_string(), inner))
+}
+
+/// What a closure answers: text when it ends with a string literal.
+fn closure_response(handler: &Expr) -> Response {
+    let mut tail = handler;
+    loop {
+        tail = match tail {
+            Expr::Closure(closure) => &closure.body,
+            Expr::Async(block) => match tail_expr(&block.block) {
+                Some(expr) => expr,
+                None => break,
+            },
+            Expr::Block(block) => match tail_expr(&block.block) {
+                Some(expr) => expr,
+                None => break,
+            },
+            _ => break,
+        };
+    }
+    let mut answers = Answers::default();
+    answers.visit_expr(handler);
+    if string_literal(tail).is_some() {
+        Response::Text
+    } else if !answers.json.is_empty() {
+        Response::Json("serde_json::Value".to_string())
+    } else {
+        Response::Empty
+    }
+}
+
+/// Names of the type parameters of `generics`, `None` when it has
+/// lifetimes or constants.
+fn type_params(generics: &Generics) -> Option<Vec<String>> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Type(param) => Some(param.ident.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_serde(attr: &Attribute) -> bool {
+    attr.path().is_ident("serde")
+}
+
+/// The doc comments of `attrs` as `///` lines, with their intra-doc links
+/// as plain code, the items they point to stay on the server.
+fn docs(attrs: &[Attribute], indent: &str) -> String {
+    let mut docs = String::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("doc")) {
+        let Meta::NameValue(doc) = &attr.meta else {
+            continue;
+        };
+        let Some(text) = string_literal(&doc.value) else {
+            continue;
+        };
+        for line in text.lines() {
+            docs.push_str(&format!("{}///{}\n", indent, without_doc_links(line)));
+        }
+    }
+    docs
+}
+
+/// The derive attribute of a copied type, keeping the derives of std
+/// among those of `attrs`.
+fn derive_attribute(attrs: &[Attribute]) -> String {
+    let mut kept = vec!["Debug", "Clone"];
+    let derives = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Path, Comma>::parse_terminated)
+                .ok()
+        })
+        .flatten();
+    for derive in derives {
+        let Some(derive) = derive
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+        else {
+            continue;
+        };
+        if let Some(std_derive) = KEPT_DERIVES.iter().find(|kept| **kept == derive)
+            && !kept.contains(std_derive)
+        {
+            kept.push(std_derive);
+        }
+    }
+    kept.extend(["Serialize", "Deserialize"]);
+    format!("#[derive({})]", kept.join(", "))
+}
+
+/// The serde attributes of `attrs` as lines, without their
+/// `default = "path"`. `None` when one names code of the server.
+fn serde_attributes(attrs: &[Attribute], indent: &str) -> Option<String> {
+    let mut lines = String::new();
+    for attr in attrs.iter().filter(|attr| is_serde(attr)) {
+        if let Some(attribute) = serde_attribute(attr)? {
+            lines.push_str(&format!("{}{}\n", indent, attribute));
+        }
+    }
+    Some(lines)
+}
+
+/// The serde attribute `attr` without its `default = "path"`: `None` when
+/// it names code of the server, `Some(None)` when nothing is left of it.
+fn serde_attribute(attr: &Attribute) -> Option<Option<String>> {
+    let args = attr
+        .parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)
+        .ok()?;
+    let mut kept = Vec::new();
+    for arg in &args {
+        let name = arg.path().get_ident().map(ToString::to_string);
+        match (name.as_deref(), arg) {
+            (Some(name), _) if SERDE_PATHS.contains(&name) => return None,
+            // Defaults of the server are left out, the server sends every
+            // field and the client too
+            (Some("default"), Meta::NameValue(_)) => {}
+            _ => kept.push(meta_text(arg)),
+        }
+    }
+    Some((!kept.is_empty()).then(|| format!("#[serde({})]", kept.join(", "))))
+}
+
+/// An argument of an attribute as written.
+fn meta_text(meta: &Meta) -> String {
+    let path = meta.path().to_token_stream().to_string().replace(' ', "");
+    match meta {
+        Meta::Path(_) => path,
+        Meta::NameValue(value) => format!("{} = {}", path, expr_text(&value.value)),
+        Meta::List(list) => match list.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)
+        {
+            Ok(args) => {
+                let args: Vec<String> = args.iter().map(meta_text).collect();
+                format!("{}({})", path, args.join(", "))
+            }
+            Err(_) => format!("{}({})", path, list.tokens),
+        },
+    }
+}
+
+/// A named field of a copied struct or variant with its docs and serde
+/// attributes, public when `public`.
+fn named_field(field: &Field, indent: &str, public: bool, writer: &mut Writer) -> Option<String> {
+    let mut text = docs(&field.attrs, indent);
+    text.push_str(&serde_attributes(&field.attrs, indent)?);
+    let visibility = if public { "pub " } else { "" };
+    text.push_str(&format!(
+        "{}{}{}: {},\n",
+        indent,
+        visibility,
+        field.ident.as_ref()?,
+        writer.ty(&field.ty)?
+    ));
+    Some(text)
+}
+
+/// The fields of a copied tuple struct or variant, public when `public`.
+fn tuple_fields(
+    fields: &Punctuated<Field, Comma>,
+    public: bool,
+    writer: &mut Writer,
+) -> Option<String> {
+    let visibility = if public { "pub " } else { "" };
+    let fields = fields
+        .iter()
+        .map(|field| {
+            let attributes = serde_attributes(&field.attrs, "")?.replace('\n', " ");
+            Some(format!(
+                "{}{}{}",
+                attributes,
+                visibility,
+                writer.ty(&field.ty)?
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(fields.join(", "))
+}
+
+/// The doc comment `line` with its intra-doc links as plain code.
+fn without_doc_links(line: &str) -> String {
+    let mut plain = String::new();
+    let mut rest = line;
+    while let Some((before, after)) = rest.split_once("[`") {
+        let link = after
+            .split_once("`]")
+            .filter(|(_, next)| !next.starts_with(['(', '[']));
+        let Some((code, next)) = link else {
+            plain.push_str(before);
+            plain.push_str("[`");
+            rest = after;
+            continue;
+        };
+        plain.push_str(before);
+        plain.push('`');
+        plain.push_str(code);
+        plain.push('`');
+        rest = next;
+    }
+    plain.push_str(rest);
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(sources: &[(&str, &str)]) -> Project {
+        Project::from_sources(
+            sources
+                .iter()
+                .map(|(path, text)| (PathBuf::from(path), text.to_string()))
+                .collect(),
+        )
+    }
+
+    fn routes(endpoints: &[Endpoint]) -> Vec<(&str, &str)> {
+        endpoints
+            .iter()
+            .map(|endpoint| (endpoint.method, endpoint.path.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn follows_axum_routers_across_modules() {
+        let project = project(&[
+            (
+                "main.rs",
+                r#"
+mod routes;
+
+fn app() -> Router {
+    Router::new()
+        .route("/", get(|| async { "Hello" }))
+        .nest("/users", routes::users::router())
+        .nest("/api", Router::new().route("/health", get(routes::health)))
+}
+"#,
+            ),
+            (
+                "routes/mod.rs",
+                r#"
+pub mod users;
+
+pub async fn health() -> StatusCode {
+    StatusCode::OK
+}
+"#,
+            ),
+            (
+                "routes/users.rs",
+                r#"
+pub fn router() -> Router {
+    Router::new()
+        .route("/", get(list).post(create))
+        .route("/{id}/posts/{slug}", get(post))
+}
+
+async fn list(Query(filter): Query<Filter>) -> Json<Vec<User>> {
+    todo!()
+}
+
+async fn create(Json(user): Json<NewUser>) -> (StatusCode, Json<User>) {
+    todo!()
+}
+
+async fn post(Path((id, slug)): Path<(i64, String)>) -> Result<String, AppError> {
+    todo!()
+}
+"#,
+            ),
+        ]);
+        let endpoints = project.endpoints("axum");
+        assert_eq!(
+            routes(&endpoints),
+            [
+                ("get", "/"),
+                ("get", "/users"),
+                ("post", "/users"),
+                ("get", "/users/{id}/posts/{slug}"),
+                ("get", "/api/health"),
+            ]
+        );
+
+        assert_eq!(endpoints[0].handler, None);
+        assert_eq!(endpoints[0].response, Response::Text);
+        assert_eq!(endpoints[1].handler.as_deref(), Some("list"));
+        assert_eq!(endpoints[1].module.as_deref(), Some("users"));
+        assert_eq!(endpoints[1].query.as_deref(), Some("Filter"));
+        assert_eq!(
+            endpoints[1].response,
+            Response::Json("Vec<User>".to_string())
+        );
+        assert_eq!(endpoints[2].body, Body::Json("NewUser".to_string()));
+        assert_eq!(endpoints[2].response, Response::Json("User".to_string()));
+        assert_eq!(
+            endpoints[3].params,
+            [
+                Param {
+                    name: "id".to_string(),
+                    rust_type: Some("i64".to_string()),
+                },
+                Param {
+                    name: "slug".to_string(),
+                    rust_type: Some("String".to_string()),
+                },
+            ]
+        );
+        assert_eq!(endpoints[3].response, Response::Text);
+        assert_eq!(endpoints[4].response, Response::Empty);
+    }
+
+    #[test]
+    fn is_not_confused_by_literals_lifetimes_and_tests() {
+        let project = project(&[(
+            "main.rs",
+            r##"
+fn app() -> Router {
+    let _ = ('\'', '{', b'}', br"{(", r#"")}"#, "\"{");
+    Router::new().route("/echo/{word}", get(echo))
+}
+
+async fn echo<'a>(Path(word): Path<String>) -> &'static str {
+    let _: &'a str = r"}";
+    "}"
+}
+
+#[cfg(test)]
+mod tests {
+    fn app() -> Router {
+        Router::new().route("/only-in-tests", get(echo))
+    }
+}
+"##,
+        )]);
+        let endpoints = project.endpoints("axum");
+        assert_eq!(routes(&endpoints), [("get", "/echo/{word}")]);
+        assert_eq!(endpoints[0].response, Response::Text);
+        assert_eq!(endpoints[0].params[0].rust_type.as_deref(), Some("String"));
+    }
+
+    #[test]
+    fn leaves_path_types_it_cannot_match_untyped() {
+        let project = project(&[(
+            "main.rs",
+            r#"
+fn app() -> Router {
+    Router::new()
+        .route("/{a}/{b}", get(unit))
+        .route("/{id}", get(named))
+}
+
+async fn unit(Path(()): Path<()>) {}
+
+async fn named(Path(params): Path<(u32, u32)>) {}
+"#,
+        )]);
+        let endpoints = project.endpoints("axum");
+        assert!(
+            endpoints[0]
+                .params
+                .iter()
+                .all(|param| param.rust_type.is_none())
+        );
+        assert_eq!(
+            endpoints[1].params[0].rust_type.as_deref(),
+            Some("(u32, u32)")
+        );
+        assert_eq!(endpoints[0].response, Response::Empty);
+    }
+
+    #[test]
+    fn follows_actix_scopes_services_and_configure() {
+        let project = project(&[
+            (
+                "main.rs",
+                r#"
+mod users;
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    HttpServer::new(|| {
+        App::new()
+            .service(index)
+            .service(web::scope("/api").configure(users::config))
+    })
+    .bind(("127.0.0.1", 8080))?
+    .run()
+    .await
+}
+
+#[get("/")]
+async fn index() -> impl Responder {
+    HttpResponse::Ok().body("Hello")
+}
+"#,
+            ),
+            (
+                "users.rs",
+                r#"
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/users")
+            .route("", web::get().to(list))
+            .route("/{id}", web::delete().to(remove)),
+    );
+}
+
+async fn list() -> impl Responder {
+    HttpResponse::Ok().json(User { id: 1 })
+}
+
+async fn remove(id: web::Path<u64>) -> impl Responder {
+    HttpResponse::NoContent().finish()
+}
+"#,
+            ),
+        ]);
+        let endpoints = project.endpoints("actix");
+        assert_eq!(
+            routes(&endpoints),
+            [
+                ("get", "/"),
+                ("get", "/api/users"),
+                ("delete", "/api/users/{id}"),
+            ]
+        );
+        assert_eq!(endpoints[0].response, Response::Text);
+        // User is no type of the project, its JSON is any JSON
+        assert_eq!(
+            endpoints[1].response,
+            Response::Json("serde_json::Value".to_string())
+        );
+        assert_eq!(endpoints[2].params[0].rust_type.as_deref(), Some("u64"));
+        assert_eq!(endpoints[2].response, Response::Empty);
+    }
+
+    #[test]
+    fn copies_the_types_an_endpoint_uses() {
+        let project = project(&[
+            ("main.rs", "mod models;"),
+            (
+                "models.rs",
+                r#"
+/// A user, see [`Role`].
+#[derive(Debug, Serialize, Deserialize, PartialEq, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    /// Id of the user.
+    pub id: uuid::Uuid,
+    #[sqlx(rename = "user_name")]
+    #[serde(default = "default_name", rename = "name")]
+    name: String,
+    created_at: DateTime<Utc>,
+    role: Role,
+    tags: HashMap<String, Vec<Tag>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind")]
+enum Role {
+    Admin,
+    Member { since: i32 },
+    #[serde(rename = "guest")]
+    Guest(Option<String>),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Tag(&'static str);
+"#,
+            ),
+        ]);
+        let mut types = Types::default();
+        assert_eq!(
+            project
+                .client_type("Vec<models::User>", &mut types)
+                .as_deref(),
+            Some("Vec<User>")
+        );
+        assert_eq!(
+            types.items,
+            [
+                r#"/// A user, see `Role`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    /// Id of the user.
+    pub id: uuid::Uuid,
+    #[serde(rename = "name")]
+    pub name: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub role: Role,
+    pub tags: std::collections::HashMap<String, Vec<Tag>>,
+}"#,
+                r#"#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Role {
+    Admin,
+    Member { since: i32 },
+    #[serde(rename = "guest")]
+    Guest(Option<String>),
+}"#,
+                r#"#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag(pub String);"#,
+            ]
+        );
+        assert_eq!(types.crates, BTreeSet::from(["chrono", "uuid"]));
+    }
+
+    #[test]
+    fn refuses_types_it_cannot_copy() {
+        let project = project(&[(
+            "main.rs",
+            r#"
+#[derive(Serialize)]
+struct Borrowed<'a> {
+    name: &'a str,
+}
+
+#[derive(Serialize)]
+struct Custom {
+    #[serde(with = "crate::dates")]
+    at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct Outer {
+    inner: Custom,
+}
+"#,
+        )]);
+        let mut types = Types::default();
+        assert_eq!(project.client_type("Borrowed", &mut types), None);
+        assert_eq!(project.client_type("Outer", &mut types), None);
+        assert_eq!(project.client_type("Box<dyn Error>", &mut types), None);
+        assert_eq!(project.client_type("other_crate::Thing", &mut types), None);
+        assert!(types.items.is_empty());
+        assert_eq!(
+            project.client_type("(i32, &str)", &mut types).as_deref(),
+            Some("(i32, String)")
+        );
+    }
+
+    #[test]
+    fn names_path_parameters() {
+        assert_eq!(
+            path_params("/users/{user_id}/files/{*path}"),
+            ["user_id", "path"]
+        );
+        assert_eq!(path_params("/users/:userId/*rest"), ["user_id", "rest"]);
+        assert_eq!(join_paths("/api/", "/"), "/api");
+        assert_eq!(join_paths("", ""), "/");
+    }
+}
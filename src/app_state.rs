@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+
+use minijinja::context;
+
+use crate::{deps::DependencySpec, error::Result, templates};
+
+/// Name of the built-in `--with` option.
+pub const WITH: &str = "app-state";
+
+/// Module building the state of the handlers, declared in main.rs.
+pub const MODULES: [&str; 1] = ["app_state"];
+
+/// Whether the `--deps` of a project bring Redis, which the state then
+/// keeps a pool of.
+pub fn has_redis(deps: &[String]) -> bool {
+    deps.iter()
+        .any(|dep| DependencySpec::parse(dep).name == "redis")
+}
+
+/// `src/app_state/mod.rs`: the config, the pools and the HTTP client in
+/// one `AppState`, and the readiness handler checking them.
+pub fn file(context: &templates::TemplateContext) -> Result<(PathBuf, String)> {
+    Ok((
+        Path::new("src").join(MODULES[0]).join("mod.rs"),
+        templates::render_with(
+            "app_state/mod.rs",
+            context,
+            context! { redis => has_redis(context.features) },
+        )?,
+    ))
+}
+
+/// Route of the readiness handler.
+pub fn route(framework: &str) -> &'static str {
+    match framework {
+        "axum" => r#".route("/ready", axum::routing::get(crate::app_state::ready))"#,
+        _ => r#".route("/ready", actix_web::web::get().to(crate::app_state::ready))"#,
+    }
+}
+
+/// `content` of the router module of an axum preset with its router taking
+/// the state main.rs hands it.
+pub fn stateful_router(content: &str) -> String {
+    let content = content.replace(
+        "pub fn router() -> Router {",
+        "pub fn router() -> Router<AppState> {",
+    );
+    match content.find("use crate::") {
+        Some(at) => format!(
+            "{}use crate::app_state::AppState;\n{}",
+            &content[..at],
+            &content[at..]
+        ),
+        None => content.replacen("\n\n", "\n\nuse crate::app_state::AppState;\n\n", 1),
+    }
+}
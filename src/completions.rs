@@ -0,0 +1,96 @@
+use clap::CommandFactory;
+use clap_complete::{
+    CompletionCandidate,
+    env::{CompleteEnv, Shells},
+};
+use std::io::{self, Write};
+
+use crate::{
+    Cli, config,
+    error::{Context, Error, Result},
+    plugin, registry, scaffold, templates,
+};
+
+/// Environment variable the registration scripts call forgeit with.
+const VAR: &str = "COMPLETE";
+
+/// Answers the completion requests of the registration scripts, exiting when
+/// this run was one.
+pub fn complete() {
+    CompleteEnv::with_factory(Cli::command).var(VAR).complete();
+}
+
+/// Frameworks for `--framework`, looked up while completing so user and
+/// installed templates show up as soon as they exist.
+pub fn frameworks() -> Vec<CompletionCandidate> {
+    templates::FRAMEWORKS
+        .iter()
+        .map(|framework| CompletionCandidate::new(framework.name))
+        .chain(templates::user_frameworks().into_iter().map(|framework| {
+            CompletionCandidate::new(framework).help(Some("user template".into()))
+        }))
+        .chain(registry::installed_templates().into_iter().map(|template| {
+            CompletionCandidate::new(template).help(Some("installed template".into()))
+        }))
+        .chain([CompletionCandidate::new(scaffold::BARE_FRAMEWORK)
+            .help(Some("project without a framework".into()))])
+        .collect()
+}
+
+/// Presets of the user config for `--preset`.
+pub fn presets() -> Vec<CompletionCandidate> {
+    config::load()
+        .map(|config| {
+            config
+                .presets
+                .into_keys()
+                .map(CompletionCandidate::new)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Built-in and configured stacks for `--stack`.
+pub fn stacks() -> Vec<CompletionCandidate> {
+    let config = config::load().unwrap_or_default();
+    config
+        .stack_names()
+        .into_iter()
+        .map(|name| {
+            let about = config::STACKS
+                .iter()
+                .find(|stack| stack.name == name && !config.stacks.contains_key(&name))
+                .map(|stack| stack.about);
+            CompletionCandidate::new(name).help(about.map(Into::into))
+        })
+        .collect()
+}
+
+/// Plugin options for `--with`.
+pub fn with_options() -> Vec<CompletionCandidate> {
+    let builtin = scaffold::BUILTIN_WITH
+        .into_iter()
+        .map(|(name, about)| CompletionCandidate::new(name).help(Some(about.into())));
+    let plugins = plugin::discover()
+        .iter()
+        .filter_map(|plugin| plugin.describe().ok())
+        .flat_map(|description| description.with)
+        .map(|option| CompletionCandidate::new(option.name).help(Some(option.about.into())))
+        .collect::<Vec<_>>();
+    builtin.chain(plugins).collect()
+}
+
+/// Prints the script registering the completions of `shell`.
+pub fn print(shell: &str) -> Result<()> {
+    let shells = Shells::builtins();
+    let Some(completer) = shells.completer(shell) else {
+        return Err(Error::user(format!("Unknown shell '{}'", shell)));
+    };
+    let mut script = Vec::new();
+    completer
+        .write_registration(VAR, "forgeit", "forgeit", "forgeit", &mut script)
+        .context("Failed to generate the completion script")?;
+    io::stdout()
+        .write_all(&script)
+        .context("Failed to print the completion script")
+}
@@ -0,0 +1,448 @@
+use clap::ValueEnum;
+use std::{collections::BTreeMap, env, fs, path::PathBuf};
+use toml_edit::{DocumentMut, Table};
+
+use crate::{
+    error::{Error, Result},
+    license::{self, License},
+    lint_config::{self, LintConfig},
+    suggest,
+    templates::{self, TaskRunner},
+};
+
+const FILE: &str = "config.toml";
+
+const DATABASES: [&str; 3] = ["postgres", "mysql", "sqlite"];
+
+/// Directory of the user configuration, `~/.config/rust-backend-scaffolder/`
+/// (or below `$XDG_CONFIG_HOME` when set).
+pub fn dir() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".config")))?;
+    Some(config_dir.join("rust-backend-scaffolder"))
+}
+
+fn path() -> Option<PathBuf> {
+    dir().map(|dir| dir.join(FILE))
+}
+
+/// Scaffold choices of the user config, set at the top level for every
+/// scaffold or in a `[preset.<name>]` table picked with `--preset`.
+#[derive(Debug, Default, Clone)]
+pub struct Settings {
+    pub framework: Option<String>,
+    pub arch: Option<String>,
+    pub database: Option<String>,
+    pub deps: Vec<String>,
+    pub with: Vec<String>,
+    pub docker: bool,
+    pub template: Option<String>,
+    pub branch: Option<String>,
+    pub license: Vec<License>,
+    /// Name in the copyright line of the license files.
+    pub copyright_holder: Option<String>,
+    pub edition: Option<String>,
+    pub msrv: Option<String>,
+    pub toolchain: Option<String>,
+    pub task_runner: Option<TaskRunner>,
+    /// `Name <email>` to configure in the repository of new projects.
+    pub git_author: Option<String>,
+    pub pre_hooks: Vec<String>,
+    pub post_hooks: Vec<String>,
+}
+
+impl Settings {
+    /// These settings with the ones of `preset` on top: its values replace
+    /// single values and extend lists.
+    fn with_preset(&self, preset: &Settings) -> Settings {
+        let join = |ours: &[String], theirs: &[String]| {
+            let mut values = ours.to_vec();
+            values.extend(theirs.iter().filter(|value| !ours.contains(value)).cloned());
+            values
+        };
+        Settings {
+            framework: preset.framework.clone().or(self.framework.clone()),
+            arch: preset.arch.clone().or(self.arch.clone()),
+            database: preset.database.clone().or(self.database.clone()),
+            deps: join(&self.deps, &preset.deps),
+            with: join(&self.with, &preset.with),
+            docker: self.docker || preset.docker,
+            template: preset.template.clone().or(self.template.clone()),
+            branch: preset.branch.clone().or(self.branch.clone()),
+            license: if preset.license.is_empty() {
+                self.license.clone()
+            } else {
+                preset.license.clone()
+            },
+            copyright_holder: preset
+                .copyright_holder
+                .clone()
+                .or(self.copyright_holder.clone()),
+            edition: preset.edition.clone().or(self.edition.clone()),
+            msrv: preset.msrv.clone().or(self.msrv.clone()),
+            toolchain: preset.toolchain.clone().or(self.toolchain.clone()),
+            task_runner: preset.task_runner.or(self.task_runner),
+            git_author: preset.git_author.clone().or(self.git_author.clone()),
+            pre_hooks: join(&self.pre_hooks, &preset.pre_hooks),
+            post_hooks: join(&self.post_hooks, &preset.post_hooks),
+        }
+    }
+}
+
+/// A stack of `--stack`: the database, cache and authentication crates a
+/// common architecture is scaffolded with.
+pub struct Stack {
+    pub name: &'static str,
+    pub about: &'static str,
+    pub database: Option<&'static str>,
+    pub deps: &'static [&'static str],
+}
+
+/// Crates of authentication with JSON web tokens and hashed passwords.
+const JWT: [&str; 2] = ["jsonwebtoken", "argon2"];
+
+/// Stacks `--stack` takes without a `[stack.<name>]` table in the config.
+pub const STACKS: [Stack; 5] = [
+    Stack {
+        name: "postgres-redis-jwt",
+        about: "PostgreSQL, a Redis cache and JWT authentication",
+        database: Some("postgres"),
+        deps: &["redis", JWT[0], JWT[1]],
+    },
+    Stack {
+        name: "postgres-jwt",
+        about: "PostgreSQL and JWT authentication",
+        database: Some("postgres"),
+        deps: &JWT,
+    },
+    Stack {
+        name: "mysql-redis",
+        about: "MySQL and a Redis cache",
+        database: Some("mysql"),
+        deps: &["redis"],
+    },
+    Stack {
+        name: "sqlite-jwt",
+        about: "SQLite and JWT authentication",
+        database: Some("sqlite"),
+        deps: &JWT,
+    },
+    Stack {
+        name: "sqlite-minimal",
+        about: "SQLite and nothing else",
+        database: Some("sqlite"),
+        deps: &[],
+    },
+];
+
+impl Stack {
+    fn settings(&self) -> Settings {
+        Settings {
+            database: self.database.map(str::to_string),
+            deps: self.deps.iter().map(|dep| dep.to_string()).collect(),
+            ..Settings::default()
+        }
+    }
+}
+
+/// Keys a `[stack.<name>]` table can set, the rest belongs in a preset.
+const STACK_KEYS: [&str; 4] = ["database", "deps", "with", "docker"];
+
+/// The parsed user config.
+#[derive(Debug, Default)]
+pub struct Config {
+    pub defaults: Settings,
+    pub presets: BTreeMap<String, Settings>,
+    /// `[stack.<name>]` tables, which replace a built-in stack of their name.
+    pub stacks: BTreeMap<String, Settings>,
+    /// What `--with lint-config` writes.
+    pub lint_config: LintConfig,
+}
+
+impl Config {
+    /// Settings of a scaffold, the top-level ones with `preset` and then
+    /// `stack` applied.
+    pub fn settings(&self, preset: Option<&str>, stack: Option<&str>) -> Result<Settings> {
+        let settings = self.preset_settings(preset)?;
+        match stack {
+            Some(name) => Ok(settings.with_preset(&self.stack(name)?)),
+            None => Ok(settings),
+        }
+    }
+
+    /// Names of the built-in and configured stacks, each once.
+    pub fn stack_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = STACKS.iter().map(|stack| stack.name.to_string()).collect();
+        for name in self.stacks.keys() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    fn stack(&self, name: &str) -> Result<Settings> {
+        if let Some(stack) = self.stacks.get(name) {
+            return Ok(stack.clone());
+        }
+        if let Some(stack) = STACKS.iter().find(|stack| stack.name == name) {
+            return Ok(stack.settings());
+        }
+        let names = self.stack_names();
+        let similar = suggest::similar(name, names.iter().map(String::as_str));
+        let hint = match similar.first() {
+            Some(similar) => format!("Did you mean {}? ", similar),
+            None => String::new(),
+        };
+        Err(Error::user(format!(
+            "Unknown stack '{}'\n👉 {}Stacks: {}, or add a [stack.{}] table to {}",
+            name,
+            hint,
+            names.join(", "),
+            name,
+            display_path()
+        )))
+    }
+
+    fn preset_settings(&self, preset: Option<&str>) -> Result<Settings> {
+        let Some(name) = preset else {
+            return Ok(self.defaults.clone());
+        };
+        match self.presets.get(name) {
+            Some(preset) => Ok(self.defaults.with_preset(preset)),
+            None if self.presets.is_empty() => Err(Error::user(format!(
+                "Unknown preset '{}'\n👉 Add a [preset.{}] table to {}",
+                name,
+                name,
+                display_path()
+            ))),
+            None => Err(Error::user(format!(
+                "Unknown preset '{}'\n👉 Presets in {}: {}",
+                name,
+                display_path(),
+                self.presets.keys().cloned().collect::<Vec<_>>().join(", ")
+            ))),
+        }
+    }
+}
+
+fn display_path() -> String {
+    path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| format!("~/.config/rust-backend-scaffolder/{}", FILE))
+}
+
+/// Reads the user config, an empty one when there is none.
+pub fn load() -> Result<Config> {
+    let Some(content) = path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Ok(Config::default());
+    };
+    let document: DocumentMut = content
+        .parse()
+        .map_err(|err| Error::user(format!("Failed to parse {}: {}", display_path(), err)))?;
+
+    let mut presets = BTreeMap::new();
+    if let Some(item) = document.get("preset") {
+        let Some(tables) = item.as_table() else {
+            return Err(invalid("preset", "a table of [preset.<name>] tables"));
+        };
+        for (name, preset) in tables.iter() {
+            let Some(preset) = preset.as_table() else {
+                return Err(invalid(
+                    &format!("preset.{}", name),
+                    "a [preset.<name>] table",
+                ));
+            };
+            presets.insert(
+                name.to_string(),
+                parse_settings(preset, &format!("preset.{}.", name))?,
+            );
+        }
+    }
+
+    let mut stacks = BTreeMap::new();
+    if let Some(item) = document.get("stack") {
+        let Some(tables) = item.as_table() else {
+            return Err(invalid("stack", "a table of [stack.<name>] tables"));
+        };
+        for (name, stack) in tables.iter() {
+            let Some(stack) = stack.as_table() else {
+                return Err(invalid(
+                    &format!("stack.{}", name),
+                    "a [stack.<name>] table",
+                ));
+            };
+            if let Some((key, _)) = stack.iter().find(|(key, _)| !STACK_KEYS.contains(key)) {
+                return Err(Error::user(format!(
+                    "'stack.{}.{}' in {} is not a stack key\n👉 Stacks only set {}, put the rest in a [preset.<name>] table",
+                    name,
+                    key,
+                    display_path(),
+                    suggest::either(&STACK_KEYS)
+                )));
+            }
+            stacks.insert(
+                name.to_string(),
+                parse_settings(stack, &format!("stack.{}.", name))?,
+            );
+        }
+    }
+
+    let mut lint_config = LintConfig::default();
+    if let Some(item) = document.get(lint_config::WITH) {
+        let Some(table) = item.as_table() else {
+            return Err(invalid(lint_config::WITH, "a [lint-config] table"));
+        };
+        lint_config = lint_config.merge(table, invalid)?;
+    }
+
+    Ok(Config {
+        defaults: parse_settings(document.as_table(), "")?,
+        presets,
+        stacks,
+        lint_config,
+    })
+}
+
+fn invalid(key: &str, expected: &str) -> Error {
+    Error::user(format!(
+        "'{}' in {} must be {}",
+        key,
+        display_path(),
+        expected
+    ))
+}
+
+/// Parses the settings of `table`, `prefix` naming the table in errors.
+fn parse_settings(table: &Table, prefix: &str) -> Result<Settings> {
+    let text = |key: &str| -> Result<Option<String>> {
+        match table.get(key) {
+            None => Ok(None),
+            Some(item) => item
+                .as_str()
+                .map(|text| Some(text.to_string()))
+                .ok_or_else(|| invalid(&format!("{}{}", prefix, key), "a string")),
+        }
+    };
+    let list = |key: &str| -> Result<Vec<String>> {
+        let Some(item) = table.get(key) else {
+            return Ok(Vec::new());
+        };
+        item.as_array()
+            .and_then(|values| {
+                values
+                    .iter()
+                    .map(|value| value.as_str().map(str::to_string))
+                    .collect()
+            })
+            .ok_or_else(|| invalid(&format!("{}{}", prefix, key), "an array of strings"))
+    };
+
+    // Typos would otherwise be ignored without a word
+    for (key, _) in table.iter() {
+        let known = matches!(
+            key,
+            "framework"
+                | "arch"
+                | "database"
+                | "deps"
+                | "with"
+                | "docker"
+                | "template"
+                | "branch"
+                | "license"
+                | "copyright-holder"
+                | "edition"
+                | "msrv"
+                | "toolchain"
+                | "task-runner"
+                | "git-author"
+                | "pre-hooks"
+                | "post-hooks"
+        ) || (prefix.is_empty()
+            && matches!(key, "preset" | "stack" | lint_config::WITH));
+        if !known {
+            return Err(Error::user(format!(
+                "Unknown key '{}{}' in {}",
+                prefix,
+                key,
+                display_path()
+            )));
+        }
+    }
+
+    let settings =
+        Settings {
+            framework: text("framework")?,
+            arch: text("arch")?,
+            database: text("database")?,
+            deps: list("deps")?,
+            with: list("with")?,
+            docker: match table.get("docker") {
+                None => false,
+                Some(item) => item
+                    .as_bool()
+                    .ok_or_else(|| invalid(&format!("{}docker", prefix), "true or false"))?,
+            },
+            template: text("template")?,
+            branch: text("branch")?,
+            license: match text("license")? {
+                None => Vec::new(),
+                Some(licenses) => license::parse(&licenses).ok_or_else(|| {
+                    invalid(
+                        &format!("{}license", prefix),
+                        "mit, apache-2.0, mpl-2.0 or proprietary, or several joined with OR",
+                    )
+                })?,
+            },
+            copyright_holder: text("copyright-holder")?,
+            edition: text("edition")?,
+            msrv: text("msrv")?,
+            toolchain: text("toolchain")?,
+            task_runner: match text("task-runner")? {
+                None => None,
+                Some(runner) => Some(TaskRunner::from_str(&runner, false).map_err(|_| {
+                    invalid(&format!("{}task-runner", prefix), "just, make or none")
+                })?),
+            },
+            git_author: text("git-author")?,
+            pre_hooks: list("pre-hooks")?,
+            post_hooks: list("post-hooks")?,
+        };
+
+    if let Some(arch) = &settings.arch
+        && !templates::ARCHITECTURES.contains(&arch.as_str())
+    {
+        return Err(invalid(
+            &format!("{}arch", prefix),
+            &format!("one of {}", templates::ARCHITECTURES.join(", ")),
+        ));
+    }
+    if let Some(database) = &settings.database
+        && !DATABASES.contains(&database.as_str())
+    {
+        return Err(invalid(
+            &format!("{}database", prefix),
+            &format!("one of {}", DATABASES.join(", ")),
+        ));
+    }
+    if let Some(author) = &settings.git_author
+        && parse_author(author).is_none()
+    {
+        return Err(invalid(
+            &format!("{}git-author", prefix),
+            "like \"Jane Doe <jane@example.com>\"",
+        ));
+    }
+    Ok(settings)
+}
+
+/// Name and email of a `Name <email>` author.
+pub fn parse_author(author: &str) -> Option<(&str, &str)> {
+    let (name, email) = author.split_once('<')?;
+    let name = name.trim();
+    let email = email.trim().strip_suffix('>')?.trim();
+    (!name.is_empty() && !email.is_empty()).then_some((name, email))
+}
@@ -0,0 +1,294 @@
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    sync::OnceLock,
+    thread,
+};
+
+use crate::{
+    error::{Error, Result},
+    network, suggest,
+};
+
+/// A published version of a crate, one line of its sparse index file.
+#[derive(Debug, Deserialize)]
+struct IndexLine {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+    /// Features using the `dep:` syntax, kept apart for older cargos.
+    #[serde(default)]
+    features2: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    deps: Vec<IndexDependency>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexDependency {
+    name: String,
+    #[serde(default)]
+    optional: bool,
+}
+
+/// A version of a crate that is not yanked.
+#[derive(Debug)]
+pub struct Release {
+    pub version: Version,
+    /// Features it can be added with, optional dependencies included.
+    pub features: BTreeSet<String>,
+}
+
+impl IndexLine {
+    fn release(self) -> Option<Release> {
+        let version = Version::parse(&self.vers).ok()?;
+        let features = self
+            .features
+            .into_iter()
+            .chain(self.features2)
+            .collect::<Vec<_>>();
+        // An optional dependency is a feature of its own unless a feature
+        // enables it with `dep:`
+        let explicit: BTreeSet<&str> = features
+            .iter()
+            .flat_map(|(_, enables)| enables)
+            .filter_map(|enabled| enabled.strip_prefix("dep:"))
+            .collect();
+        let implicit: Vec<String> = self
+            .deps
+            .iter()
+            .filter(|dependency| {
+                dependency.optional && !explicit.contains(dependency.name.as_str())
+            })
+            .map(|dependency| dependency.name.clone())
+            .collect();
+        Some(Release {
+            version,
+            features: features
+                .into_iter()
+                .map(|(name, _)| name)
+                .chain(implicit)
+                .collect(),
+        })
+    }
+}
+
+/// Root of the crates.io sparse index, `FORGEIT_CRATES_INDEX` for a mirror
+/// serving the same files.
+fn index_url() -> String {
+    env::var("FORGEIT_CRATES_INDEX")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| "https://index.crates.io".to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Path of a crate's file in the index, e.g. `se/rd/serde` or `3/s/syn`.
+fn index_path(name: &str) -> String {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{}", name),
+        2 => format!("2/{}", name),
+        3 => format!("3/{}/{}", &name[..1], name),
+        _ => format!("{}/{}/{}", &name[..2], &name[2..4], name),
+    }
+}
+
+/// Agent shared by every index request, so concurrent lookups reuse its
+/// connections.
+fn agent() -> &'static ureq::Agent {
+    static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+    AGENT.get_or_init(|| {
+        ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .into()
+    })
+}
+
+/// Releases of every crate of `names`, looked up concurrently.
+pub fn releases_of(names: &[&str]) -> Result<Vec<Vec<Release>>> {
+    releases_each(names).into_iter().collect()
+}
+
+/// Like [`releases_of`], keeping the failure of each lookup to itself.
+pub fn releases_each(names: &[&str]) -> Vec<Result<Vec<Release>>> {
+    thread::scope(|scope| {
+        let lookups: Vec<_> = names
+            .iter()
+            .map(|name| scope.spawn(move || releases(name)))
+            .collect();
+        lookups
+            .into_iter()
+            .map(|lookup| {
+                lookup.join().unwrap_or_else(|_| {
+                    Err(Error::Internal("The index lookup panicked".to_string()))
+                })
+            })
+            .collect()
+    })
+}
+
+/// Releases of `name`, oldest first.
+pub fn releases(name: &str) -> Result<Vec<Release>> {
+    network::require(&format!("Looking up {} on crates.io", name))?;
+    let url = format!("{}/{}", index_url(), index_path(name));
+    tracing::debug!("fetching {}", url);
+    let mut response = agent()
+        .get(&url)
+        .header("User-Agent", concat!("forgeit/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .map_err(|err| Error::environment(format!("Failed to reach {}: {}", url, err)))?;
+    if response.status() == 404 {
+        let similar = similar(name);
+        let hint = match similar.as_slice() {
+            [] => String::new(),
+            names => format!(
+                "\n👉 Did you mean {}?",
+                suggest::either(&names.iter().map(String::as_str).collect::<Vec<_>>())
+            ),
+        };
+        return Err(Error::user(format!(
+            "There is no crate '{}' on crates.io{}",
+            name, hint
+        )));
+    }
+    if !response.status().is_success() {
+        return Err(Error::environment(format!(
+            "{} answered {}",
+            url,
+            response.status()
+        )));
+    }
+    let body = response
+        .body_mut()
+        .with_config()
+        .limit(64 * 1024 * 1024)
+        .read_to_string()
+        .map_err(|err| Error::environment(format!("Failed to read {}: {}", url, err)))?;
+    let mut releases: Vec<Release> = body
+        .lines()
+        .filter_map(|line| serde_json::from_str::<IndexLine>(line).ok())
+        .filter(|line| !line.yanked)
+        .filter_map(IndexLine::release)
+        .collect();
+    releases.sort_by(|a, b| a.version.cmp(&b.version));
+    Ok(releases)
+}
+
+/// Newest stable release, or the newest pre-release of crates without one.
+pub fn latest(releases: &[Release]) -> Option<&Release> {
+    releases
+        .iter()
+        .rev()
+        .find(|release| release.version.pre.is_empty())
+        .or_else(|| releases.last())
+}
+
+/// Newest release `req` accepts.
+pub fn latest_matching<'a>(releases: &'a [Release], req: &VersionReq) -> Option<&'a Release> {
+    releases
+        .iter()
+        .rev()
+        .find(|release| req.matches(&release.version))
+}
+
+/// A crate found by [`search`].
+#[derive(Debug, Serialize)]
+pub struct Crate {
+    pub name: String,
+    pub description: String,
+    pub downloads: u64,
+    /// Newest stable version, or the newest of crates without one.
+    pub version: String,
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    crates: Vec<SearchHit>,
+}
+
+#[derive(Deserialize)]
+struct SearchHit {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    downloads: u64,
+    #[serde(default)]
+    max_stable_version: Option<String>,
+    max_version: String,
+}
+
+/// Root of the crates.io web API, `FORGEIT_CRATES_API` for a mirror of it.
+fn api_url() -> String {
+    env::var("FORGEIT_CRATES_API")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| "https://crates.io/api/v1".to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// The `limit` crates matching `query` best, as crates.io ranks them.
+pub fn search(query: &str, limit: usize) -> Result<Vec<Crate>> {
+    network::require("Searching crates.io")?;
+    let url = format!("{}/crates", api_url());
+    tracing::debug!("searching {} for {}", url, query);
+    let mut response = agent()
+        .get(&url)
+        .query("q", query)
+        .query("per_page", limit.to_string())
+        .header("User-Agent", concat!("forgeit/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .map_err(|err| Error::environment(format!("Failed to reach {}: {}", url, err)))?;
+    if !response.status().is_success() {
+        return Err(Error::environment(format!(
+            "{} answered {}",
+            url,
+            response.status()
+        )));
+    }
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .map_err(|err| Error::environment(format!("Failed to read {}: {}", url, err)))?;
+    let found: SearchResponse = serde_json::from_str(&body)
+        .map_err(|err| Error::environment(format!("Unexpected answer from {}: {}", url, err)))?;
+    Ok(found
+        .crates
+        .into_iter()
+        .map(|hit| Crate {
+            version: hit.max_stable_version.unwrap_or(hit.max_version),
+            name: hit.name,
+            description: hit
+                .description
+                .map(|description| description.split_whitespace().collect::<Vec<_>>().join(" "))
+                .unwrap_or_default(),
+            downloads: hit.downloads,
+        })
+        .collect())
+}
+
+/// Names of crates spelled like `name`, for a crate that does not exist.
+/// Empty when crates.io cannot be searched.
+fn similar(name: &str) -> Vec<String> {
+    // The search matches words, which typos in separators would hide
+    let query = name.replace(['-', '_'], " ");
+    let found = match search(&query, 20) {
+        Ok(found) => found,
+        Err(err) => {
+            tracing::debug!("not suggesting crates for {}: {}", name, err);
+            return Vec::new();
+        }
+    };
+    suggest::similar(name, found.iter().map(|found| found.name.as_str()))
+        .into_iter()
+        .take(3)
+        .map(str::to_string)
+        .collect()
+}
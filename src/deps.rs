@@ -0,0 +1,1282 @@
+use clap::Args;
+use dialoguer::{
+    MultiSelect, Select,
+    theme::{ColorfulTheme, SimpleTheme, Theme},
+};
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+};
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, Value};
+
+use crate::{
+    crates_io,
+    error::{Context, Error, Result},
+    manifest, network,
+    output::{self, say},
+    templates,
+};
+
+/// Dependency tables of a Cargo.toml.
+pub const TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// How `forgeit add` adds its crates.
+#[derive(Args, Debug)]
+pub struct AddArgs {
+    /// Crates to add, each optionally as name@version
+    #[arg(required = true, value_name = "CRATE")]
+    pub names: Vec<String>,
+
+    /// Version to use, with a single crate
+    #[arg(long, default_value = "latest")]
+    pub version: String,
+
+    /// Add them as dev-dependencies
+    #[arg(long, conflicts_with = "build")]
+    pub dev: bool,
+
+    /// Add them as build-dependencies
+    #[arg(long)]
+    pub build: bool,
+
+    /// Features to enable, as crate/feature when adding several crates
+    #[arg(long, value_delimiter = ',')]
+    pub features: Vec<String>,
+
+    /// Mark them optional, exposed as features of the project
+    #[arg(long, conflicts_with_all = ["dev", "build"])]
+    pub optional: bool,
+
+    /// Disable the default features of the crates
+    #[arg(long)]
+    pub no_default_features: bool,
+}
+
+/// Adds the crates of `args` to the Cargo project in the current directory.
+pub fn add(args: &AddArgs) -> Result<()> {
+    let specs = specs(args)?;
+    let names = specs
+        .iter()
+        .map(|spec| spec.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let added = insert(Path::new("."), &specs)?;
+    output::set("dependencies", &added);
+
+    let kind = if args.dev {
+        " to the dev-dependencies"
+    } else if args.build {
+        " to the build-dependencies"
+    } else {
+        ""
+    };
+    say!("✅  Added {}{} successfully!", names, kind);
+    Ok(())
+}
+
+/// The dependency of every crate of `args`, with `--version` applied and
+/// the features handed to their crate.
+fn specs(args: &AddArgs) -> Result<Vec<DependencySpec>> {
+    let mut specs: Vec<DependencySpec> = args
+        .names
+        .iter()
+        .map(|name| DependencySpec::parse(name))
+        .collect();
+    if args.version != "latest" {
+        match specs.as_mut_slice() {
+            [spec] if spec.version.is_none() => spec.version = Some(args.version.clone()),
+            [_] => {
+                return Err(Error::user(format!(
+                    "'{}' already has a version, --version would replace it",
+                    args.names[0]
+                )));
+            }
+            _ => {
+                return Err(Error::user(
+                    "--version applies to a single crate\n👉 Pass each crate as name@version instead",
+                ));
+            }
+        }
+    }
+    for feature in &args.features {
+        let (owner, feature) = match feature.split_once('/') {
+            Some((owner, feature)) => (Some(owner), feature),
+            None => (None, feature.as_str()),
+        };
+        let spec = match (owner, specs.len()) {
+            (Some(owner), _) => specs.iter_mut().find(|spec| spec.name == owner),
+            (None, 1) => specs.first_mut(),
+            (None, _) => {
+                return Err(Error::user(format!(
+                    "Which crate is the feature '{}' of?\n👉 Pass it as crate/{} when adding several crates",
+                    feature, feature
+                )));
+            }
+        };
+        let Some(spec) = spec else {
+            return Err(Error::user(format!(
+                "'{}' is not one of the crates being added",
+                owner.unwrap_or_default()
+            )));
+        };
+        spec.features.push(feature.to_string());
+    }
+    for spec in &mut specs {
+        spec.table = if args.dev {
+            "dev-dependencies"
+        } else if args.build {
+            "build-dependencies"
+        } else {
+            "dependencies"
+        };
+        spec.optional = args.optional;
+        spec.default_features = !args.no_default_features;
+    }
+    Ok(specs)
+}
+
+/// A dependency to write into Cargo.toml, the way `cargo add` would.
+#[derive(Debug, Clone)]
+pub struct DependencySpec {
+    pub name: String,
+    /// Version requirement, the newest release when unset.
+    pub version: Option<String>,
+    pub features: Vec<String>,
+    /// `dependencies`, `dev-dependencies` or `build-dependencies`.
+    pub table: &'static str,
+    pub optional: bool,
+    pub default_features: bool,
+}
+
+impl DependencySpec {
+    pub fn new(name: &str) -> Self {
+        DependencySpec {
+            name: name.to_string(),
+            version: None,
+            features: Vec::new(),
+            table: "dependencies",
+            optional: false,
+            default_features: true,
+        }
+    }
+
+    /// `name` or `name@version`.
+    pub fn parse(spec: &str) -> Self {
+        match spec.split_once('@') {
+            Some((name, version)) => DependencySpec {
+                version: Some(version.to_string()),
+                ..DependencySpec::new(name)
+            },
+            None => DependencySpec::new(spec),
+        }
+    }
+
+    /// Enables the comma-separated `features`.
+    pub fn with_features(mut self, features: Option<&str>) -> Self {
+        self.features.extend(
+            features
+                .into_iter()
+                .flat_map(|features| features.split(','))
+                .map(str::trim)
+                .filter(|feature| !feature.is_empty())
+                .map(str::to_string),
+        );
+        self
+    }
+
+    pub fn dev(mut self) -> Self {
+        self.table = "dev-dependencies";
+        self
+    }
+}
+
+/// Adds `dependency` to the Cargo project in `project_dir`, e.g. `tower`
+/// with the `util` feature.
+pub fn add_dependency(project_dir: &str, dependency: &str, features: Option<&str>) -> Result<()> {
+    insert(
+        Path::new(project_dir),
+        &[DependencySpec::new(dependency).with_features(features)],
+    )?;
+    Ok(())
+}
+
+/// Adds `dependency` to the dev-dependencies of the Cargo project in
+/// `project_dir`.
+pub fn add_dev_dependency(project_dir: &str, dependency: &str, features: &str) -> Result<()> {
+    insert(
+        Path::new(project_dir),
+        &[DependencySpec::new(dependency)
+            .with_features(Some(features))
+            .dev()],
+    )?;
+    Ok(())
+}
+
+/// Writes `specs` into the Cargo.toml in `project_dir` at once, keeping its
+/// formatting. Versions are resolved on crates.io, and features checked
+/// against the release. A crate in `[workspace.dependencies]` is inherited
+/// from there instead. Returns every crate as `name@requirement`.
+pub fn insert(project_dir: &Path, specs: &[DependencySpec]) -> Result<Vec<String>> {
+    let path = project_dir.join("Cargo.toml");
+    let content = fs::read_to_string(&path).map_err(|_| {
+        Error::user(format!(
+            "No Cargo.toml in {}\n👉 Run this in a Cargo project",
+            project_dir.display()
+        ))
+    })?;
+    let mut document: DocumentMut = content
+        .parse()
+        .map_err(|err| Error::user(format!("Failed to parse Cargo.toml: {}", err)))?;
+    let inherited = workspace_dependencies(project_dir);
+    let inherits = |spec: &DependencySpec| spec.version.is_none() && inherited.contains(&spec.name);
+    let offline = network::is_offline();
+    // Every crate is looked up at once rather than one after the other
+    let mut names: Vec<&str> = specs
+        .iter()
+        .filter(|spec| !offline && !inherits(spec))
+        .map(|spec| spec.name.as_str())
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    let releases = crates_io::releases_of(&names)?;
+    let pinned = if offline {
+        templates::pinned_versions()?
+    } else {
+        BTreeMap::new()
+    };
+
+    let mut added = Vec::new();
+    for spec in specs {
+        let existing = document
+            .get(spec.table)
+            .and_then(|table| table.get(&spec.name))
+            .and_then(|item| match item {
+                Item::Value(Value::String(version)) => Some(version.value().clone()),
+                _ => item.get("version")?.as_str().map(str::to_string),
+            });
+        let version = if inherits(spec) {
+            None
+        } else if offline {
+            Some(pinned_version(spec, existing, &pinned)?)
+        } else {
+            let index = names
+                .binary_search(&spec.name.as_str())
+                .map_err(|_| Error::Internal(format!("{} was not looked up", spec.name)))?;
+            Some(resolve(spec, existing.as_deref(), &releases[index])?)
+        };
+        write_dependency(&mut document, spec, version.as_deref())?;
+        added.push(match &version {
+            Some(version) => format!("{}@{}", spec.name, version),
+            None => format!("{}@workspace", spec.name),
+        });
+    }
+
+    fs::write(&path, document.to_string()).context("Failed to update Cargo.toml")?;
+    output::file(&path);
+    Ok(added)
+}
+
+/// Requirement of `spec`: the one it asks for, the one Cargo.toml already
+/// has, or the newest release. Fails for crates, versions and features
+/// crates.io does not have.
+fn resolve(
+    spec: &DependencySpec,
+    existing: Option<&str>,
+    releases: &[crates_io::Release],
+) -> Result<String> {
+    let requirement = spec.version.as_deref().or(existing);
+    let release = match requirement {
+        Some(requirement) => {
+            let req = VersionReq::parse(requirement).map_err(|err| {
+                Error::user(format!(
+                    "'{}' is not a version requirement of {}: {}",
+                    requirement, spec.name, err
+                ))
+            })?;
+            crates_io::latest_matching(releases, &req).ok_or_else(|| {
+                Error::user(format!(
+                    "No release of {} matches {}\n👉 Its latest is {}",
+                    spec.name,
+                    requirement,
+                    crates_io::latest(releases).map_or_else(
+                        || "yanked".to_string(),
+                        |release| release.version.to_string()
+                    )
+                ))
+            })?
+        }
+        None => crates_io::latest(releases)
+            .ok_or_else(|| Error::user(format!("Every release of {} is yanked", spec.name)))?,
+    };
+    if let Some(unknown) = spec
+        .features
+        .iter()
+        .find(|feature| !release.features.contains(*feature))
+    {
+        return Err(Error::user(format!(
+            "{} {} has no feature '{}'\n👉 Its features are {}",
+            spec.name,
+            release.version,
+            unknown,
+            release
+                .features
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+    Ok(requirement.map_or_else(|| release.version.to_string(), str::to_string))
+}
+
+/// Requirement of `spec` without the network: the one it asks for, the one
+/// Cargo.toml already has, or the pinned one. Nothing is checked, that is
+/// left to the first build.
+fn pinned_version(
+    spec: &DependencySpec,
+    existing: Option<String>,
+    pinned: &BTreeMap<String, String>,
+) -> Result<String> {
+    spec.version
+        .clone()
+        .or(existing)
+        .or_else(|| pinned.get(&spec.name).cloned())
+        .ok_or_else(|| {
+            Error::user(format!(
+                "No version of {} is pinned for --offline\n👉 Pass it as {}@<version>, or add it to the versions.toml template",
+                spec.name, spec.name
+            ))
+        })
+}
+
+/// Crates of the `[workspace.dependencies]` of the workspace `project_dir`
+/// belongs to, empty outside of one.
+fn workspace_dependencies(project_dir: &Path) -> Vec<String> {
+    let Ok(dir) = project_dir.canonicalize() else {
+        return Vec::new();
+    };
+    dir.ancestors()
+        .filter_map(|dir| fs::read_to_string(dir.join("Cargo.toml")).ok())
+        .filter_map(|content| content.parse::<DocumentMut>().ok())
+        .find(|document| document.contains_key("workspace"))
+        .and_then(|document| {
+            let table = document["workspace"].get("dependencies")?.as_table_like()?;
+            Some(table.iter().map(|(name, _)| name.to_string()).collect())
+        })
+        .unwrap_or_default()
+}
+
+/// Sets `spec` in its table of `document`, merging the features with those
+/// already there. `version` is `None` for inherited workspace dependencies.
+fn write_dependency(
+    document: &mut DocumentMut,
+    spec: &DependencySpec,
+    version: Option<&str>,
+) -> Result<()> {
+    if !document.contains_key(spec.table) {
+        document[spec.table] = Item::Table(Table::new());
+    }
+    let Some(table) = document[spec.table].as_table_like_mut() else {
+        return Err(Error::user(format!(
+            "[{}] in Cargo.toml is not a table",
+            spec.table
+        )));
+    };
+    let sorted = table
+        .iter()
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>()
+        .is_sorted();
+
+    let mut entry = match table.get(&spec.name) {
+        Some(Item::Value(Value::InlineTable(entry))) => entry.clone(),
+        Some(Item::Table(entry)) => entry.clone().into_inline_table(),
+        _ => InlineTable::new(),
+    };
+    match version {
+        Some(version) => {
+            entry.insert("version", version.into());
+            entry.remove("workspace");
+        }
+        None => {
+            entry.remove("version");
+            entry.insert("workspace", true.into());
+        }
+    }
+    if !spec.features.is_empty() {
+        let mut features: Vec<String> = entry
+            .get("features")
+            .and_then(Value::as_array)
+            .map(|features| {
+                features
+                    .iter()
+                    .filter_map(|feature| feature.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        for feature in &spec.features {
+            if !features.contains(feature) {
+                features.push(feature.clone());
+            }
+        }
+        entry.insert("features", Value::Array(features.iter().collect()));
+    }
+    if !spec.default_features {
+        entry.insert("default-features", false.into());
+    }
+    if spec.optional {
+        entry.insert("optional", true.into());
+    }
+    // Keys in the order cargo writes them
+    entry.sort_values_by(|a, _, b, _| {
+        let rank = |key: &str| {
+            [
+                "version",
+                "workspace",
+                "default-features",
+                "features",
+                "optional",
+            ]
+            .iter()
+            .position(|known| *known == key)
+            .unwrap_or(usize::MAX)
+        };
+        rank(a.get()).cmp(&rank(b.get()))
+    });
+
+    let item = match (entry.len(), version) {
+        (1, Some(version)) => toml_edit::value(version),
+        _ => toml_edit::value(entry),
+    };
+    // A dependency written as its own table stays one
+    match table.get_mut(&spec.name) {
+        Some(Item::Table(existing)) => {
+            if let Item::Value(Value::InlineTable(entry)) = item {
+                let decor = existing.decor().clone();
+                *existing = entry.into_table();
+                *existing.decor_mut() = decor;
+            }
+        }
+        Some(existing) if existing.is_value() => {
+            let decor = existing.as_value().map(|value| value.decor().clone());
+            *existing = item;
+            if let (Some(decor), Some(value)) = (decor, existing.as_value_mut()) {
+                *value.decor_mut() = decor;
+            }
+        }
+        _ => {
+            table.insert(&spec.name, item);
+        }
+    }
+    if sorted {
+        table.sort_values();
+    }
+
+    // Optional dependencies are enabled through a feature of the same name
+    if spec.optional {
+        let dep = format!("dep:{}", spec.name);
+        let enabled = document
+            .get("features")
+            .and_then(Item::as_table_like)
+            .is_some_and(|features| {
+                features.iter().any(|(_, enables)| {
+                    enables.as_array().is_some_and(|enables| {
+                        enables
+                            .iter()
+                            .any(|enabled| enabled.as_str() == Some(dep.as_str()))
+                    })
+                })
+            });
+        if !enabled {
+            if !document.contains_key("features") {
+                document["features"] = Item::Table(Table::new());
+            }
+            document["features"][spec.name.as_str()] = toml_edit::value(Array::from_iter([dep]));
+        }
+    }
+    Ok(())
+}
+
+/// What `forgeit remove` removes.
+#[derive(Args, Debug)]
+pub struct RemoveArgs {
+    /// Crates to remove
+    #[arg(required = true, value_name = "CRATE")]
+    pub names: Vec<String>,
+
+    /// Remove them from the dev-dependencies
+    #[arg(long, conflicts_with = "build")]
+    pub dev: bool,
+
+    /// Remove them from the build-dependencies
+    #[arg(long)]
+    pub build: bool,
+
+    /// Also delete the modules below src/ that still use them
+    #[arg(long)]
+    pub delete_code: bool,
+}
+
+/// A line of the project's code naming a removed crate.
+struct Reference {
+    file: PathBuf,
+    line: usize,
+}
+
+/// Removes the crates of `args` from the Cargo project in the current
+/// directory and its manifest, then points at the code still using them.
+pub fn remove(args: &RemoveArgs) -> Result<()> {
+    let project_dir = Path::new(".");
+    let table = if args.dev {
+        "dev-dependencies"
+    } else if args.build {
+        "build-dependencies"
+    } else {
+        "dependencies"
+    };
+    let names = args.names.join(", ");
+    delete_dependencies(project_dir, table, &args.names)?;
+    output::set("dependencies", &args.names);
+
+    if !args.dev && !args.build {
+        forget(project_dir, &args.names)?;
+    }
+    say!("✅  Removed {} successfully!", names);
+
+    let references = references(project_dir, &args.names)?;
+    if references.is_empty() {
+        output::set("references", Vec::<String>::new());
+        return Ok(());
+    }
+    output::set(
+        "references",
+        references
+            .iter()
+            .map(|reference| format!("{}:{}", reference.file.display(), reference.line))
+            .collect::<Vec<_>>(),
+    );
+    say!("\nThe code still uses {}:", names);
+    for reference in &references {
+        say!("  {}:{}", reference.file.display(), reference.line);
+    }
+
+    let mut modules: Vec<&Path> = references
+        .iter()
+        .map(|reference| reference.file.as_path())
+        .filter(|file| is_leaf_module(file))
+        .collect();
+    modules.dedup();
+    if modules.is_empty() {
+        say!("👉 Change that code, or add the crates back with forgeit add");
+    } else if args.delete_code {
+        for module in &modules {
+            delete_module(project_dir, module)?;
+        }
+        say!(
+            "✅ Deleted {}\n👉 Run cargo check for the code that used them",
+            modules
+                .iter()
+                .map(|module| module.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    } else {
+        say!("👉 Change that code, or pass --delete-code to delete the modules using them");
+    }
+    Ok(())
+}
+
+/// Deletes `names` from `table` of the Cargo.toml in `project_dir`, keeping
+/// its formatting, along with the features enabling them when they were
+/// optional. Nothing is written when one of them is not in `table`.
+fn delete_dependencies(project_dir: &Path, table: &str, names: &[String]) -> Result<()> {
+    let path = project_dir.join("Cargo.toml");
+    let content = fs::read_to_string(&path).map_err(|_| {
+        Error::user(format!(
+            "No Cargo.toml in {}\n👉 Run this in a Cargo project",
+            project_dir.display()
+        ))
+    })?;
+    let mut document: DocumentMut = content
+        .parse()
+        .map_err(|err| Error::user(format!("Failed to parse Cargo.toml: {}", err)))?;
+
+    let has = |document: &DocumentMut, table: &str, name: &str| {
+        document
+            .get(table)
+            .and_then(Item::as_table_like)
+            .is_some_and(|table| table.contains_key(name))
+    };
+    if let Some(missing) = names.iter().find(|name| !has(&document, table, name)) {
+        let elsewhere: Vec<&str> = TABLES
+            .into_iter()
+            .filter(|other| *other != table && has(&document, other, missing))
+            .collect();
+        let message = match elsewhere.as_slice() {
+            [] => format!(
+                "{} is not a dependency of this project, checked [{}] of Cargo.toml\n👉 Check the name, forgeit remove takes the names Cargo.toml uses",
+                missing,
+                TABLES.join("], [")
+            ),
+            [other, ..] => format!(
+                "{} is not in [{}] of Cargo.toml but in [{}]\n👉 Run it {}",
+                missing,
+                table,
+                other,
+                match *other {
+                    "dev-dependencies" => "with --dev",
+                    "build-dependencies" => "with --build",
+                    _ => "without --dev or --build",
+                }
+            ),
+        };
+        return Err(Error::user(message));
+    }
+
+    let mut optional = Vec::new();
+    if let Some(dependencies) = document[table].as_table_like_mut() {
+        for name in names {
+            let removed = dependencies.remove(name);
+            if removed
+                .as_ref()
+                .and_then(|item| item.get("optional"))
+                .and_then(Item::as_bool)
+                == Some(true)
+            {
+                optional.push(name.as_str());
+            }
+        }
+    }
+
+    // Features enabling an optional dependency go with it, and the feature
+    // named after it when nothing else is left in it
+    if !optional.is_empty()
+        && let Some(features) = document
+            .get_mut("features")
+            .and_then(Item::as_table_like_mut)
+    {
+        let enables = |value: &str, name: &str| {
+            value == name
+                || value == format!("dep:{}", name)
+                || value.starts_with(&format!("{}/", name))
+                || value.starts_with(&format!("{}?/", name))
+        };
+        let mut emptied = Vec::new();
+        for (feature, values) in features.iter_mut() {
+            let Some(values) = values.as_array_mut() else {
+                continue;
+            };
+            values.retain(|value| {
+                !value
+                    .as_str()
+                    .is_some_and(|value| optional.iter().any(|name| enables(value, name)))
+            });
+            if values.is_empty() && optional.contains(&feature.get()) {
+                emptied.push(feature.get().to_string());
+            }
+        }
+        for feature in emptied {
+            features.remove(&feature);
+        }
+    }
+
+    fs::write(&path, document.to_string()).context("Failed to update Cargo.toml")?;
+    output::file(&path);
+    Ok(())
+}
+
+/// Drops the removed crates from the features of the forgeit manifest, and
+/// the database with sqlx.
+fn forget(project_dir: &Path, names: &[String]) -> Result<()> {
+    manifest::update(project_dir, |document| {
+        if let Some(features) = document
+            .get_mut("features")
+            .and_then(|item| item.as_array_mut())
+        {
+            let kept: Array = features
+                .iter()
+                .filter(|feature| {
+                    !feature
+                        .as_str()
+                        .is_some_and(|feature| names.iter().any(|name| name == feature))
+                })
+                .cloned()
+                .collect();
+            *features = kept;
+        }
+        if names.iter().any(|name| name == "sqlx") {
+            document.remove("database");
+        }
+    })
+}
+
+/// Lines of the Rust files in `src/`, `tests/`, `benches/`, `examples/` and
+/// `build.rs` that name one of the crates, like `use serde::Serialize` or
+/// `#[tokio::main]`.
+fn references(project_dir: &Path, names: &[String]) -> Result<Vec<Reference>> {
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_dir() {
+                walk(&path, files);
+            } else if path.extension().is_some_and(|extension| extension == "rs") {
+                files.push(path);
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    for dir in ["src", "tests", "benches", "examples"] {
+        walk(&project_dir.join(dir), &mut files);
+    }
+    if project_dir.join("build.rs").is_file() {
+        files.push(project_dir.join("build.rs"));
+    }
+    files.sort();
+
+    // Crates are named with underscores in code
+    let idents: Vec<String> = names.iter().map(|name| name.replace('-', "_")).collect();
+    let mut references = Vec::new();
+    for path in files {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let file = path
+            .strip_prefix(project_dir)
+            .unwrap_or(&path)
+            .to_path_buf();
+        for (index, line) in content.lines().enumerate() {
+            if idents.iter().any(|ident| names_crate(line, ident)) {
+                references.push(Reference {
+                    file: file.clone(),
+                    line: index + 1,
+                });
+            }
+        }
+    }
+    Ok(references)
+}
+
+/// Whether `line` uses the crate `ident`, as a path or in `extern crate`.
+fn names_crate(line: &str, ident: &str) -> bool {
+    let line = line.trim_start();
+    if line.starts_with("//") {
+        return false;
+    }
+    if line
+        .strip_prefix("extern crate ")
+        .is_some_and(|rest| rest.trim_end_matches(';').split_whitespace().next() == Some(ident))
+    {
+        return true;
+    }
+    let path = format!("{}::", ident);
+    line.match_indices(&path).any(|(start, _)| {
+        // `::serde::` is the crate too, `my_serde::` or `crate::serde::` are not
+        let before = &line[..start];
+        !before
+            .strip_suffix("::")
+            .unwrap_or(before)
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '.'))
+    })
+}
+
+/// Whether `file` is a module of its own below `src/`, which can go
+/// without taking other modules with it.
+fn is_leaf_module(file: &Path) -> bool {
+    file.starts_with("src")
+        && !file.starts_with("src/bin")
+        && file.file_name().is_some_and(|name| {
+            !["main.rs", "lib.rs", "mod.rs"]
+                .iter()
+                .any(|root| name == *root)
+        })
+}
+
+/// Deletes the module `file` and its `mod` declaration in the parent module,
+/// and stops `upgrade` from tracking it.
+fn delete_module(project_dir: &Path, file: &Path) -> Result<()> {
+    let path = project_dir.join(file);
+    fs::remove_file(&path).with_context(|| format!("Failed to delete {}", path.display()))?;
+    let key = file.to_string_lossy().replace('\\', "/");
+    if manifest::Manifest::read(project_dir)?
+        .is_some_and(|manifest| manifest.templates.contains_key(&key))
+    {
+        manifest::update(project_dir, |document| {
+            if let Some(templates) = document
+                .get_mut("templates")
+                .and_then(|item| item.as_table_like_mut())
+            {
+                templates.remove(&key);
+            }
+        })?;
+        fs::remove_file(project_dir.join(manifest::base_path(&key))).ok();
+    }
+
+    let Some(module) = file.file_stem().and_then(|stem| stem.to_str()) else {
+        return Ok(());
+    };
+    let dir = file.parent().unwrap_or(Path::new("src"));
+    let parents = if dir == Path::new("src") {
+        vec![dir.join("main.rs"), dir.join("lib.rs")]
+    } else {
+        vec![dir.join("mod.rs"), dir.with_extension("rs")]
+    };
+    for parent in parents {
+        let parent = project_dir.join(parent);
+        let Ok(content) = fs::read_to_string(&parent) else {
+            continue;
+        };
+        let declaration = format!("mod {};", module);
+        let kept: Vec<&str> = content
+            .lines()
+            .filter(|line| {
+                let line = line.trim();
+                !(line == declaration
+                    || line
+                        .strip_suffix(&declaration)
+                        .is_some_and(|visibility| visibility.trim_end().starts_with("pub")))
+            })
+            .collect();
+        if kept.len() != content.lines().count() {
+            let mut updated = kept.join("\n");
+            updated.push('\n');
+            fs::write(&parent, updated)
+                .with_context(|| format!("Failed to update {}", parent.display()))?;
+            output::file(&parent);
+        }
+    }
+    Ok(())
+}
+
+/// Which upgrades `forgeit upgrade-deps` applies.
+#[derive(Args, Debug)]
+pub struct UpgradeDepsArgs {
+    /// Apply every upgrade the version requirements already allow
+    #[arg(long, conflicts_with = "all")]
+    pub compatible: bool,
+
+    /// Apply every upgrade, breaking ones too
+    #[arg(long)]
+    pub all: bool,
+}
+
+/// How far the newest version of a dependency is from its requirement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Change {
+    UpToDate,
+    /// Newer versions the requirement accepts.
+    Compatible,
+    /// A newer version outside the requirement.
+    Breaking,
+}
+
+impl Change {
+    fn label(self) -> &'static str {
+        match self {
+            Change::UpToDate => "up to date",
+            Change::Compatible => "compatible",
+            Change::Breaking => "breaking",
+        }
+    }
+}
+
+/// A crates.io dependency of the project and its newer versions.
+#[derive(Debug, Serialize)]
+struct Outdated {
+    table: &'static str,
+    name: String,
+    current: String,
+    /// Newest version the requirement accepts.
+    compatible: Option<String>,
+    latest: String,
+    change: Change,
+    upgraded_to: Option<String>,
+}
+
+/// Dependencies of `document` with a crates.io version requirement, as
+/// their table, key, crate name and requirement. Path, git and workspace
+/// dependencies have nothing to upgrade.
+fn registry_dependencies(document: &DocumentMut) -> Vec<(&'static str, String, String, String)> {
+    let mut dependencies = Vec::new();
+    for table_name in TABLES {
+        let Some(table) = document.get(table_name).and_then(Item::as_table_like) else {
+            continue;
+        };
+        for (key, item) in table.iter() {
+            let (version, package) = match item {
+                Item::Value(Value::String(version)) => (version.value().clone(), None),
+                _ => {
+                    let Some(entry) = item.as_table_like() else {
+                        continue;
+                    };
+                    if ["path", "git", "workspace", "registry"]
+                        .iter()
+                        .any(|source| entry.contains_key(source))
+                    {
+                        continue;
+                    }
+                    let Some(version) = entry.get("version").and_then(Item::as_str) else {
+                        continue;
+                    };
+                    (
+                        version.to_string(),
+                        entry
+                            .get("package")
+                            .and_then(Item::as_str)
+                            .map(str::to_string),
+                    )
+                }
+            };
+            dependencies.push((
+                table_name,
+                key.to_string(),
+                package.unwrap_or_else(|| key.to_string()),
+                version,
+            ));
+        }
+    }
+    dependencies
+}
+
+/// Lowest version `requirement` accepts, `1` for `^1` or `1.2.0` for `~1.2`.
+fn floor(requirement: &str) -> Option<Version> {
+    let version = requirement.trim_start_matches(['^', '~', '=', '>', ' ']);
+    let parts = version.split('.').count();
+    let padded = match parts {
+        1 => format!("{}.0.0", version),
+        2 => format!("{}.0", version),
+        _ => version.to_string(),
+    };
+    Version::parse(&padded).ok()
+}
+
+/// `requirement` moved to `version`, keeping its operator.
+fn bump(requirement: &str, version: &str) -> String {
+    let operator = requirement
+        .find(|c: char| c.is_ascii_digit())
+        .map_or("", |start| &requirement[..start]);
+    format!("{}{}", operator, version)
+}
+
+/// Checks crates.io for newer versions of the dependencies of the Cargo
+/// project in the current directory and applies the chosen upgrades.
+pub fn upgrade_deps(args: &UpgradeDepsArgs) -> Result<()> {
+    let path = Path::new("Cargo.toml");
+    let content = fs::read_to_string(path).map_err(|_| {
+        Error::user(
+            "No Cargo.toml in the current directory\n👉 Run upgrade-deps in a Cargo project",
+        )
+    })?;
+    let mut document: DocumentMut = content
+        .parse()
+        .map_err(|err| Error::user(format!("Failed to parse Cargo.toml: {}", err)))?;
+
+    let dependencies = registry_dependencies(&document);
+    let step = output::step(format!(
+        "Check {} dependencies on crates.io",
+        dependencies.len()
+    ));
+    let packages: Vec<&str> = dependencies
+        .iter()
+        .map(|(_, _, package, _)| package.as_str())
+        .collect();
+    let releases = crates_io::releases_of(&packages)?;
+    let mut outdated = Vec::new();
+    for ((table, key, _, current), releases) in dependencies.into_iter().zip(releases) {
+        let Some(latest) = crates_io::latest(&releases).map(|release| &release.version) else {
+            continue;
+        };
+        // Requirements cargo accepts but semver does not are left alone
+        let Ok(req) = VersionReq::parse(&current) else {
+            continue;
+        };
+        let compatible = crates_io::latest_matching(&releases, &req)
+            .map(|release| &release.version)
+            .filter(|version| floor(&current).is_some_and(|floor| *version > &floor));
+        let change = if !req.matches(latest) && floor(&current).is_some_and(|floor| *latest > floor)
+        {
+            Change::Breaking
+        } else if compatible.is_some() {
+            Change::Compatible
+        } else {
+            Change::UpToDate
+        };
+        outdated.push(Outdated {
+            table,
+            name: key,
+            current,
+            compatible: compatible.map(Version::to_string),
+            latest: latest.to_string(),
+            change,
+            upgraded_to: None,
+        });
+    }
+    step.done();
+
+    print_table(&outdated);
+    let upgradable: Vec<usize> = (0..outdated.len())
+        .filter(|&index| outdated[index].change != Change::UpToDate)
+        .collect();
+    if upgradable.is_empty() {
+        say!("✅ Every dependency is up to date");
+        output::set("dependencies", &outdated);
+        return Ok(());
+    }
+
+    let chosen: Vec<(usize, String)> = if args.all {
+        upgradable
+            .iter()
+            .map(|&index| (index, outdated[index].latest.clone()))
+            .collect()
+    } else if args.compatible {
+        upgradable
+            .iter()
+            .filter_map(|&index| Some((index, outdated[index].compatible.clone()?)))
+            .collect()
+    } else if io::stdin().is_terminal() && io::stdout().is_terminal() && !output::is_json() {
+        choose(&outdated, &upgradable)?
+    } else {
+        say!("👉 Pass --compatible or --all to upgrade them");
+        output::set("dependencies", &outdated);
+        return Ok(());
+    };
+
+    for (index, version) in &chosen {
+        let dependency = &mut outdated[*index];
+        let requirement = bump(&dependency.current, version);
+        let item = &mut document[dependency.table][dependency.name.as_str()];
+        let target = match item {
+            Item::Value(Value::String(_)) => item.as_value_mut(),
+            _ => item.get_mut("version").and_then(Item::as_value_mut),
+        };
+        if let Some(target) = target {
+            let decor = target.decor().clone();
+            *target = requirement.as_str().into();
+            *target.decor_mut() = decor;
+        }
+        dependency.upgraded_to = Some(requirement);
+    }
+    output::set("dependencies", &outdated);
+    if chosen.is_empty() {
+        return Ok(());
+    }
+    fs::write(path, document.to_string()).context("Failed to update Cargo.toml")?;
+    output::file(path);
+    for dependency in &outdated {
+        if let Some(requirement) = &dependency.upgraded_to {
+            say!(
+                "✅ Upgraded {} from {} to {}",
+                dependency.name,
+                dependency.current,
+                requirement
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Prints the current and newest versions of every dependency.
+fn print_table(outdated: &[Outdated]) {
+    let rows: Vec<[String; 5]> = outdated
+        .iter()
+        .map(|dependency| {
+            [
+                dependency.name.clone(),
+                dependency.current.clone(),
+                dependency
+                    .compatible
+                    .clone()
+                    .unwrap_or_else(|| "-".to_string()),
+                dependency.latest.clone(),
+                dependency.change.label().to_string(),
+            ]
+        })
+        .collect();
+    print_rows(
+        ["Crate", "Current", "Compatible", "Latest", "Change"],
+        &rows,
+    );
+}
+
+/// Prints `rows` below `header`, each column as wide as its widest cell.
+fn print_rows<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let widths: Vec<usize> = (0..N)
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([header[column].len()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let line = |cells: [&str; N]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    say!("\n  {}", line(header));
+    for row in rows {
+        say!("  {}", line(row.each_ref().map(String::as_str)));
+    }
+    say!("");
+}
+
+/// Asks which of the `upgradable` dependencies to upgrade, to their newest
+/// version. Compatible upgrades start out selected.
+fn choose(outdated: &[Outdated], upgradable: &[usize]) -> Result<Vec<(usize, String)>> {
+    let theme: Box<dyn Theme> = if output::no_color_env() {
+        Box::new(SimpleTheme)
+    } else {
+        Box::new(ColorfulTheme::default())
+    };
+    let items: Vec<String> = upgradable
+        .iter()
+        .map(|&index| {
+            let dependency = &outdated[index];
+            format!(
+                "{} {} -> {} ({})",
+                dependency.name,
+                dependency.current,
+                dependency.latest,
+                dependency.change.label()
+            )
+        })
+        .collect();
+    let defaults: Vec<bool> = upgradable
+        .iter()
+        .map(|&index| outdated[index].change == Change::Compatible)
+        .collect();
+    let picked = MultiSelect::with_theme(theme.as_ref())
+        .with_prompt("Upgrade (space toggles, enter applies)")
+        .items(&items)
+        .defaults(&defaults)
+        .interact()
+        .map_err(|err| Error::environment(format!("Failed to read the answer: {}", err)))?;
+    Ok(picked
+        .into_iter()
+        .map(|choice| {
+            let index = upgradable[choice];
+            (index, outdated[index].latest.clone())
+        })
+        .collect())
+}
+
+/// What `forgeit search` looks for.
+#[derive(Args, Debug)]
+pub struct SearchArgs {
+    /// Words of the crate's name or description
+    #[arg(required = true)]
+    pub query: Vec<String>,
+
+    /// Number of crates to show
+    #[arg(long, default_value_t = 10)]
+    pub limit: usize,
+}
+
+/// Characters of a description shown in the search results.
+const DESCRIPTION_WIDTH: usize = 60;
+
+/// `12.3M` for a download count.
+fn downloads(count: u64) -> String {
+    match count {
+        0..1_000 => count.to_string(),
+        1_000..1_000_000 => format!("{:.1}K", count as f64 / 1e3),
+        _ => format!("{:.1}M", count as f64 / 1e6),
+    }
+}
+
+/// Searches crates.io and, on a terminal inside a Cargo project, adds the
+/// crate picked from the results.
+pub fn search(args: &SearchArgs) -> Result<()> {
+    let query = args.query.join(" ");
+    let step = output::step(format!("Search crates.io for '{}'", query));
+    let found = crates_io::search(&query, args.limit)?;
+    step.done();
+    output::set("crates", &found);
+    if found.is_empty() {
+        say!("No crates matching '{}'", query);
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 4]> = found
+        .iter()
+        .map(|found| {
+            let mut description = found.description.clone();
+            if description.chars().count() > DESCRIPTION_WIDTH {
+                description = description.chars().take(DESCRIPTION_WIDTH - 1).collect();
+                description.push('…');
+            }
+            [
+                found.name.clone(),
+                found.version.clone(),
+                downloads(found.downloads),
+                description,
+            ]
+        })
+        .collect();
+    print_rows(["Crate", "Version", "Downloads", "Description"], &rows);
+
+    let project = Path::new("Cargo.toml").is_file();
+    if !project || output::is_json() || !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        say!("👉 forgeit add <crate>");
+        return Ok(());
+    }
+    let theme: Box<dyn Theme> = if output::no_color_env() {
+        Box::new(SimpleTheme)
+    } else {
+        Box::new(ColorfulTheme::default())
+    };
+    let items: Vec<&str> = found.iter().map(|found| found.name.as_str()).collect();
+    let picked = Select::with_theme(theme.as_ref())
+        .with_prompt("Add to Cargo.toml (esc skips)")
+        .items(&items)
+        .default(0)
+        .interact_opt()
+        .map_err(|err| Error::environment(format!("Failed to read the answer: {}", err)))?;
+    let Some(picked) = picked else {
+        return Ok(());
+    };
+    let added = insert(Path::new("."), &[DependencySpec::new(items[picked])])?;
+    output::set("dependencies", &added);
+    say!("✅  Added {} successfully!", items[picked]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn floors_requirements_to_the_version_they_start_at() {
+        assert_eq!(floor("1"), Some(Version::new(1, 0, 0)));
+        assert_eq!(floor("0.8"), Some(Version::new(0, 8, 0)));
+        assert_eq!(floor("^1.2.3"), Some(Version::new(1, 2, 3)));
+        assert_eq!(floor("~0.4"), Some(Version::new(0, 4, 0)));
+        assert_eq!(floor("=2.0.1"), Some(Version::new(2, 0, 1)));
+        assert_eq!(floor(">= 1.5"), Some(Version::new(1, 5, 0)));
+        assert_eq!(floor("*"), None);
+        assert_eq!(floor("git"), None);
+    }
+
+    #[test]
+    fn bumps_requirements_keeping_their_operator() {
+        assert_eq!(bump("1.0", "1.2.3"), "1.2.3");
+        assert_eq!(bump("^0.7", "0.8.1"), "^0.8.1");
+        assert_eq!(bump("~1.2", "1.3.0"), "~1.3.0");
+        assert_eq!(bump("=2.0.0", "2.1.0"), "=2.1.0");
+        assert_eq!(bump(">= 1", "2.0.0"), ">= 2.0.0");
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    fs,
+    net::{TcpStream, ToSocketAddrs},
+    path::Path,
+    process::Command,
+    time::Duration,
+};
+
+use crate::{
+    error::{Error, Result},
+    generate,
+    manifest::{self, Manifest},
+    network,
+    output::{self, say},
+    templates,
+};
+
+/// Oldest Rust able to build the generated projects, which use edition 2024.
+const MIN_RUST: (u32, u32) = (1, 85);
+const MIN_GIT: (u32, u32) = (2, 0);
+
+/// Outcome of a single check: `Err` holds the problem and how to fix it.
+type Check = std::result::Result<String, (String, String)>;
+
+/// Checks reported so far, the `result` of `--output json`.
+#[derive(Default)]
+struct Results {
+    failures: usize,
+    checks: Vec<serde_json::Value>,
+}
+
+fn report(check: Check, results: &mut Results) {
+    match check {
+        Ok(message) => {
+            say!("✅ {}", message);
+            results
+                .checks
+                .push(serde_json::json!({ "ok": true, "message": message }));
+        }
+        Err((problem, fix)) => {
+            results.failures += 1;
+            say!("❌ {}", problem);
+            say!("   👉 {}", fix);
+            results
+                .checks
+                .push(serde_json::json!({ "ok": false, "message": problem, "fix": fix }));
+        }
+    }
+}
+
+/// `major.minor` of the version printed by `<program> --version`, the
+/// first word that parses as one.
+fn tool_version(program: &str) -> Option<(String, (u32, u32))> {
+    let output = Command::new(program).arg("--version").output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let version = text.split_whitespace().find_map(|word| {
+        let mut parts = word.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        Some((major, minor))
+    })?;
+    Some((text, version))
+}
+
+fn check_tool(program: &str, minimum: (u32, u32), install: &str) -> Check {
+    let Some((text, version)) = tool_version(program) else {
+        return Err((
+            format!("{} not found on PATH", program),
+            format!("Install it from {}", install),
+        ));
+    };
+    if version < minimum {
+        return Err((
+            format!("{} is older than {}.{}", text, minimum.0, minimum.1),
+            format!("Update it, see {}", install),
+        ));
+    }
+    Ok(text)
+}
+
+fn check_network() -> Check {
+    if network::is_offline() {
+        return Ok("Offline, crates.io is not checked".to_string());
+    }
+    let reachable = ("index.crates.io", 443)
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .is_some_and(|addr| TcpStream::connect_timeout(&addr, Duration::from_secs(5)).is_ok());
+    if reachable {
+        Ok("crates.io is reachable".to_string())
+    } else {
+        Err((
+            "Could not reach index.crates.io".to_string(),
+            "Check your network or proxy, forgeit needs it to resolve dependency versions"
+                .to_string(),
+        ))
+    }
+}
+
+fn check_framework(project_dir: &Path, manifest: &Manifest) -> Check {
+    let Some(framework) = &manifest.framework else {
+        return Ok("No framework recorded, nothing to compare".to_string());
+    };
+    match generate::detect_framework(project_dir) {
+        Some(detected) if detected == framework => {
+            Ok(format!("{} is a dependency in Cargo.toml", framework))
+        }
+        // Frameworks of user templates are not detected from Cargo.toml
+        None if !matches!(framework.as_str(), "axum" | "actix-web") => {
+            Ok(format!("Framework {} comes from a template", framework))
+        }
+        Some(detected) => Err((
+            format!(
+                "{} records {} but Cargo.toml depends on {}",
+                manifest::FILE,
+                framework,
+                detected
+            ),
+            format!("Set framework = \"{}\" in {}", detected, manifest::FILE),
+        )),
+        None => Err((
+            format!("{} is not a dependency in Cargo.toml", framework),
+            format!("cargo add {}", framework),
+        )),
+    }
+}
+
+/// Names of the modules declared with `mod <name>;` in `content`.
+fn declared_modules(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix("pub ").unwrap_or(line);
+            Some(line.strip_prefix("mod ")?.strip_suffix(';')?.to_string())
+        })
+        .collect()
+}
+
+fn check_modules(project_dir: &Path) -> Vec<Check> {
+    let src = project_dir.join("src");
+    let root = ["main.rs", "lib.rs"]
+        .into_iter()
+        .map(|file| src.join(file))
+        .find(|path| path.is_file());
+    let Some(root) = root else {
+        return vec![Err((
+            "Neither src/main.rs nor src/lib.rs exists".to_string(),
+            "Restore the crate root, e.g. with git checkout".to_string(),
+        ))];
+    };
+    let root_name = root
+        .strip_prefix(project_dir)
+        .unwrap_or(&root)
+        .display()
+        .to_string();
+    let declared = declared_modules(&fs::read_to_string(&root).unwrap_or_default());
+
+    let mut checks = Vec::new();
+    for module in &declared {
+        if !src.join(format!("{}.rs", module)).is_file()
+            && !src.join(module).join("mod.rs").is_file()
+        {
+            checks.push(Err((
+                format!(
+                    "{} declares `mod {};` but src/{}/mod.rs is missing",
+                    root_name, module, module
+                ),
+                format!("Create src/{}/mod.rs or remove the declaration", module),
+            )));
+        }
+    }
+
+    // Module directories the crate root never declares are not compiled
+    if let Ok(entries) = fs::read_dir(&src) {
+        let mut undeclared: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().join("mod.rs").is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|module| !declared.contains(module))
+            .collect();
+        undeclared.sort();
+        for module in undeclared {
+            checks.push(Err((
+                format!("src/{}/ is not declared in {}", module, root_name),
+                format!("Add `mod {};` to {}", module, root_name),
+            )));
+        }
+    }
+
+    if checks.is_empty() {
+        checks.push(Ok(format!(
+            "Modules declared in {} exist ({})",
+            root_name,
+            declared.len()
+        )));
+    }
+    checks
+}
+
+/// Route groups created by `generate route` have to be registered below the
+/// routes marker to be served.
+fn check_router(project_dir: &Path, framework: &str) -> Vec<Check> {
+    let Some(marker_file) = generate::find_routes_marker(project_dir) else {
+        return vec![Err((
+            format!("No `{}` marker in the router", generate::ROUTES_MARKER),
+            format!(
+                "Add `{}` where route groups are registered, generate route inserts them there",
+                generate::ROUTES_MARKER
+            ),
+        ))];
+    };
+    let marker_name = marker_file
+        .strip_prefix(project_dir)
+        .unwrap_or(&marker_file)
+        .display()
+        .to_string();
+    let router = fs::read_to_string(&marker_file).unwrap_or_default();
+    let groups_dir = match marker_file.file_name() {
+        Some(name) if name == "mod.rs" => marker_file.parent().map(Path::to_path_buf),
+        _ => Some(project_dir.join("src").join("routes")),
+    };
+    let entry_point = if framework == "axum" {
+        "router"
+    } else {
+        "scope"
+    };
+
+    let mut checks = Vec::new();
+    let mut groups: Vec<String> = groups_dir
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            fs::read_to_string(entry.path())
+                .is_ok_and(|content| content.contains(&format!("pub fn {}() ->", entry_point)))
+        })
+        .filter_map(|entry| Some(entry.path().file_stem()?.to_str()?.to_string()))
+        .filter(|group| group != "mod")
+        .collect();
+    groups.sort();
+    for group in &groups {
+        let call = format!("{}::{}()", group, entry_point);
+        if !router.contains(&call) {
+            checks.push(Err((
+                format!(
+                    "Route group '{}' is not registered in {}",
+                    group, marker_name
+                ),
+                format!(
+                    "Add a line calling `{}` below `{}`",
+                    call,
+                    generate::ROUTES_MARKER
+                ),
+            )));
+        }
+    }
+
+    if checks.is_empty() {
+        checks.push(Ok(format!(
+            "Router in {} registers every route group ({})",
+            marker_name,
+            groups.len()
+        )));
+    }
+    checks
+}
+
+fn check_owned_files(project_dir: &Path, manifest: &Manifest) -> Vec<Check> {
+    let checks: Vec<Check> = manifest
+        .templates
+        .keys()
+        .filter(|path| !project_dir.join(manifest::base_path(path)).is_file())
+        .map(|path| {
+            Err((
+                format!("{} has no base copy in {}", path, manifest::BASE_DIR),
+                "Run forgeit upgrade once and review the conflicts it reports".to_string(),
+            ))
+        })
+        .collect();
+    if checks.is_empty() {
+        vec![Ok(format!(
+            "Template-owned files have base copies ({})",
+            manifest.templates.len()
+        ))]
+    } else {
+        checks
+    }
+}
+
+pub fn doctor() -> Result<()> {
+    let mut results = Results::default();
+
+    say!("Environment:");
+    report(
+        check_tool("cargo", MIN_RUST, "https://rustup.rs"),
+        &mut results,
+    );
+    report(
+        check_tool("rustc", MIN_RUST, "https://rustup.rs"),
+        &mut results,
+    );
+    report(
+        check_tool("git", MIN_GIT, "https://git-scm.com"),
+        &mut results,
+    );
+    report(check_network(), &mut results);
+
+    let project_dir = Path::new(".");
+    match Manifest::read(project_dir) {
+        Ok(None) => say!("\nNo {} here, skipping the project checks", manifest::FILE),
+        Err(err) => {
+            say!("\nProject:");
+            report(
+                Err((
+                    err.to_string(),
+                    format!("Fix the TOML syntax of {}", manifest::FILE),
+                )),
+                &mut results,
+            );
+        }
+        Ok(Some(manifest)) => {
+            say!("\nProject:");
+            report(Ok(format!("{} is valid", manifest::FILE)), &mut results);
+            report(check_framework(project_dir, &manifest), &mut results);
+            // Workspaces keep their modules in the member crates
+            if project_dir.join("src").is_dir() {
+                for check in check_modules(project_dir) {
+                    report(check, &mut results);
+                }
+            }
+            // Libraries have no server to route to
+            let framework = manifest.framework.as_deref().unwrap_or_default();
+            if matches!(framework, "axum" | "actix-web")
+                && manifest.arch.as_deref() != Some(templates::LIB_ARCH)
+                && project_dir.join("src").is_dir()
+            {
+                for check in check_router(project_dir, framework) {
+                    report(check, &mut results);
+                }
+            }
+            for check in check_owned_files(project_dir, &manifest) {
+                report(check, &mut results);
+            }
+        }
+    }
+
+    output::set("checks", &results.checks);
+    if results.failures > 0 {
+        return Err(Error::user(format!("{} checks failed", results.failures)));
+    }
+    say!("\n✅ Everything looks good");
+    Ok(())
+}
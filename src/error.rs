@@ -0,0 +1,68 @@
+use std::{fmt, io, process::ExitCode};
+
+/// Failure of a command, reported as a message and a distinct exit code.
+#[derive(Debug)]
+pub enum Error {
+    /// Invalid input, or a project state the user has to fix first.
+    User(String),
+    /// Something outside forgeit failed: cargo, the file system or the network.
+    Environment(String),
+    /// A bug in forgeit.
+    Internal(String),
+}
+
+impl Error {
+    pub fn user(message: impl Into<String>) -> Self {
+        Error::User(message.into())
+    }
+
+    pub fn environment(message: impl Into<String>) -> Self {
+        Error::Environment(message.into())
+    }
+
+    /// Exit code of the process: 2 for user errors (the code clap uses for
+    /// invalid arguments), 3 for environment errors and 4 for internal ones.
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Error::User(_) => ExitCode::from(2),
+            Error::Environment(_) => ExitCode::from(3),
+            Error::Internal(_) => ExitCode::from(4),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::User(message) | Error::Environment(message) => write!(f, "{}", message),
+            Error::Internal(message) => write!(
+                f,
+                "internal error: {}\n👉 Please report this at {}/issues",
+                message,
+                env!("CARGO_PKG_REPOSITORY")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Turns I/O failures into environment errors with a message saying what
+/// forgeit was doing.
+pub trait Context<T> {
+    fn context(self, message: &str) -> Result<T>;
+
+    fn with_context(self, message: impl FnOnce() -> String) -> Result<T>;
+}
+
+impl<T> Context<T> for io::Result<T> {
+    fn context(self, message: &str) -> Result<T> {
+        self.with_context(|| message.to_string())
+    }
+
+    fn with_context(self, message: impl FnOnce() -> String) -> Result<T> {
+        self.map_err(|err| Error::Environment(format!("{}: {}", message(), err)))
+    }
+}
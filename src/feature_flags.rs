@@ -0,0 +1,30 @@
+use std::path::{Path, PathBuf};
+
+use crate::{error::Result, templates};
+
+/// Name of the built-in `--with` option.
+pub const WITH: &str = "feature-flags";
+
+/// Module of the flags, declared in main.rs.
+pub const MODULES: [&str; 1] = ["flags"];
+
+/// Feature of the generated project fetching the flags from Flipt.
+pub const FLIPT_FEATURE: &str = "flipt";
+
+/// `src/flags/mod.rs`: the provider trait with its env and file backed
+/// default, the Flipt client and the extractor of flag-gated routes.
+pub fn file(context: &templates::TemplateContext) -> Result<(PathBuf, String)> {
+    Ok((
+        Path::new("src").join(MODULES[0]).join("mod.rs"),
+        templates::render("feature-flags/mod.rs", context)?,
+    ))
+}
+
+/// Route of the example handler gated behind the `beta` flag, registered
+/// below the routes marker with full paths like the health route.
+pub fn route(framework: &str) -> &'static str {
+    match framework {
+        "axum" => r#".route("/beta", axum::routing::get(crate::flags::beta))"#,
+        _ => r#".route("/beta", actix_web::web::get().to(crate::flags::beta))"#,
+    }
+}
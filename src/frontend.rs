@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use crate::{error::Result, templates};
+
+/// Module of the page handlers, declared in main.rs.
+pub const MODULES: [&str; 1] = ["pages"];
+
+/// What `--frontend askama-htmx` writes, keyed by the built-in template of
+/// each file. askama reads the templates from `templates/` when building.
+const FILES: [(&str, &str); 5] = [
+    ("frontend/pages.rs", "src/pages/mod.rs"),
+    ("frontend/base.html", "templates/base.html"),
+    ("frontend/index.html", "templates/index.html"),
+    ("frontend/items.html", "templates/partials/items.html"),
+    ("frontend/app.css", "static/app.css"),
+];
+
+/// The page handlers, the askama layout, page and partial they render, and
+/// the stylesheet served next to them.
+pub fn files(context: &templates::TemplateContext) -> Result<Vec<(PathBuf, String)>> {
+    FILES
+        .into_iter()
+        .map(|(template, file)| Ok((PathBuf::from(file), templates::render(template, context)?)))
+        .collect()
+}
+
+/// Paths of the page and the partials with their method routers. The item
+/// routes of axum share a path, their handlers come as one method router.
+pub fn routes(framework: &str) -> Vec<(&'static str, &'static str)> {
+    match framework {
+        "axum" => vec![
+            ("/admin", "axum::routing::get(crate::pages::index)"),
+            ("/admin/items", "crate::pages::items_route()"),
+        ],
+        _ => vec![
+            ("/admin", "actix_web::web::get().to(crate::pages::index)"),
+            (
+                "/admin/items",
+                "actix_web::web::get().to(crate::pages::items)",
+            ),
+            (
+                "/admin/items",
+                "actix_web::web::post().to(crate::pages::add_item)",
+            ),
+        ],
+    }
+}
+
+/// The call mounting the assets of `static/` at `/static`.
+pub fn assets(framework: &str) -> &'static str {
+    match framework {
+        "axum" => r#".nest_service("/static", crate::pages::assets())"#,
+        _ => ".service(crate::pages::assets())",
+    }
+}
@@ -0,0 +1,72 @@
+use git2::Repository;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    error::{Context, Error, Result},
+    templates,
+};
+
+/// Name of the built-in `--with` option.
+pub const WITH: &str = "git-hooks";
+
+/// Directory of the hooks, committed with the project.
+const DIR: &str = ".githooks";
+
+/// Hooks written by `--with git-hooks`, keyed by their template.
+const HOOKS: [(&str, &str); 1] = [("git-hooks/pre-commit", "pre-commit")];
+
+/// The hook scripts, below [`DIR`].
+pub fn files(context: &templates::TemplateContext) -> Result<Vec<(PathBuf, String)>> {
+    HOOKS
+        .into_iter()
+        .map(|(template, hook)| {
+            Ok((
+                Path::new(DIR).join(hook),
+                templates::render(template, context)?,
+            ))
+        })
+        .collect()
+}
+
+/// Makes the hooks of `project_dir` executable and, with `wire`, points
+/// `core.hooksPath` of its repository at them, which has to be repeated in
+/// every clone.
+pub fn install(project_dir: &Path, wire: bool) -> Result<()> {
+    #[cfg(unix)]
+    for (_, hook) in HOOKS {
+        use std::{fs, os::unix::fs::PermissionsExt};
+        let path = project_dir.join(DIR).join(hook);
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make {} executable", path.display()))?;
+    }
+
+    if !wire {
+        return Ok(());
+    }
+    let git_error = |err: git2::Error| {
+        Error::environment(format!("Failed to set core.hooksPath: {}", err.message()))
+    };
+    // Without a repository there is nothing to wire the hooks into yet
+    let Ok(repo) = Repository::discover(project_dir) else {
+        return Ok(());
+    };
+    let Some(workdir) = repo.workdir() else {
+        return Ok(());
+    };
+    // The path is relative to the top of the repository, which may hold the
+    // project in a subdirectory
+    let project = project_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", project_dir.display()))?;
+    let workdir = workdir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", workdir.display()))?;
+    let hooks_path = project
+        .strip_prefix(&workdir)
+        .unwrap_or(Path::new(""))
+        .join(DIR);
+    tracing::debug!("setting core.hooksPath to {}", hooks_path.display());
+    repo.config()
+        .and_then(|mut config| config.set_str("core.hooksPath", &hooks_path.to_string_lossy()))
+        .map_err(git_error)
+}
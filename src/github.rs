@@ -0,0 +1,218 @@
+use git2::{Cred, PushOptions, RemoteCallbacks, Repository};
+use serde_json::{Value, json};
+use std::{env, path::Path};
+
+use crate::{
+    error::{Error, Result},
+    network,
+    output::{self, say},
+};
+
+/// Variables the token is read from, the first that is set wins.
+const TOKEN_VARS: [&str; 2] = ["GITHUB_TOKEN", "GH_TOKEN"];
+
+/// The repository `--github` creates.
+#[derive(Debug, Clone)]
+pub struct GithubRepo {
+    pub owner: String,
+    pub name: String,
+    pub public: bool,
+    /// Require reviewed pull requests on the default branch.
+    pub protect: bool,
+    pub topics: Vec<String>,
+}
+
+impl GithubRepo {
+    /// Parses `OWNER/NAME`, checking the token is there before anything is
+    /// scaffolded.
+    pub fn parse(spec: &str) -> Result<GithubRepo> {
+        let valid = |part: &str| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        };
+        let Some((owner, name)) = spec
+            .split_once('/')
+            .filter(|(owner, name)| valid(owner) && valid(name))
+        else {
+            return Err(Error::user(format!(
+                "'{}' is not a GitHub repository\n👉 Pass --github like my-org/my-service",
+                spec
+            )));
+        };
+        network::require("--github")?;
+        token()?;
+        Ok(GithubRepo {
+            owner: owner.to_string(),
+            name: name.to_string(),
+            public: false,
+            protect: false,
+            topics: Vec::new(),
+        })
+    }
+}
+
+fn token() -> Result<String> {
+    TOKEN_VARS
+        .iter()
+        .find_map(|var| env::var(var).ok().filter(|token| !token.is_empty()))
+        .ok_or_else(|| {
+            Error::user(format!(
+                "--github needs a token with the repo scope\n👉 Set {} (or {}), e.g. with gh auth token",
+                TOKEN_VARS[0], TOKEN_VARS[1]
+            ))
+        })
+}
+
+/// GitHub API root, `FORGEIT_GITHUB_API` for GitHub Enterprise, e.g.
+/// `https://github.example.com/api/v3`.
+fn api_url() -> String {
+    env::var("FORGEIT_GITHUB_API")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| "https://api.github.com".to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Calls the GitHub API, returning the JSON it answered with. Failures carry
+/// the message GitHub gives, like a repository name that is taken.
+fn api(method: &str, path: &str, token: &str, body: Option<Value>) -> Result<Value> {
+    let url = format!("{}{}", api_url(), path);
+    tracing::debug!("{} {}", method, url);
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let request = ureq::http::Request::builder()
+        .method(method)
+        .uri(&url)
+        .header("Accept", "application/vnd.github+json")
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", concat!("forgeit/", env!("CARGO_PKG_VERSION")))
+        .header("X-GitHub-Api-Version", "2022-11-28");
+    let internal = |err: ureq::http::Error| Error::Internal(format!("Invalid request: {}", err));
+    let result = match body {
+        Some(body) => agent.run(
+            request
+                .header("Content-Type", "application/json")
+                .body(body.to_string())
+                .map_err(internal)?,
+        ),
+        None => agent.run(request.body(()).map_err(internal)?),
+    };
+    let mut response =
+        result.map_err(|err| Error::environment(format!("Failed to reach {}: {}", url, err)))?;
+    let status = response.status();
+    let text = response.body_mut().read_to_string().unwrap_or_default();
+    let json: Value = serde_json::from_str(&text).unwrap_or(Value::Null);
+    if !status.is_success() {
+        let message = json["message"].as_str().unwrap_or(status.as_str());
+        // Validation errors explain themselves in the list
+        let details: Vec<&str> = json["errors"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|error| error["message"].as_str())
+            .collect();
+        let reason = if details.is_empty() {
+            message.to_string()
+        } else {
+            format!("{} ({})", message, details.join(", "))
+        };
+        return Err(Error::environment(format!(
+            "GitHub answered {} {}: {}",
+            method, path, reason
+        )));
+    }
+    Ok(json)
+}
+
+/// Creates the repository, sets it as `origin` of the project and pushes
+/// `branch` to it, then applies the topics and the branch protection.
+/// Returns the repository's web URL.
+pub fn publish(project_dir: &Path, repo: &GithubRepo, branch: &str) -> Result<String> {
+    let token = token()?;
+    let step = output::step(format!(
+        "Create the GitHub repository {}/{}",
+        repo.owner, repo.name
+    ));
+    let user = api("GET", "/user", &token, None)?;
+    // Repositories of the token's own user are created through another endpoint
+    let path = if user["login"].as_str() == Some(repo.owner.as_str()) {
+        "/user/repos".to_string()
+    } else {
+        format!("/orgs/{}/repos", repo.owner)
+    };
+    say!("Creating {}/{} on GitHub", repo.owner, repo.name);
+    let created = api(
+        "POST",
+        &path,
+        &token,
+        Some(json!({ "name": repo.name, "private": !repo.public })),
+    )?;
+    let clone_url = created["clone_url"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let html_url = created["html_url"].as_str().unwrap_or_default().to_string();
+
+    say!("Pushing {} to {}", branch, clone_url);
+    push(project_dir, &clone_url, branch, &token)?;
+
+    let repo_path = format!("/repos/{}/{}", repo.owner, repo.name);
+    if !repo.topics.is_empty() {
+        say!("Adding the topics {}", repo.topics.join(", "));
+        api(
+            "PUT",
+            &format!("{}/topics", repo_path),
+            &token,
+            Some(json!({ "names": repo.topics })),
+        )?;
+    }
+    if repo.protect {
+        say!("Protecting {}", branch);
+        api(
+            "PUT",
+            &format!("{}/branches/{}/protection", repo_path, branch),
+            &token,
+            Some(json!({
+                "required_status_checks": null,
+                "enforce_admins": true,
+                "required_pull_request_reviews": { "required_approving_review_count": 1 },
+                "restrictions": null,
+            })),
+        )?;
+    }
+    step.done();
+    Ok(html_url)
+}
+
+/// Adds `url` as `origin` and pushes `branch` with the token, tracking it.
+fn push(project_dir: &Path, url: &str, branch: &str, token: &str) -> Result<()> {
+    let git_error = |err: git2::Error| {
+        Error::environment(format!("Failed to push to {}: {}", url, err.message()))
+    };
+    let repository = Repository::open(project_dir).map_err(git_error)?;
+    let mut remote = repository.remote("origin", url).map_err(git_error)?;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(|_, _, _| Cred::userpass_plaintext("x-access-token", token));
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+    let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch);
+    remote
+        .push(&[refspec.as_str()], Some(&mut options))
+        .map_err(git_error)?;
+
+    let mut config = repository.config().map_err(git_error)?;
+    config
+        .set_str(&format!("branch.{}.remote", branch), "origin")
+        .map_err(git_error)?;
+    config
+        .set_str(
+            &format!("branch.{}.merge", branch),
+            &format!("refs/heads/{}", branch),
+        )
+        .map_err(git_error)
+}
@@ -0,0 +1,133 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use toml_edit::DocumentMut;
+
+use crate::{
+    error::{Context, Error, Result},
+    output::{self, say},
+    remote::Checkout,
+};
+
+/// File at the root of a template declaring its hooks, which is not copied
+/// into the project.
+pub const TEMPLATE_FILE: &str = "hooks.toml";
+
+/// Shell commands run before and after a scaffold.
+#[derive(Debug, Default)]
+pub struct Hooks {
+    pub pre: Vec<String>,
+    pub post: Vec<String>,
+}
+
+/// Project a hook runs for, passed to it as `FORGEIT_*` environment variables.
+pub struct HookProject<'a> {
+    pub name: &'a str,
+    pub dir: &'a Path,
+    pub framework: Option<&'a str>,
+    pub arch: &'a str,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre.is_empty() && self.post.is_empty()
+    }
+
+    /// Hooks a template declares in its `hooks.toml`:
+    ///
+    /// ```toml
+    /// pre = ["./check-name.sh"]
+    /// post = ["terraform init"]
+    /// ```
+    pub fn from_template(checkout: &Checkout) -> Result<Self> {
+        let path = checkout.path().join(TEMPLATE_FILE);
+        if !path.is_file() {
+            return Ok(Hooks::default());
+        }
+        let content = fs::read_to_string(&path).context("Failed to read the template hooks")?;
+        let document: DocumentMut = content
+            .parse()
+            .map_err(|err| Error::user(format!("Failed to parse {}: {}", TEMPLATE_FILE, err)))?;
+
+        let commands = |key: &str| -> Result<Vec<String>> {
+            let Some(item) = document.get(key) else {
+                return Ok(Vec::new());
+            };
+            item.as_array()
+                .and_then(|array| {
+                    array
+                        .iter()
+                        .map(|command| command.as_str().map(str::to_string))
+                        .collect()
+                })
+                .ok_or_else(|| {
+                    Error::user(format!(
+                        "'{}' in {} must be a list of commands",
+                        key, TEMPLATE_FILE
+                    ))
+                })
+        };
+        Ok(Hooks {
+            pre: commands("pre")?,
+            post: commands("post")?,
+        })
+    }
+
+    /// Appends the hooks of `other`, which run after the ones already here.
+    pub fn extend(&mut self, other: Hooks) {
+        self.pre.extend(other.pre);
+        self.post.extend(other.post);
+    }
+
+    pub fn print(&self) {
+        for (stage, commands) in [("pre", &self.pre), ("post", &self.post)] {
+            for command in commands {
+                say!("\nWould run {}-scaffold hook: {}", stage, command);
+            }
+        }
+    }
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    }
+}
+
+/// Runs `commands` one after the other in `cwd`, stopping at the first one
+/// that fails.
+pub fn run(stage: &str, commands: &[String], cwd: &Path, project: &HookProject) -> Result<()> {
+    // The project directory may not exist yet for pre-scaffold hooks
+    let project_dir =
+        std::path::absolute(project.dir).unwrap_or_else(|_| PathBuf::from(project.dir));
+
+    for command in commands {
+        say!("Running {}-scaffold hook: {}", stage, command);
+        tracing::debug!("running hook `{}` in {}", command, cwd.display());
+        let status = shell(command)
+            .current_dir(cwd)
+            .env("FORGEIT_HOOK", stage)
+            .env("FORGEIT_PROJECT_NAME", project.name)
+            .env("FORGEIT_PROJECT_DIR", &project_dir)
+            .env("FORGEIT_FRAMEWORK", project.framework.unwrap_or_default())
+            .env("FORGEIT_ARCH", project.arch)
+            .stdout(output::child_stdout())
+            .status()
+            .with_context(|| format!("Failed to run hook `{}`", command))?;
+        if !status.success() {
+            return Err(Error::environment(format!(
+                "{}-scaffold hook `{}` failed with {}",
+                stage, command, status
+            )));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use crate::{error::Result, templates};
+
+/// Name of the built-in `--with` option.
+pub const WITH: &str = "i18n";
+
+/// Modules of the translations and the errors translated with them,
+/// declared in main.rs.
+pub const MODULES: [&str; 2] = ["error", "i18n"];
+
+/// What `--with i18n` writes, keyed by the built-in template of each file.
+const FILES: [(&str, &str); 4] = [
+    ("i18n/mod.rs", "src/i18n/mod.rs"),
+    ("i18n/error.rs", "src/error/mod.rs"),
+    ("i18n/en.ftl", "locales/en/main.ftl"),
+    ("i18n/fr.ftl", "locales/fr/main.ftl"),
+];
+
+/// The Fluent bundles with the `Accept-Language` extractor, the error
+/// module answering in the negotiated language, and the English and
+/// example French messages.
+pub fn files(context: &templates::TemplateContext) -> Result<Vec<(PathBuf, String)>> {
+    FILES
+        .into_iter()
+        .map(|(template, file)| Ok((PathBuf::from(file), templates::render(template, context)?)))
+        .collect()
+}
+
+/// Route of the handler greeting in the language of the request.
+pub fn route(framework: &str) -> &'static str {
+    match framework {
+        "axum" => r#".route("/hi/{name}", axum::routing::get(crate::i18n::greet))"#,
+        _ => r#".route("/hi/{name}", actix_web::web::get().to(crate::i18n::greet))"#,
+    }
+}
@@ -0,0 +1,107 @@
+use serde::Serialize;
+use std::path::Path;
+
+use crate::{
+    error::{Error, Result},
+    manifest::{self, Manifest},
+    output::{self, say},
+    upgrade,
+};
+
+#[derive(Serialize)]
+struct TemplateFile {
+    path: String,
+    template: String,
+    upgrade_pending: bool,
+}
+
+/// What `info --json` prints.
+#[derive(Serialize)]
+struct ProjectInfo {
+    name: Option<String>,
+    framework: Option<String>,
+    arch: Option<String>,
+    database: Option<String>,
+    features: Vec<String>,
+    /// forgeit version the templates were generated or last upgraded with.
+    template_version: String,
+    forgeit_version: &'static str,
+    templates: Vec<TemplateFile>,
+}
+
+fn or_none(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or("none")
+}
+
+pub fn info(json: bool) -> Result<()> {
+    let project_dir = Path::new(".");
+    let Some(manifest) = Manifest::read(project_dir)? else {
+        return Err(Error::user(format!(
+            "No {} in the current directory\n👉 Run info inside a project scaffolded by forgeit",
+            manifest::FILE
+        )));
+    };
+
+    let pending: Vec<String> = upgrade::pending_updates(project_dir, &manifest)?
+        .into_iter()
+        .map(|update| update.path)
+        .collect();
+    let info = ProjectInfo {
+        name: manifest.name.clone(),
+        framework: manifest::framework(project_dir)?,
+        arch: manifest.arch.clone(),
+        database: manifest::database(project_dir)?,
+        features: manifest.features.clone(),
+        template_version: manifest.version.clone(),
+        forgeit_version: env!("CARGO_PKG_VERSION"),
+        templates: manifest
+            .templates
+            .iter()
+            .map(|(path, template)| TemplateFile {
+                path: path.clone(),
+                template: template.clone(),
+                upgrade_pending: pending.contains(path),
+            })
+            .collect(),
+    };
+
+    if output::is_json() {
+        output::set("project", &info);
+        return Ok(());
+    }
+    if json {
+        let json = serde_json::to_string_pretty(&info).map_err(|err| {
+            Error::Internal(format!("Failed to encode the project info: {}", err))
+        })?;
+        say!("{}", json);
+        return Ok(());
+    }
+
+    say!("Project:   {}", or_none(&info.name));
+    say!("Framework: {}", or_none(&info.framework));
+    say!("Arch:      {}", or_none(&info.arch));
+    say!("Database:  {}", or_none(&info.database));
+    if info.features.is_empty() {
+        say!("Features:  none");
+    } else {
+        say!("Features:  {}", info.features.join(", "));
+    }
+    say!(
+        "Templates: generated with forgeit {}, this is {}",
+        info.template_version,
+        info.forgeit_version
+    );
+
+    for file in &info.templates {
+        let status = if file.upgrade_pending {
+            " (upgrade available)"
+        } else {
+            ""
+        };
+        say!("  - {} from {}{}", file.path, file.template, status);
+    }
+    if !pending.is_empty() {
+        say!("\n👉 forgeit upgrade --dry-run shows the template changes");
+    }
+    Ok(())
+}
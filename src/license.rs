@@ -0,0 +1,132 @@
+use clap::ValueEnum;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    error::{Error, Result},
+    templates,
+};
+
+/// License of a generated project, picked with `--license`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum License {
+    /// The MIT license
+    Mit,
+    /// The Apache License 2.0
+    #[value(name = "apache-2.0")]
+    Apache,
+    /// The Mozilla Public License 2.0
+    #[value(name = "mpl-2.0")]
+    Mpl,
+    /// All rights reserved, no license granted
+    Proprietary,
+}
+
+impl License {
+    /// SPDX identifier for the `license` field, `None` for a proprietary
+    /// license, which has no identifier.
+    fn spdx(self) -> Option<&'static str> {
+        match self {
+            License::Mit => Some("MIT"),
+            License::Apache => Some("Apache-2.0"),
+            License::Mpl => Some("MPL-2.0"),
+            License::Proprietary => None,
+        }
+    }
+
+    fn template(self) -> &'static str {
+        match self {
+            License::Mit => "licenses/MIT",
+            License::Apache => "licenses/Apache-2.0",
+            License::Mpl => "licenses/MPL-2.0",
+            License::Proprietary => "licenses/proprietary",
+        }
+    }
+
+    /// File of this license when the project has several, e.g. `LICENSE-MIT`.
+    fn file_name(self) -> &'static str {
+        match self {
+            License::Mit => "LICENSE-MIT",
+            License::Apache => "LICENSE-APACHE",
+            License::Mpl => "LICENSE-MPL",
+            License::Proprietary => "LICENSE",
+        }
+    }
+}
+
+/// Parses the `license` of the user config: names as in `--license`, or
+/// SPDX identifiers joined with `OR` like `MIT OR Apache-2.0`.
+pub fn parse(text: &str) -> Option<Vec<License>> {
+    text.split(" OR ")
+        .flat_map(|names| names.split(','))
+        .map(|name| License::from_str(name.trim(), true).ok())
+        .collect()
+}
+
+/// Rejects a proprietary license combined with an open one.
+pub fn check(licenses: &[License]) -> Result<()> {
+    if licenses.len() > 1 && licenses.contains(&License::Proprietary) {
+        return Err(Error::user(
+            "A proprietary license cannot be combined with another one\n👉 Pass --license proprietary on its own",
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `path` is a license file, as a template or an existing
+/// directory may ship them.
+pub fn is_license_file(path: &Path) -> bool {
+    path.parent() == Some(Path::new(""))
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("LICENSE"))
+}
+
+/// The license files of `licenses` of the project `name` with the current
+/// year: `LICENSE` for a single license, one `LICENSE-<name>` each for a
+/// dual license.
+pub fn files(licenses: &[License], name: &str, holder: &str) -> Result<Vec<(PathBuf, String)>> {
+    licenses
+        .iter()
+        .map(|license| {
+            let file = if licenses.len() == 1 {
+                "LICENSE"
+            } else {
+                license.file_name()
+            };
+            Ok((
+                PathBuf::from(file),
+                templates::render_license(license.template(), name, holder)?,
+            ))
+        })
+        .collect()
+}
+
+/// The `[package]` field of `licenses`: `license` with their SPDX
+/// expression, or `license-file` pointing at a proprietary license.
+pub fn package_field(licenses: &[License]) -> Option<(&'static str, String)> {
+    if licenses.contains(&License::Proprietary) {
+        return Some(("license-file", "LICENSE".to_string()));
+    }
+    let spdx: Vec<&str> = licenses
+        .iter()
+        .filter_map(|license| license.spdx())
+        .collect();
+    (!spdx.is_empty()).then(|| ("license", spdx.join(" OR ")))
+}
+
+/// Copyright holder of the project `name`, also its `author` in the
+/// templates: `given`, else git's user.name, else `The <name> authors`.
+pub fn holder(given: Option<String>, name: &str) -> String {
+    given
+        .or_else(git_user_name)
+        .unwrap_or_else(|| format!("The {} authors", name))
+}
+
+/// Name of the git user, the copyright holder when none is configured.
+pub fn git_user_name() -> Option<String> {
+    git2::Config::open_default()
+        .and_then(|config| config.get_string("user.name"))
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+}
@@ -0,0 +1,132 @@
+use toml_edit::{DocumentMut, InlineTable, Item, Table, value};
+
+use crate::error::{Error, Result};
+
+/// Name of the built-in `--with` option.
+pub const WITH: &str = "lint-config";
+
+/// What `--with lint-config` writes: the defaults below with the
+/// `[lint-config]` table of the user config on top.
+#[derive(Debug, Clone)]
+pub struct LintConfig {
+    /// Keys of `rustfmt.toml`.
+    pub rustfmt: Table,
+    /// `[lints]` of Cargo.toml, a table of levels per tool (`rust`, `clippy`).
+    pub lints: Table,
+    /// Lints denied in the module of the HTTP handlers, which should answer
+    /// with an error response rather than panic.
+    pub handlers: Vec<String>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        let mut rustfmt = Table::new();
+        rustfmt["max_width"] = value(100);
+        rustfmt["newline_style"] = value("Unix");
+        rustfmt["use_field_init_shorthand"] = value(true);
+        rustfmt["use_try_shorthand"] = value(true);
+
+        let mut rust = Table::new();
+        rust["unsafe_code"] = value("forbid");
+        let mut clippy = Table::new();
+        // Groups go first so single lints can override them
+        let mut all = InlineTable::new();
+        all.insert("level", "warn".into());
+        all.insert("priority", (-1).into());
+        clippy["all"] = value(all);
+        clippy["dbg_macro"] = value("warn");
+        clippy["todo"] = value("warn");
+        let mut lints = Table::new();
+        // Only the tool tables get a header
+        lints.set_implicit(true);
+        lints["rust"] = Item::Table(rust);
+        lints["clippy"] = Item::Table(clippy);
+
+        LintConfig {
+            rustfmt,
+            lints,
+            handlers: vec![
+                "clippy::unwrap_used".to_string(),
+                "clippy::expect_used".to_string(),
+            ],
+        }
+    }
+}
+
+impl LintConfig {
+    /// These settings with the `[lint-config]` table of the user config on
+    /// top: its `rustfmt` keys and lint levels replace the defaults one by
+    /// one, its `handlers` list replaces the default one. `invalid` builds
+    /// the error for a key holding the wrong type.
+    pub fn merge(
+        mut self,
+        table: &Table,
+        invalid: impl Fn(&str, &str) -> Error,
+    ) -> Result<LintConfig> {
+        for (key, item) in table.iter() {
+            match key {
+                "rustfmt" => {
+                    let Some(rustfmt) = item.as_table_like() else {
+                        return Err(invalid("lint-config.rustfmt", "a table"));
+                    };
+                    for (key, item) in rustfmt.iter() {
+                        self.rustfmt[key] = item.clone();
+                    }
+                }
+                "lints" => {
+                    let Some(tools) = item.as_table_like() else {
+                        return Err(invalid("lint-config.lints", "a table of tools"));
+                    };
+                    for (tool, levels) in tools.iter() {
+                        let key = format!("lint-config.lints.{}", tool);
+                        let Some(levels) = levels.as_table_like() else {
+                            return Err(invalid(&key, "a table of lint levels"));
+                        };
+                        if !self.lints.contains_table(tool) {
+                            self.lints[tool] = Item::Table(Table::new());
+                        }
+                        for (lint, level) in levels.iter() {
+                            self.lints[tool][lint] = level.clone();
+                        }
+                    }
+                }
+                "handlers" => {
+                    self.handlers = item
+                        .as_array()
+                        .and_then(|lints| {
+                            lints
+                                .iter()
+                                .map(|lint| lint.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .ok_or_else(|| invalid("lint-config.handlers", "an array of lint names"))?;
+                }
+                _ => {
+                    return Err(invalid(
+                        &format!("lint-config.{}", key),
+                        "one of rustfmt, lints, handlers",
+                    ));
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Content of `rustfmt.toml`.
+    pub fn rustfmt_toml(&self) -> String {
+        let mut document = DocumentMut::new();
+        for (key, item) in self.rustfmt.iter() {
+            document[key] = item.clone();
+        }
+        document.to_string()
+    }
+
+    /// Attribute denying the `handlers` lints at the top of the handler
+    /// module, empty when there are none.
+    pub fn handlers_attribute(&self) -> String {
+        if self.handlers.is_empty() {
+            return String::new();
+        }
+        format!("#![deny({})]\n\n", self.handlers.join(", "))
+    }
+}
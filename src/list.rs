@@ -0,0 +1,304 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::{
+    config, crates_io,
+    error::{Error, Result},
+    network,
+    output::{self, say},
+    plugin::{self, Extension},
+    registry, scaffold, templates,
+};
+
+/// A framework `scaffold --framework` takes.
+#[derive(Serialize)]
+struct FrameworkInfo {
+    name: String,
+    /// `built-in`, `user` or `installed`.
+    source: &'static str,
+    about: Option<&'static str>,
+    /// Version lines the templates are written for, the newest first.
+    versions: &'static [&'static str],
+    /// Newest version on crates.io, unset offline or when the lookup failed.
+    latest: Option<String>,
+    /// Scaffold options the templates work with.
+    features: &'static [&'static str],
+}
+
+#[derive(Serialize)]
+struct WithInfo {
+    name: &'static str,
+    about: &'static str,
+}
+
+#[derive(Serialize)]
+struct PluginInfo {
+    name: String,
+    generators: Vec<Extension>,
+    with: Vec<Extension>,
+}
+
+/// A stack `scaffold --stack` takes.
+#[derive(Serialize)]
+struct StackInfo {
+    name: String,
+    /// `built-in` or `user`.
+    source: &'static str,
+    about: Option<&'static str>,
+    database: Option<String>,
+    deps: Vec<String>,
+    with: Vec<String>,
+    docker: bool,
+}
+
+#[derive(Serialize)]
+struct OverriddenTemplate {
+    name: &'static str,
+    path: PathBuf,
+}
+
+/// What `list --json` prints.
+#[derive(Serialize)]
+struct Listing {
+    frameworks: Vec<FrameworkInfo>,
+    with: Vec<WithInfo>,
+    plugins: Vec<PluginInfo>,
+    presets: Vec<String>,
+    stacks: Vec<StackInfo>,
+    overridden_templates: Vec<OverriddenTemplate>,
+}
+
+fn frameworks() -> Vec<FrameworkInfo> {
+    let mut frameworks: Vec<FrameworkInfo> = templates::FRAMEWORKS
+        .iter()
+        .map(|framework| FrameworkInfo {
+            name: framework.name.to_string(),
+            source: "built-in",
+            about: Some(framework.about),
+            versions: framework.versions,
+            latest: None,
+            features: framework.features,
+        })
+        .chain(
+            templates::user_frameworks()
+                .into_iter()
+                .map(|name| (name, "user"))
+                .chain(
+                    registry::installed_templates()
+                        .into_iter()
+                        .map(|name| (name, "installed")),
+                )
+                .map(|(name, source)| FrameworkInfo {
+                    name,
+                    source,
+                    about: None,
+                    versions: &[],
+                    latest: None,
+                    features: &[],
+                }),
+        )
+        .collect();
+
+    // Installed templates bring their own dependencies, the others are
+    // added as the crate of their name
+    if network::is_offline() {
+        return frameworks;
+    }
+    let crates: Vec<&str> = frameworks
+        .iter()
+        .filter(|framework| framework.source != "installed")
+        .map(|framework| framework.name.as_str())
+        .collect();
+    let latest: Vec<Option<String>> = crates_io::releases_each(&crates)
+        .into_iter()
+        .zip(&crates)
+        .map(|(releases, name)| match releases {
+            Ok(releases) => crates_io::latest(&releases).map(|release| release.version.to_string()),
+            Err(err) => {
+                tracing::debug!("skipping the latest version of {}: {}", name, err);
+                None
+            }
+        })
+        .collect();
+    let looked_up = frameworks
+        .iter_mut()
+        .filter(|framework| framework.source != "installed");
+    for (framework, latest) in looked_up.zip(latest) {
+        framework.latest = latest;
+    }
+    frameworks
+}
+
+fn stacks(config: &config::Config) -> Vec<StackInfo> {
+    config::STACKS
+        .iter()
+        .filter(|stack| !config.stacks.contains_key(stack.name))
+        .map(|stack| StackInfo {
+            name: stack.name.to_string(),
+            source: "built-in",
+            about: Some(stack.about),
+            database: stack.database.map(str::to_string),
+            deps: stack.deps.iter().map(|dep| dep.to_string()).collect(),
+            with: Vec::new(),
+            docker: false,
+        })
+        .chain(config.stacks.iter().map(|(name, stack)| StackInfo {
+            name: name.clone(),
+            source: "user",
+            about: None,
+            database: stack.database.clone(),
+            deps: stack.deps.clone(),
+            with: stack.with.clone(),
+            docker: stack.docker,
+        }))
+        .collect()
+}
+
+/// What `stack` expands into, e.g. `--database postgres --deps redis`.
+fn stack_flags(stack: &StackInfo) -> String {
+    let mut flags = Vec::new();
+    if let Some(database) = &stack.database {
+        flags.push(format!("--database {}", database));
+    }
+    if !stack.deps.is_empty() {
+        flags.push(format!("--deps {}", stack.deps.join(",")));
+    }
+    if !stack.with.is_empty() {
+        flags.push(format!("--with {}", stack.with.join(",")));
+    }
+    if stack.docker {
+        flags.push("--docker".to_string());
+    }
+    flags.join(" ")
+}
+
+/// `0.6 to 0.8` for the version lines of a framework, the newest first.
+fn version_range(versions: &[&str]) -> String {
+    match versions {
+        [] => String::new(),
+        [only] => only.to_string(),
+        [newest, .., oldest] => format!("{} to {}", oldest, newest),
+    }
+}
+
+fn say_framework(framework: &FrameworkInfo) {
+    match framework.about {
+        Some(about) => say!("  - {}: {}", framework.name, about),
+        None => say!("  - {} ({} template)", framework.name, framework.source),
+    }
+    let mut versions = Vec::new();
+    if !framework.versions.is_empty() {
+        versions.push(format!(
+            "templates for {}",
+            version_range(framework.versions)
+        ));
+    }
+    if let Some(latest) = &framework.latest {
+        versions.push(format!("latest {} on crates.io", latest));
+    }
+    if !versions.is_empty() {
+        say!("      {}", versions.join(", "));
+    }
+    if !framework.features.is_empty() {
+        say!("      works with {}", framework.features.join(", "));
+    }
+}
+
+/// Prints the frameworks, plugins, presets, stacks and overridden templates, as a
+/// JSON document with `json`.
+pub fn list(json: bool) -> Result<()> {
+    let mut plugins = Vec::new();
+    for plugin in plugin::discover() {
+        let description = plugin.describe()?;
+        plugins.push(PluginInfo {
+            name: plugin.name,
+            generators: description.generators,
+            with: description.with,
+        });
+    }
+    let config = config::load()?;
+    let listing = Listing {
+        frameworks: frameworks(),
+        with: scaffold::BUILTIN_WITH
+            .iter()
+            .map(|&(name, about)| WithInfo { name, about })
+            .collect(),
+        plugins,
+        stacks: stacks(&config),
+        presets: config.presets.into_keys().collect(),
+        overridden_templates: templates::builtin_templates()
+            .filter_map(|name| {
+                Some(OverriddenTemplate {
+                    name,
+                    path: templates::user_template_path(name)?,
+                })
+            })
+            .collect(),
+    };
+
+    if output::is_json() {
+        output::set("frameworks", &listing.frameworks);
+        output::set("with", &listing.with);
+        output::set("plugins", &listing.plugins);
+        output::set("presets", &listing.presets);
+        output::set("stacks", &listing.stacks);
+        output::set("overridden_templates", &listing.overridden_templates);
+        return Ok(());
+    }
+    if json {
+        let json = serde_json::to_string_pretty(&listing)
+            .map_err(|err| Error::Internal(format!("Failed to encode the list: {}", err)))?;
+        say!("{}", json);
+        return Ok(());
+    }
+
+    say!("Available frameworks:");
+    for framework in &listing.frameworks {
+        say_framework(framework);
+    }
+    if network::is_offline() {
+        say!("  The latest versions are not looked up with --offline");
+    }
+
+    say!("\nBuilt-in --with options:");
+    for option in &listing.with {
+        say!("  - {}: {}", option.name, option.about);
+    }
+
+    if !listing.plugins.is_empty() {
+        say!("\nPlugins:");
+    }
+    for plugin in &listing.plugins {
+        say!("  - {}", plugin.name);
+        for generator in &plugin.generators {
+            say!("      generate {}: {}", generator.name, generator.about);
+        }
+        for option in &plugin.with {
+            say!("      --with {}: {}", option.name, option.about);
+        }
+    }
+
+    if !listing.presets.is_empty() {
+        say!("\nPresets:");
+    }
+    for name in &listing.presets {
+        say!("  - {} (scaffold --preset {})", name, name);
+    }
+
+    say!("\nStacks:");
+    for stack in &listing.stacks {
+        match stack.about {
+            Some(about) => say!("  - {}: {}", stack.name, about),
+            None => say!("  - {} (user stack)", stack.name),
+        }
+        say!("      {}", stack_flags(stack));
+    }
+
+    if !listing.overridden_templates.is_empty() {
+        say!("\nOverridden templates:");
+    }
+    for template in &listing.overridden_templates {
+        say!("  - {} ({})", template.name, template.path.display());
+    }
+    Ok(())
+}
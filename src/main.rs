@@ -1,9 +1,85 @@
-use clap::{Parser, Subcommand};
-use std::{fs, path::Path, process::Command};
+use clap::{ArgMatches, ColorChoice, CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::{
+    env,
+    ffi::OsString,
+    io::{self, Write},
+    path::PathBuf,
+    process::{Command, ExitCode},
+};
+
+mod api;
+mod app_state;
+mod completions;
+mod config;
+mod crates_io;
+mod deps;
+mod doctor;
+mod error;
+mod feature_flags;
+mod frontend;
+mod generate;
+mod git_hooks;
+mod github;
+mod hooks;
+mod i18n;
+mod info;
+mod license;
+mod lint_config;
+mod list;
+mod man;
+mod manifest;
+mod musl;
+mod network;
+mod output;
+mod package_name;
+mod payments;
+mod plugin;
+mod profiling;
+mod registry;
+mod release;
+mod remote;
+mod scaffold;
+mod schema;
+mod search;
+mod self_update;
+mod signing;
+mod suggest;
+mod templates;
+mod upgrade;
+mod uploads;
+mod wizard;
+mod workspace;
+
+use error::{Error, Result};
+use scaffold::ScaffoldArgs;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
+    /// Output format, json prints one event per line for tools wrapping forgeit
+    #[arg(long, global = true, value_enum, default_value_t)]
+    output: output::Format,
+
+    /// Log the cargo and git operations, -vv also every file and cargo's output
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Only print errors
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Print without ANSI colors, also set by the NO_COLOR variable
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Print without colors, emoji and spinners, for CI logs and limited terminals
+    #[arg(long, global = true)]
+    plain: bool,
+
+    /// Never use the network, dependencies get the pinned versions of the versions.toml template
+    #[arg(long, global = true)]
+    offline: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -11,204 +87,441 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Scaffold a new framework project
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Scaffold {
-        /// Name of the project
-        #[arg(short, long)]
-        name: String,
+        #[command(subcommand)]
+        kind: Option<ScaffoldKind>,
 
-        /// Name of the framework (e.g. axum, actix-web)
-        #[arg(short, long)]
-        framework: String,
+        /// Name of the project, converted to kebab-case when confirmed
+        #[arg(short, long, required_unless_present = "path")]
+        name: Option<String>,
 
-        /// Additional dependencies to add (e.g. dotenvy)
+        /// Directory to create the project in, defaults to the project name,
+        /// which is derived from the directory without --name
         #[arg(short, long)]
-        deps: Option<Vec<String>>,
+        path: Option<PathBuf>,
+
+        #[command(flatten)]
+        args: ScaffoldArgs,
     },
 
-    /// List available frameworks
-    List,
+    /// Scaffold into the current directory instead of creating a new one
+    Init {
+        #[command(flatten)]
+        args: ScaffoldArgs,
+    },
 
-    /// Add a dependency to the project
-    Add {
-        /// Name of the crate to add
+    /// List the frameworks with their versions, the --with options, plugins, presets and stacks
+    List {
+        /// Print the list as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Add dependencies to the project
+    Add(deps::AddArgs),
+
+    /// Search crates.io, adding the crate picked from the results
+    Search(deps::SearchArgs),
+
+    /// Remove dependencies from the project
+    Remove(deps::RemoveArgs),
+
+    /// Check crates.io for newer versions of the dependencies and upgrade them
+    UpgradeDeps(deps::UpgradeDepsArgs),
+
+    /// Add a service crate to the workspace in the current directory
+    AddService {
+        /// Name of the service
         name: String,
 
-        /// Version to use
-        #[arg(short, long, default_value = "latest")]
-        version: String,
+        /// Framework of the service, defaults to the one the workspace uses
+        #[arg(short, long, value_parser = ["axum", "actix-web"])]
+        framework: Option<String>,
     },
-}
 
-fn get_main_content(framework: &str) -> &'static str {
-    match framework {
-        "axum" => {
-            r#"use axum::{routing::get, Router};
-
-#[tokio::main]
-async fn main() {
-    let app = Router::new().route("/", get(|| async { "Hello from Axum! 🦀" }));
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await.unwrap();
-    println!("Listening on http://127.0.0.1:3000");
-    axum::serve(listener, app).await.unwrap();
-}
-"#
-        }
-        "actix-web" => {
-            r#"use actix_web::{get, App, HttpServer, Responder, HttpResponse};
+    /// Generate code inside the current project
+    Generate {
+        #[command(subcommand)]
+        kind: GenerateKind,
+    },
 
-#[get("/")]
-async fn index() -> impl Responder {
-    HttpResponse::Ok().body("Hello from Actix-web! 🦀")
-}
+    /// Find and install community templates
+    Template {
+        #[command(subcommand)]
+        kind: TemplateKind,
+    },
 
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    println!("Listening on http://127.0.0.1:3000");
-    HttpServer::new(|| App::new().service(index))
-        .bind("127.0.0.1:3000")?
-        .run()
-        .await
-}
-"#
-        }
-        _ => {
-            r#"fn main() {
-    println!("Hello, world!");
-}
-"#
-        }
-    }
+    /// Check the toolchain, network access and the project in the current directory
+    Doctor,
+
+    /// Show the scaffold choices and template status of the current project
+    Info {
+        /// Print the information as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Merge changes of the current templates into the files they generated
+    Upgrade {
+        /// Show the template changes without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Print the shell script enabling tab completion
+    Completions {
+        /// Shell to complete in
+        #[arg(value_parser = ["bash", "zsh", "fish", "powershell", "elvish"])]
+        shell: String,
+    },
+
+    /// Update forgeit to the latest release
+    SelfUpdate {
+        /// Only report whether a newer release exists
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Generate man pages, for packaging
+    #[command(hide = true)]
+    Man {
+        /// Directory to write a page per subcommand to, prints the main page otherwise
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+    },
 }
 
-fn create_module_dir(project_name: &str, module_name: &str) {
-    let module_dir = Path::new(project_name).join("src").join(module_name);
-    fs::create_dir_all(&module_dir)
-        .unwrap_or_else(|_| panic!("Failed to create {} directory", module_name));
+#[derive(Subcommand, Debug)]
+enum ScaffoldKind {
+    /// Scaffold a cargo workspace with one crate per service
+    Workspace {
+        /// Name of the workspace
+        name: String,
+
+        /// Services to create, each gets its own crate (e.g. api,worker)
+        #[arg(short, long, value_delimiter = ',', default_value = "api")]
+        services: Vec<String>,
+
+        /// Framework used by every service
+        #[arg(short, long, default_value = "axum", value_parser = ["axum", "actix-web"])]
+        framework: String,
 
-    let mod_path = module_dir.join("mod.rs");
-    fs::write(mod_path, "").unwrap_or_else(|_| panic!("Failed to create {}/mod.rs", module_name));
+        /// Task file with run, test, lint and docker targets
+        #[arg(long, value_enum, default_value_t)]
+        task_runner: templates::TaskRunner,
+    },
 }
 
-fn add_dependency(project_name: &str, dep: &str, features: Option<&str>) -> bool {
-    let mut cmd = Command::new("cargo");
-    cmd.current_dir(project_name).arg("add").arg(dep);
+#[derive(Subcommand, Debug)]
+enum TemplateKind {
+    /// Search the template index by name and description
+    Search {
+        /// Text to look for (e.g. axum)
+        query: String,
+    },
 
-    if let Some(feat) = features {
-        cmd.args(["--features", feat]);
-    }
+    /// Install a template from the index, making it usable as a framework
+    Install {
+        /// Name of the template in the index
+        name: String,
+    },
 
-    cmd.status().expect("Failed to run cargo add").success()
+    /// List the installed templates
+    List,
 }
 
-fn create_gitignore(project_name: &str) {
-    let gitignore_content = r#"# Rust
-/target/
+#[derive(Subcommand, Debug)]
+enum GenerateKind {
+    /// Generate a middleware skeleton for the project's framework
+    Middleware {
+        /// Name of the middleware (e.g. request_timer)
+        name: String,
+    },
+
+    /// Generate a timestamped SQL migration for the configured database
+    Migration {
+        /// Name of the migration (e.g. create_users)
+        name: String,
+    },
+
+    /// Generate a seeder run in order by the project's `seed` binary
+    Seeder {
+        /// Name of the seeder (e.g. users)
+        name: String,
+    },
+
+    /// Generate a service with injected dependencies and a mockable trait
+    Service {
+        /// Name of the service (e.g. users)
+        name: String,
+    },
+
+    /// Generate a library crate shared by the workspace services
+    SharedCrate {
+        /// Name of the crate (e.g. common-types)
+        name: String,
 
+        /// Services depending on the crate, defaults to every service
+        #[arg(short, long, value_delimiter = ',')]
+        services: Option<Vec<String>>,
+    },
 
-# Environment
-.env
-.env.local
-.env.*.local
+    /// Generate a route group and register it in the main router
+    Route {
+        /// URL prefix of the route group (e.g. users)
+        prefix: String,
+    },
 
+    /// Generate a webhook endpoint verifying HMAC signatures, with a service handling its events
+    Webhook {
+        /// Name of the provider sending the webhook (e.g. acme)
+        provider: String,
+
+        /// Header of the signature, x-<provider>-signature by default
+        #[arg(long)]
+        header: Option<String>,
+
+        /// Environment variable of the signing secret, <PROVIDER>_WEBHOOK_SECRET by default
+        #[arg(long)]
+        secret_env: Option<String>,
+    },
 
-"#;
+    /// Generate model structs and repositories from the tables of an existing database
+    Models {
+        /// Connection string of the database to read the schema of (e.g. postgres://localhost/app)
+        #[arg(long, value_name = "DATABASE_URL")]
+        from_db: String,
 
-    let gitignore_path = Path::new(project_name).join(".gitignore");
-    fs::write(gitignore_path, gitignore_content)
-        .unwrap_or_else(|_| panic!("Failed to create .gitignore file"));
+        /// Tables to generate models for, defaults to every table
+        #[arg(long, value_delimiter = ',')]
+        tables: Option<Vec<String>>,
+    },
+
+    /// Generate a crate with a typed reqwest client of the routes of a service
+    Client {
+        /// Workspace member to generate the client of, defaults to the only service
+        #[arg(short, long)]
+        service: Option<String>,
+    },
+
+    /// Generators provided by plugins
+    #[command(external_subcommand)]
+    Plugin(Vec<String>),
 }
 
+/// Keywords that cannot name a module, including the reserved ones.
+const KEYWORDS: &[&str] = &[
+    "_", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+fn is_module_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_lowercase() || first == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && !KEYWORDS.contains(&name)
+}
 
-fn scaffold_project(name: &str, framework: &str, deps: Option<Vec<String>>) {
-    println!("Creating new Cargo project: {}", name);
+fn is_crate_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
 
-    // Run `cargo new <name>`
-    let status = Command::new("cargo")
-        .args(["new", name])
-        .status()
-        .expect("Failed to run cargo new");
+/// Runs a cargo command, `action` describes it in the error when it fails.
+/// Cargo prints its own diagnostics, so they are not repeated here.
+fn run_cargo(cmd: &mut Command, action: &str) -> Result<()> {
+    tracing::debug!("running {}", output::command_line(cmd));
+    // Cargo's progress is noise next to forgeit's messages, so it is only
+    // shown with -vv, and its diagnostics when it fails
+    let result = if output::shows_tool_output() {
+        cmd.stdout(output::child_stdout())
+            .status()
+            .map(|status| (status, Vec::new()))
+    } else {
+        cmd.output().map(|output| (output.status, output.stderr))
+    };
+    let (status, stderr) = result.map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => {
+            Error::environment("cargo not found on PATH, install Rust from https://rustup.rs")
+        }
+        _ => Error::environment(format!("Failed to run cargo: {}", err)),
+    })?;
 
     if !status.success() {
-        eprintln!("Failed to create project '{}'", name);
-        return;
+        output::suspend(|| io::stderr().write_all(&stderr)).ok();
+        return Err(Error::environment(format!("Failed to {}", action)));
     }
+    Ok(())
+}
 
-    // Add framework dependency
-    println!("Adding {} to {}", framework, name);
-    if !add_dependency(name, framework, None) {
-        eprintln!("Failed to add framework dependency '{}'", framework);
-        return;
-    }
+fn main() -> ExitCode {
+    completions::complete();
 
-    // Add additional dependencies
-    if let Some(deps) = deps {
-        for dep in deps {
-            if !add_dependency(name, &dep, None) {
-                eprintln!("Failed to add dependency '{}'", dep);
-                return;
-            }
+    // `scaffold` without any arguments asks for them instead
+    let args: Vec<OsString> = if wizard::requested() {
+        match wizard::ask() {
+            Ok(Some(args)) => args.into_iter().map(OsString::from).collect(),
+            Ok(None) => return ExitCode::SUCCESS,
+            Err(err) => return output::fail(Some("scaffold"), &err),
         }
+    } else {
+        env::args_os().collect()
+    };
+
+    // Help and argument errors are printed before the flags are parsed
+    let mut command = Cli::command();
+    if args
+        .iter()
+        .any(|arg| arg == "--no-color" || arg == "--plain")
+    {
+        command = command.color(ColorChoice::Never);
     }
-
-    // Write main.rs based on framework
-    let main_content = get_main_content(framework);
-    let main_path = format!("{}/src/main.rs", name);
-    fs::write(&main_path, main_content).expect("Failed to write main.rs");
-
-    // Add additional dependencies for async frameworks
-    if matches!(framework, "axum" | "actix-web") {
-        add_dependency(name, "serde", Some("derive"));
-        add_dependency(name, "tokio", Some("full"));
+    let parsed = command
+        .try_get_matches_from(&args)
+        .and_then(|matches| Ok((Cli::from_arg_matches(&matches)?, matches)));
+    let (cli, matches) = match parsed {
+        Ok(parsed) => parsed,
+        Err(err) => return parse_error(err, &args),
+    };
+    output::set_format(cli.output);
+    output::set_style(cli.no_color, cli.plain);
+    output::set_verbosity(cli.quiet, cli.verbose);
+    network::set_offline(cli.offline);
+
+    let command = command_name(&matches);
+    match run(cli) {
+        Ok(()) => {
+            output::finish(&command);
+            ExitCode::SUCCESS
+        }
+        Err(err) => output::fail(Some(&command), &err),
     }
+}
 
-    // Create module directories
-    let modules = vec!["services", "models", "handlers", "routes"];
-    for module in modules {
-        create_module_dir(name, module);
+/// Subcommand path of the invocation, e.g. `generate middleware`.
+fn command_name(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, matches)) = current.subcommand() {
+        names.push(name);
+        current = matches;
     }
-
-    // Create .gitignore file
-    println!("\nCreating .gitignore file");
-    create_gitignore(name);
-
-    println!("\n✅ Project '{}' scaffolded successfully!", name);
-    println!("👉 cd {} && cargo run", name);
+    names.join(" ")
 }
 
-fn main() {
-    let cli = Cli::parse();
+/// Reports invalid arguments, as an `error` event when `--output json` is
+/// among them so wrappers can parse those failures too.
+fn parse_error(err: clap::Error, args: &[OsString]) -> ExitCode {
+    let json = args
+        .windows(2)
+        .any(|pair| pair[0] == "--output" && pair[1] == "json")
+        || args.iter().any(|arg| arg == "--output=json");
+    if !json || !err.use_stderr() {
+        err.exit();
+    }
+    output::set_format(output::Format::Json);
+    output::fail(None, &Error::user(err.render().to_string().trim_end()))
+}
 
+fn run(cli: Cli) -> Result<()> {
     match cli.command {
         Commands::Scaffold {
-            name,
-            framework,
-            deps,
+            kind:
+                Some(ScaffoldKind::Workspace {
+                    name,
+                    services,
+                    framework,
+                    task_runner,
+                }),
+            ..
         } => {
-            scaffold_project(&name, &framework, deps);
-        }
-        Commands::List => {
-            println!("Available frameworks:");
-            println!("  - axum");
-            println!("  - actix-web");
+            if let Some(invalid) = services
+                .iter()
+                .find(|service| !is_module_name(service) || *service == "common")
+            {
+                return Err(Error::user(format!(
+                    "'{}' is not a valid service name",
+                    invalid
+                )));
+            }
+
+            workspace::scaffold_workspace(&name, &services, &framework, task_runner)
         }
-        Commands::Add { name, version } => {
-            let status = if version == "latest" {
-                Command::new("cargo")
-                    .args(["add", &name])
-                    .status()
-                    .expect("Failed to run cargo add")
-            } else {
-                Command::new("cargo")
-                    .args(["add", &format!("{}@{}", name, version)])
-                    .status()
-                    .expect("Failed to run cargo add")
+        Commands::Scaffold {
+            kind: None,
+            name,
+            path,
+            args,
+        } => {
+            let name = match (name, &path) {
+                (Some(name), _) => package_name::resolve(&name)?,
+                (None, Some(path)) => package_name::derive(path)?,
+                (None, None) => {
+                    return Err(Error::Internal("clap requires --name".to_string()));
+                }
             };
-
-            if status.success() {
-                println!("✅  Added {} successfully!", name);
-            } else {
-                eprintln!("❌ Failed to add {}", name);
+            scaffold::scaffold_project(&name, path.as_deref(), &args.into_options()?)
+        }
+        Commands::Init { args } => scaffold::init_project(&args.into_options()?),
+        Commands::List { json } => list::list(json),
+        Commands::Add(args) => deps::add(&args),
+        Commands::Search(args) => deps::search(&args),
+        Commands::Remove(args) => deps::remove(&args),
+        Commands::UpgradeDeps(args) => deps::upgrade_deps(&args),
+        Commands::AddService { name, framework } => {
+            if !is_module_name(&name) || name == "common" {
+                return Err(Error::user(format!(
+                    "'{}' is not a valid service name",
+                    name
+                )));
             }
+
+            workspace::add_service(&name, framework.as_deref())
         }
+        Commands::Generate { kind } => match kind {
+            GenerateKind::Middleware { name } => generate::generate_middleware(&name),
+            GenerateKind::Migration { name } => generate::generate_migration(&name),
+            GenerateKind::Seeder { name } => generate::generate_seeder(&name),
+            GenerateKind::Service { name } => generate::generate_service(&name),
+            GenerateKind::Route { prefix } => generate::generate_route(&prefix),
+            GenerateKind::Webhook {
+                provider,
+                header,
+                secret_env,
+            } => generate::generate_webhook(&provider, header, secret_env),
+            GenerateKind::Models { from_db, tables } => {
+                generate::generate_models(&from_db, tables.as_deref())
+            }
+            GenerateKind::Client { service } => generate::generate_client(service.as_deref()),
+            GenerateKind::Plugin(args) => plugin::generate(args),
+            GenerateKind::SharedCrate { name, services } => {
+                if !is_crate_name(&name) {
+                    return Err(Error::user(format!("'{}' is not a valid crate name", name)));
+                }
+
+                workspace::generate_shared_crate(&name, services)
+            }
+        },
+        Commands::Template { kind } => match kind {
+            TemplateKind::Search { query } => registry::search(&query),
+            TemplateKind::Install { name } => registry::install(&name),
+            TemplateKind::List => registry::list(),
+        },
+        Commands::Doctor => doctor::doctor(),
+        Commands::Info { json } => info::info(json),
+        Commands::Upgrade { dry_run } => upgrade::upgrade(dry_run),
+        Commands::Completions { shell } => completions::print(&shell),
+        Commands::SelfUpdate { check } => self_update::self_update(check),
+        Commands::Man { out_dir } => man::man(out_dir.as_deref()),
     }
 }
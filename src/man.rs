@@ -0,0 +1,26 @@
+use clap::CommandFactory;
+use std::{fs, io, path::Path};
+
+use crate::{
+    Cli,
+    error::{Context, Result},
+    output::say,
+};
+
+/// Writes a man page for forgeit and one for every subcommand into
+/// `out_dir`, e.g. `forgeit.1` and `forgeit-scaffold.1`. Without a directory
+/// the page of forgeit itself is printed.
+pub fn man(out_dir: Option<&Path>) -> Result<()> {
+    let Some(out_dir) = out_dir else {
+        return clap_mangen::Man::new(Cli::command())
+            .render(&mut io::stdout())
+            .context("Failed to print the man page");
+    };
+
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    clap_mangen::generate_to(Cli::command(), out_dir)
+        .with_context(|| format!("Failed to write the man pages to {}", out_dir.display()))?;
+    say!("✅ Wrote the man pages to {}", out_dir.display());
+    Ok(())
+}
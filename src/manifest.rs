@@ -0,0 +1,239 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use toml_edit::{Array, DocumentMut, Table, value};
+
+use crate::{
+    error::{Context, Error, Result},
+    generate, output,
+};
+
+/// File recording the scaffold choices in the root of generated projects.
+pub const FILE: &str = ".scaffolder.toml";
+
+/// Directory keeping the generated version of every file a template owns,
+/// the common ancestor `upgrade` merges template changes against.
+pub const BASE_DIR: &str = ".scaffolder/base";
+
+/// Scaffold choices of a project, read back by `generate` and `add-service`
+/// so later code matches what the project was created with.
+#[derive(Debug, Default)]
+pub struct Manifest {
+    pub version: String,
+    pub name: Option<String>,
+    pub framework: Option<String>,
+    /// Version line the framework's templates were generated for, e.g. `0.6`.
+    pub framework_version: Option<String>,
+    pub arch: Option<String>,
+    pub database: Option<String>,
+    /// Runtime picked with `--target`, unset for a server.
+    pub target: Option<String>,
+    /// Platform picked with `--deploy`.
+    pub deploy: Option<String>,
+    /// Frontend picked with `--frontend`.
+    pub frontend: Option<String>,
+    /// Port picked with `--port`, unset for the default one.
+    pub port: Option<u16>,
+    pub features: Vec<String>,
+    /// Options of `--with`, which change the files of the templates, e.g.
+    /// the Dockerfile of `musl`.
+    pub with: Vec<String>,
+    /// Files owned by a template, keyed by their path in the project.
+    pub templates: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    pub fn new(name: &str, framework: Option<&str>, arch: &str, features: &[String]) -> Self {
+        Manifest {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            name: Some(name.to_string()),
+            framework: framework
+                .filter(|name| !name.is_empty())
+                .map(str::to_string),
+            framework_version: None,
+            arch: Some(arch.to_string()).filter(|arch| !arch.is_empty()),
+            database: None,
+            target: None,
+            deploy: None,
+            frontend: None,
+            port: None,
+            features: features.to_vec(),
+            with: Vec::new(),
+            templates: BTreeMap::new(),
+        }
+    }
+
+    /// Records that `path` was generated from `template`.
+    pub fn own(mut self, path: &str, template: &str) -> Self {
+        self.templates
+            .insert(path.to_string(), template.to_string());
+        self
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut document = DocumentMut::new();
+        document["version"] = value(&self.version);
+        for (key, field) in [
+            ("name", &self.name),
+            ("framework", &self.framework),
+            ("framework_version", &self.framework_version),
+            ("arch", &self.arch),
+            ("database", &self.database),
+            ("target", &self.target),
+            ("deploy", &self.deploy),
+            ("frontend", &self.frontend),
+        ] {
+            if let Some(field) = field {
+                document[key] = value(field);
+            }
+        }
+        if let Some(port) = self.port {
+            document["port"] = value(i64::from(port));
+        }
+        document["features"] = value(self.features.iter().collect::<Array>());
+        if !self.with.is_empty() {
+            document["with"] = value(self.with.iter().collect::<Array>());
+        }
+        if !self.templates.is_empty() {
+            let mut templates = Table::new();
+            for (path, template) in &self.templates {
+                templates[path.as_str()] = value(template);
+            }
+            document["templates"] = templates.into();
+        }
+
+        format!(
+            "# Scaffold choices, read by `forgeit generate` to match the project\n{}",
+            document
+        )
+    }
+
+    /// Manifest of the project in `project_dir`, `None` for projects not
+    /// created by forgeit.
+    pub fn read(project_dir: &Path) -> Result<Option<Self>> {
+        let Some(document) = read_document(project_dir)? else {
+            return Ok(None);
+        };
+
+        let text = |key: &str| {
+            document
+                .get(key)
+                .and_then(|item| item.as_str())
+                .map(str::to_string)
+        };
+        let strings = |key: &str| -> Vec<String> {
+            document
+                .get(key)
+                .and_then(|item| item.as_array())
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|value| value.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Ok(Some(Manifest {
+            version: text("version").unwrap_or_default(),
+            name: text("name"),
+            framework: text("framework"),
+            framework_version: text("framework_version"),
+            arch: text("arch"),
+            database: text("database"),
+            target: text("target"),
+            deploy: text("deploy"),
+            frontend: text("frontend"),
+            port: document
+                .get("port")
+                .and_then(|item| item.as_integer())
+                .and_then(|port| u16::try_from(port).ok()),
+            features: strings("features"),
+            with: strings("with"),
+            templates: document
+                .get("templates")
+                .and_then(|item| item.as_table())
+                .map(|templates| {
+                    templates
+                        .iter()
+                        .filter_map(|(path, template)| {
+                            Some((path.to_string(), template.as_str()?.to_string()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }))
+    }
+}
+
+fn read_document(project_dir: &Path) -> Result<Option<DocumentMut>> {
+    let Ok(content) = fs::read_to_string(project_dir.join(FILE)) else {
+        return Ok(None);
+    };
+    content
+        .parse()
+        .map(Some)
+        .map_err(|err| Error::user(format!("Failed to parse {}: {}", FILE, err)))
+}
+
+/// Edits the manifest in place, keeping its comments and formatting. Does
+/// nothing for projects without a manifest.
+pub fn update(project_dir: &Path, edit: impl FnOnce(&mut DocumentMut)) -> Result<()> {
+    let Some(mut document) = read_document(project_dir)? else {
+        return Ok(());
+    };
+    edit(&mut document);
+    let path = project_dir.join(FILE);
+    fs::write(&path, document.to_string()).with_context(|| format!("Failed to update {}", FILE))?;
+    output::file(&path);
+    Ok(())
+}
+
+/// Path of the base copy of the template-owned file `path`.
+pub fn base_path(path: &str) -> PathBuf {
+    Path::new(BASE_DIR).join(path)
+}
+
+/// Stores `content` as the base copy of the template-owned file `path`.
+pub fn write_base(project_dir: &Path, path: &str, content: &str) -> Result<()> {
+    let base = project_dir.join(base_path(path));
+    if let Some(parent) = base.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&base, content).with_context(|| format!("Failed to write {}", base.display()))?;
+    output::file(&base);
+    Ok(())
+}
+
+/// Records a file generated after the scaffold, like a middleware module,
+/// as owned by `template` so `upgrade` keeps it current.
+pub fn own(project_dir: &Path, path: &str, template: &str, content: &str) -> Result<()> {
+    if !project_dir.join(FILE).is_file() {
+        return Ok(());
+    }
+    update(project_dir, |document| {
+        if !document.contains_table("templates") {
+            document["templates"] = Table::new().into();
+        }
+        document["templates"][path] = value(template);
+    })?;
+    write_base(project_dir, path, content)
+}
+
+/// Framework of the project in `project_dir`: the one in its manifest, or
+/// the one detected from Cargo.toml for projects without a manifest.
+pub fn framework(project_dir: &Path) -> Result<Option<String>> {
+    Ok(Manifest::read(project_dir)?
+        .and_then(|manifest| manifest.framework)
+        .or_else(|| generate::detect_framework(project_dir).map(str::to_string)))
+}
+
+/// SQL dialect of the project in `project_dir`, from its manifest or the
+/// features of its `sqlx` dependency.
+pub fn database(project_dir: &Path) -> Result<Option<String>> {
+    Ok(Manifest::read(project_dir)?
+        .and_then(|manifest| manifest.database)
+        .or_else(|| generate::detect_database(project_dir).map(str::to_string)))
+}
@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+use crate::{error::Result, templates};
+
+/// Name of the built-in `--with` option.
+pub const WITH: &str = "musl";
+
+/// What `--with musl` writes, keyed by the built-in template of each file.
+const FILES: [(&str, &str); 2] = [
+    ("musl/config.toml", ".cargo/config.toml"),
+    ("musl/Cross.toml", "Cross.toml"),
+];
+
+/// The cargo config linking the musl target statically, and the
+/// `Cross.toml` of cross, which builds it for other architectures in a
+/// container with their C toolchain.
+pub fn files(context: &templates::TemplateContext) -> Result<Vec<(PathBuf, String)>> {
+    FILES
+        .into_iter()
+        .map(|(template, file)| Ok((PathBuf::from(file), templates::render(template, context)?)))
+        .collect()
+}
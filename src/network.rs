@@ -0,0 +1,30 @@
+use std::{
+    env,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::error::{Error, Result};
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Turns the network off for `--offline`, or when cargo's own
+/// `CARGO_NET_OFFLINE` is set.
+pub fn set_offline(offline: bool) {
+    let cargo_offline = env::var("CARGO_NET_OFFLINE").is_ok_and(|value| value == "true");
+    OFFLINE.store(offline || cargo_offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fails when offline, `what` is what needed the network.
+pub fn require(what: &str) -> Result<()> {
+    if is_offline() {
+        return Err(Error::user(format!(
+            "{} needs the network, which --offline turns off",
+            what
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,373 @@
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use std::{
+    borrow::Cow,
+    env, fmt,
+    io::{self, IsTerminal},
+    path::Path,
+    process::{Command, ExitCode, Stdio},
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicU8, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use tracing::level_filters::LevelFilter;
+
+use crate::error::Error;
+
+/// How forgeit reports what it does, picked with `--output`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// Messages for people
+    #[default]
+    Text,
+    /// One JSON object per line: `message` and `file` events, then a `result` or an `error`
+    Json,
+}
+
+/// How much forgeit prints, from `-q` to `-vv`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Errors only.
+    Quiet,
+    /// The messages, without the output of cargo.
+    Normal,
+    /// Also the cargo, git and plugin operations, logged at debug level.
+    Verbose,
+    /// Also every file written, and cargo's own output.
+    Trace,
+}
+
+/// ASCII stand-ins of the symbols in messages, printed with `--plain`.
+const PLAIN: [(&str, &str); 7] = [
+    ("❌ ", "error: "),
+    ("⚠️ ", "warning: "),
+    ("✅ ", ""),
+    ("👉 ", "hint: "),
+    ("├── ", "|-- "),
+    ("└── ", "`-- "),
+    ("│   ", "|   "),
+];
+
+static JSON: AtomicBool = AtomicBool::new(false);
+static COLOR: AtomicBool = AtomicBool::new(true);
+static PLAIN_TEXT: AtomicBool = AtomicBool::new(false);
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+/// Fields of the `result` event, set by the command while it runs.
+static RESULT: Mutex<Vec<(String, Value)>> = Mutex::new(Vec::new());
+/// Files the command wrote, listed in the `result` event.
+static FILES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// Spinner of the running [`Step`], showing its latest message.
+static SPINNER: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+pub fn set_format(format: Format) {
+    JSON.store(format == Format::Json, Ordering::Relaxed);
+}
+
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// Whether `NO_COLOR` is set, see <https://no-color.org>.
+pub fn no_color_env() -> bool {
+    env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+/// Turns off ANSI styling for `--no-color` or `NO_COLOR`, and with `--plain`
+/// also the emoji, tree lines and spinners. Call before [`set_verbosity`].
+pub fn set_style(no_color: bool, plain: bool) {
+    COLOR.store(!(no_color || plain || no_color_env()), Ordering::Relaxed);
+    PLAIN_TEXT.store(plain, Ordering::Relaxed);
+}
+
+fn is_plain() -> bool {
+    PLAIN_TEXT.load(Ordering::Relaxed)
+}
+
+/// `text` with its symbols replaced by ASCII for `--plain`.
+pub fn symbols(text: &str) -> Cow<'_, str> {
+    if !is_plain() {
+        return Cow::Borrowed(text);
+    }
+    let mut text = text.to_string();
+    for (symbol, plain) in PLAIN {
+        text = text.replace(symbol, plain);
+    }
+    Cow::Owned(text)
+}
+
+/// Sets the verbosity and starts logging the operations forgeit runs to
+/// stderr, at debug level for `-v` and trace level for `-vv`.
+pub fn set_verbosity(quiet: bool, verbose: u8) {
+    let verbosity = match verbose {
+        _ if quiet => Verbosity::Quiet,
+        0 => Verbosity::Normal,
+        1 => Verbosity::Verbose,
+        _ => Verbosity::Trace,
+    };
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+
+    let level = match verbosity {
+        Verbosity::Quiet => LevelFilter::ERROR,
+        Verbosity::Normal => LevelFilter::WARN,
+        Verbosity::Verbose => LevelFilter::DEBUG,
+        Verbosity::Trace => LevelFilter::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_ansi(COLOR.load(Ordering::Relaxed) && io::stderr().is_terminal())
+        .with_max_level(level)
+        .with_target(false)
+        .without_time()
+        .init();
+}
+
+fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        2 => Verbosity::Verbose,
+        _ => Verbosity::Trace,
+    }
+}
+
+/// Whether cargo prints its progress, which only `-vv` shows. Its errors
+/// are shown regardless.
+pub fn shows_tool_output() -> bool {
+    verbosity() >= Verbosity::Trace
+}
+
+/// `cmd` as it would be typed, for the debug log.
+pub fn command_line(cmd: &Command) -> String {
+    let mut line = cmd.get_program().to_string_lossy().into_owned();
+    for arg in cmd.get_args() {
+        line.push(' ');
+        line.push_str(&arg.to_string_lossy());
+    }
+    match cmd.get_current_dir() {
+        Some(dir) => format!("{} (in {})", line, dir.display()),
+        None => line,
+    }
+}
+
+/// Empties one of the collections of the `result` event.
+fn take<T>(values: &Mutex<Vec<T>>) -> Vec<T> {
+    std::mem::take(&mut *values.lock().unwrap_or_else(|err| err.into_inner()))
+}
+
+fn emit(event: Value) {
+    println!("{}", event);
+}
+
+fn spinner() -> Option<ProgressBar> {
+    SPINNER
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
+
+/// Reports a line for people: printed as is, or as a `message` event
+/// without the blank lines that only space out the text. While a [`Step`]
+/// runs on a terminal the line replaces the message of its spinner.
+pub fn message(text: impl fmt::Display) {
+    if verbosity() == Verbosity::Quiet {
+        return;
+    }
+    let text = text.to_string();
+    let text = symbols(&text);
+    if let Some(spinner) = spinner() {
+        let text = text.trim();
+        if !text.is_empty() {
+            spinner.set_message(text.to_string());
+        }
+        return;
+    }
+    if !is_json() {
+        println!("{}", text);
+        return;
+    }
+    let text = text.trim_matches('\n');
+    if !text.is_empty() {
+        emit(json!({ "event": "message", "text": text }));
+    }
+}
+
+/// Reports something that works but may not be what was meant, on stderr
+/// so it stays out of the command's output.
+pub fn warn(text: impl fmt::Display) {
+    if verbosity() == Verbosity::Quiet {
+        return;
+    }
+    suspend(|| eprintln!("{}", symbols(&format!("⚠️ {}", text))));
+}
+
+/// `println!` for output that goes through [`message`].
+macro_rules! say {
+    () => {
+        $crate::output::message("")
+    };
+    ($($arg:tt)*) => {
+        $crate::output::message(format!($($arg)*))
+    };
+}
+pub(crate) use say;
+
+/// Reports text that brings its own line breaks, like file contents.
+pub fn text(text: &str) {
+    if is_json() {
+        message(text);
+    } else if verbosity() != Verbosity::Quiet {
+        print!("{}", text);
+    }
+}
+
+/// Runs `f` with the spinner of the running step hidden, for output written
+/// around [`message`] like the errors of cargo.
+pub fn suspend<R>(f: impl FnOnce() -> R) -> R {
+    match spinner() {
+        Some(spinner) => spinner.suspend(f),
+        None => f(),
+    }
+}
+
+/// A step of a long command like `scaffold`. On a terminal it shows a
+/// spinner with the step's messages, then a line with the time it took.
+/// Elsewhere the messages are printed as they come.
+pub struct Step {
+    title: String,
+    started: Instant,
+    spinner: Option<ProgressBar>,
+    done: bool,
+}
+
+/// Starts the step `title`, ended with [`Step::done`].
+pub fn step(title: impl fmt::Display) -> Step {
+    // Log lines of -v and events of JSON mode would tear through a spinner
+    let interactive = !is_json()
+        && !is_plain()
+        && verbosity() == Verbosity::Normal
+        && io::stdout().is_terminal()
+        && spinner().is_none();
+    let title = title.to_string();
+    let spinner = interactive.then(|| {
+        let spinner = ProgressBar::with_draw_target(None, ProgressDrawTarget::stdout())
+            .with_style(
+                ProgressStyle::with_template(if COLOR.load(Ordering::Relaxed) {
+                    "{spinner} {prefix} {wide_msg:.dim}"
+                } else {
+                    "{spinner} {prefix} {wide_msg}"
+                })
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+            )
+            .with_prefix(title.clone());
+        spinner.enable_steady_tick(Duration::from_millis(100));
+        *SPINNER.lock().unwrap_or_else(|err| err.into_inner()) = Some(spinner.clone());
+        spinner
+    });
+    Step {
+        title,
+        started: Instant::now(),
+        spinner,
+        done: false,
+    }
+}
+
+impl Step {
+    pub fn done(mut self) {
+        self.done = true;
+    }
+}
+
+impl Drop for Step {
+    fn drop(&mut self) {
+        let Some(spinner) = self.spinner.take() else {
+            return;
+        };
+        spinner.finish_and_clear();
+        *SPINNER.lock().unwrap_or_else(|err| err.into_inner()) = None;
+        // A failed step is left to the error that follows
+        if self.done {
+            println!(
+                "✅ {} ({:.1}s)",
+                self.title,
+                self.started.elapsed().as_secs_f64()
+            );
+        }
+    }
+}
+
+/// Records that the command wrote `path`, a `file` event in JSON mode.
+pub fn file(path: &Path) {
+    tracing::trace!("wrote {}", path.display());
+    if !is_json() {
+        return;
+    }
+    let path = path.strip_prefix(".").unwrap_or(path).display().to_string();
+    let mut files = FILES.lock().unwrap_or_else(|err| err.into_inner());
+    // Files like Cargo.toml are written by several steps
+    if !files.contains(&path) {
+        emit(json!({ "event": "file", "path": path }));
+        files.push(path);
+    }
+}
+
+/// Sets a field of the `result` event the command ends with.
+pub fn set(key: &str, value: impl Serialize) {
+    if !is_json() {
+        return;
+    }
+    let value = serde_json::to_value(value).unwrap_or(Value::Null);
+    let mut result = RESULT.lock().unwrap_or_else(|err| err.into_inner());
+    result.retain(|(existing, _)| existing != key);
+    result.push((key.to_string(), value));
+}
+
+/// Ends a successful `command` with its `result` event.
+pub fn finish(command: &str) {
+    if !is_json() {
+        return;
+    }
+    let mut event = Map::new();
+    event.insert("event".to_string(), json!("result"));
+    event.insert("command".to_string(), json!(command));
+    event.insert("ok".to_string(), json!(true));
+    event.insert("files".to_string(), json!(take(&FILES)));
+    event.extend(take(&RESULT));
+    emit(Value::Object(event));
+}
+
+/// Reports the error a command failed with and returns the exit code.
+pub fn fail(command: Option<&str>, err: &Error) -> ExitCode {
+    if !is_json() {
+        eprintln!("{}", symbols(&format!("❌ {}", err)));
+        return err.exit_code();
+    }
+    let kind = match err {
+        Error::User(_) => "user",
+        Error::Environment(_) => "environment",
+        Error::Internal(_) => "internal",
+    };
+    let mut event = Map::new();
+    event.insert("event".to_string(), json!("error"));
+    event.insert("command".to_string(), json!(command));
+    event.insert("ok".to_string(), json!(false));
+    event.insert("kind".to_string(), json!(kind));
+    event.insert("message".to_string(), json!(err.to_string()));
+    event.insert("files".to_string(), json!(take(&FILES)));
+    // What the command found before failing, like the checks of doctor
+    event.extend(take(&RESULT));
+    emit(Value::Object(event));
+    err.exit_code()
+}
+
+/// Where cargo and hooks print to: the terminal, or stderr in JSON mode so
+/// stdout only carries events.
+pub fn child_stdout() -> Stdio {
+    if is_json() {
+        Stdio::from(io::stderr())
+    } else {
+        Stdio::inherit()
+    }
+}
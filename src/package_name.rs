@@ -0,0 +1,215 @@
+use dialoguer::{
+    Confirm,
+    theme::{ColorfulTheme, SimpleTheme, Theme},
+};
+use heck::ToKebabCase;
+use std::{
+    io::{self, IsTerminal},
+    path::Path,
+};
+
+use crate::{
+    error::{Error, Result},
+    is_crate_name,
+    output::{self, say},
+};
+
+/// Rust keywords, which cargo refuses as package names.
+const KEYWORDS: [&str; 51] = [
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while",
+];
+
+/// Names cargo refuses besides the keywords: its directories below
+/// `target/` and the built-in test library.
+const REFUSED: [(&str, &str); 5] = [
+    ("build", "a directory cargo builds into"),
+    ("deps", "a directory cargo builds into"),
+    ("examples", "a directory cargo builds into"),
+    ("incremental", "a directory cargo builds into"),
+    ("test", "Rust's built-in test library"),
+];
+
+/// Crates of the standard library, which a package of the same name hides
+/// from its own code.
+const STD_CRATES: [&str; 4] = ["alloc", "core", "proc_macro", "std"];
+
+/// Device names Windows reserves, which cannot be checked out there.
+const WINDOWS_RESERVED: [&str; 22] = [
+    "aux", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8", "com9", "con", "lpt1",
+    "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9", "nul", "prn",
+];
+
+/// `name` as a package name: lowercase words joined by `-`, without the
+/// characters a package name cannot have. `None` when no letter is left.
+pub fn sanitize(name: &str) -> Option<String> {
+    let kebab: String = name
+        .to_kebab_case()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    let kebab = kebab
+        .trim_start_matches(|c: char| !c.is_ascii_alphabetic())
+        .trim_end_matches('-');
+    (!kebab.is_empty()).then(|| kebab.to_string())
+}
+
+/// Fails for a name cargo refuses as a package name, and warns about the
+/// ones it takes but that cause trouble later.
+pub fn check(name: &str) -> Result<()> {
+    if !is_crate_name(name) {
+        return Err(Error::user(format!(
+            "'{}' is not a valid package name\n👉 Use letters, digits, '-' and '_', starting with a letter",
+            name
+        )));
+    }
+    let refused = if KEYWORDS.contains(&name) {
+        Some("a Rust keyword")
+    } else {
+        REFUSED
+            .iter()
+            .find(|(refused, _)| *refused == name)
+            .map(|(_, reason)| *reason)
+    };
+    if let Some(reason) = refused {
+        return Err(Error::user(format!(
+            "'{}' is {}, which cargo refuses as a package name\n👉 Pick another name, e.g. {}-app",
+            name, reason, name
+        )));
+    }
+
+    // Cargo compares the library name, which has `_` for `-`
+    let lowercase = name.to_lowercase().replace('-', "_");
+    if STD_CRATES.contains(&lowercase.as_str()) {
+        output::warn(format!(
+            "'{}' is also a crate of the standard library, which the project's own code would no longer reach as {}::",
+            name, lowercase
+        ));
+    }
+    if WINDOWS_RESERVED.contains(&lowercase.as_str()) {
+        output::warn(format!(
+            "'{}' is a reserved file name on Windows, where the project cannot be checked out",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// The package name to scaffold `--name` as. A name with spaces, uppercase
+/// letters or other characters cargo does not take is converted to
+/// kebab-case when that is confirmed on a terminal. Elsewhere an invalid
+/// name fails with the converted one as the hint.
+pub fn resolve(name: &str) -> Result<String> {
+    let valid = is_crate_name(name);
+    if valid && !name.chars().any(|c| c.is_ascii_uppercase()) {
+        check(name)?;
+        return Ok(name.to_string());
+    }
+    let Some(converted) = sanitize(name) else {
+        check(name)?;
+        return Ok(name.to_string());
+    };
+
+    let interactive = !output::is_json() && io::stdin().is_terminal() && io::stdout().is_terminal();
+    let convert = interactive && {
+        let theme: Box<dyn Theme> = if output::no_color_env() {
+            Box::new(SimpleTheme)
+        } else {
+            Box::new(ColorfulTheme::default())
+        };
+        Confirm::with_theme(theme.as_ref())
+            .with_prompt(format!(
+                "Name the package '{}' instead of '{}'?",
+                converted, name
+            ))
+            .default(true)
+            .interact()
+            .map_err(|err| Error::environment(format!("Failed to read the answer: {}", err)))?
+    };
+    if convert {
+        check(&converted)?;
+        return Ok(converted);
+    }
+    if !valid {
+        return Err(Error::user(format!(
+            "'{}' is not a valid package name\n👉 Pass --name {}",
+            name, converted
+        )));
+    }
+    output::warn(format!(
+        "'{}' has uppercase letters, crates are usually named in lowercase like {}",
+        name, converted
+    ));
+    check(name)?;
+    Ok(name.to_string())
+}
+
+/// The package name of a project in `dir`, derived from the directory's
+/// name, which can stay as it is.
+pub fn derive(dir: &Path) -> Result<String> {
+    let dir_name = dir
+        .canonicalize()
+        .unwrap_or_else(|_| dir.to_path_buf())
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let valid = is_crate_name(&dir_name) && !dir_name.chars().any(|c| c.is_ascii_uppercase());
+    let name = match sanitize(&dir_name) {
+        Some(converted) if !valid => {
+            say!(
+                "Naming the package '{}' after the directory '{}'",
+                converted,
+                dir_name
+            );
+            converted
+        }
+        _ => dir_name,
+    };
+    check(&name)?;
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitizes_names_into_kebab_case() {
+        assert_eq!(sanitize("My Project").as_deref(), Some("my-project"));
+        assert_eq!(sanitize("myProject_v2").as_deref(), Some("my-project-v2"));
+        assert_eq!(sanitize("2fast 2furious").as_deref(), Some("fast-2furious"));
+        assert_eq!(sanitize("café!").as_deref(), Some("caf"));
+        assert_eq!(sanitize("123"), None);
+        assert_eq!(sanitize("---"), None);
+    }
+
+    #[test]
+    fn refuses_names_cargo_refuses() {
+        for name in [
+            "",
+            "1up",
+            "my project",
+            "-app",
+            "fn",
+            "Self",
+            "test",
+            "deps",
+        ] {
+            assert!(
+                matches!(check(name), Err(Error::User(_))),
+                "{:?} was accepted",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_names_cargo_takes() {
+        for name in ["api", "my-api", "my_api", "Api2", "std", "con"] {
+            assert!(check(name).is_ok(), "{:?} was refused", name);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::path::{Path, PathBuf};
+
+use crate::{error::Result, templates};
+
+/// Name of the built-in `--with` option.
+pub const WITH: &str = "payments";
+
+/// Module of the Stripe client and handlers, declared in main.rs.
+pub const MODULES: [&str; 1] = ["payments"];
+
+/// `src/payments/mod.rs`: the client, the checkout handler and the webhook
+/// verifying Stripe's signature before recording completed checkouts.
+pub fn file(context: &templates::TemplateContext) -> Result<(PathBuf, String)> {
+    Ok((
+        Path::new("src").join(MODULES[0]).join("mod.rs"),
+        templates::render("payments/mod.rs", context)?,
+    ))
+}
+
+/// Migration creating the table the webhook records payments in, for
+/// projects with a database.
+pub fn migration(context: &templates::TemplateContext) -> Result<(PathBuf, String)> {
+    Ok((
+        Path::new("migrations").join(format!(
+            "{}_create_payments.sql",
+            chrono::Utc::now().format("%Y%m%d%H%M%S")
+        )),
+        templates::render("payments/migration.sql", context)?,
+    ))
+}
+
+/// Routes of the checkout and the webhook, wrapped the way rustfmt wraps
+/// them below the routes marker.
+pub fn routes(framework: &str) -> [&'static str; 2] {
+    match framework {
+        "axum" => [
+            r#".route("/checkout", axum::routing::post(crate::payments::checkout))"#,
+            r#".route(
+    "/webhooks/stripe",
+    axum::routing::post(crate::payments::webhook),
+)"#,
+        ],
+        _ => [
+            r#".route(
+    "/checkout",
+    actix_web::web::post().to(crate::payments::checkout),
+)"#,
+            r#".route(
+    "/webhooks/stripe",
+    actix_web::web::post().to(crate::payments::webhook),
+)"#,
+        ],
+    }
+}
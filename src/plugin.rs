@@ -0,0 +1,345 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    io::Write,
+    path::{Component, Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::{
+    deps::{self, DependencySpec},
+    error::{Context, Error, Result},
+    manifest::{self, Manifest},
+    output::{self, say},
+};
+
+/// Plugins are executables on `PATH` named `scaffolder-plugin-<name>`.
+const PREFIX: &str = "scaffolder-plugin-";
+
+/// A generator or `--with` option registered by a plugin.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Extension {
+    pub name: String,
+    #[serde(default)]
+    pub about: String,
+}
+
+/// What a plugin prints for `scaffolder-plugin-<name> describe`.
+#[derive(Debug, Default, Deserialize)]
+pub struct Description {
+    #[serde(default)]
+    pub generators: Vec<Extension>,
+    #[serde(default)]
+    pub with: Vec<Extension>,
+}
+
+/// Project context a plugin receives as JSON on stdin.
+#[derive(Debug, Serialize)]
+pub struct ProjectContext {
+    pub version: &'static str,
+    pub name: String,
+    pub project_dir: PathBuf,
+    pub framework: Option<String>,
+    pub database: Option<String>,
+    pub features: Vec<String>,
+    pub args: Vec<String>,
+}
+
+impl ProjectContext {
+    /// Context of the Cargo project in the current directory.
+    pub fn current(args: Vec<String>) -> Result<Self> {
+        let project_dir = Path::new(".");
+        Ok(ProjectContext {
+            version: env!("CARGO_PKG_VERSION"),
+            name: env::current_dir()
+                .ok()
+                .and_then(|dir| Some(dir.file_name()?.to_string_lossy().into_owned()))
+                .unwrap_or_default(),
+            project_dir: project_dir.to_path_buf(),
+            framework: manifest::framework(project_dir)?,
+            database: manifest::database(project_dir)?,
+            features: Manifest::read(project_dir)?
+                .map(|manifest| manifest.features)
+                .unwrap_or_default(),
+            args,
+        })
+    }
+}
+
+/// How a plugin file operation treats an existing file.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// Fail when the file exists.
+    #[default]
+    Create,
+    Overwrite,
+    Append,
+}
+
+/// A file a plugin wants written, relative to the project directory.
+#[derive(Debug, Deserialize)]
+pub struct FileOperation {
+    pub path: PathBuf,
+    pub content: String,
+    #[serde(default)]
+    pub action: Action,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PluginDependency {
+    pub name: String,
+    pub features: Option<String>,
+}
+
+/// What a plugin prints after running a generator or `--with` option.
+#[derive(Debug, Default, Deserialize)]
+pub struct Output {
+    #[serde(default)]
+    pub files: Vec<FileOperation>,
+    #[serde(default)]
+    pub dependencies: Vec<PluginDependency>,
+    #[serde(default)]
+    pub messages: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Plugin {
+    pub name: String,
+    path: PathBuf,
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Plugins found on `PATH`, the first one wins when a name appears twice.
+pub fn discover() -> Vec<Plugin> {
+    let mut plugins: Vec<Plugin> = Vec::new();
+    let Some(path) = env::var_os("PATH") else {
+        return plugins;
+    };
+
+    for dir in env::split_paths(&path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        let mut found: Vec<Plugin> = entries
+            .flatten()
+            .filter(|entry| is_executable(&entry.path()))
+            .filter_map(|entry| {
+                let file_name = entry.file_name().into_string().ok()?;
+                let name = file_name.strip_prefix(PREFIX)?;
+                let name = name.strip_suffix(".exe").unwrap_or(name);
+                Some(Plugin {
+                    name: name.to_string(),
+                    path: entry.path(),
+                })
+            })
+            .filter(|plugin| !plugins.iter().any(|known| known.name == plugin.name))
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        plugins.extend(found);
+    }
+    plugins
+}
+
+impl Plugin {
+    /// Runs the plugin with `args`, writing `input` as JSON to its stdin and
+    /// parsing its stdout as JSON. Its stderr goes to the terminal.
+    fn call<T: for<'de> Deserialize<'de>>(
+        &self,
+        args: &[&str],
+        input: &impl Serialize,
+    ) -> Result<T> {
+        tracing::debug!("running {} {}", self.path.display(), args.join(" "));
+        let mut child = Command::new(&self.path)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to run plugin {}", self.path.display()))?;
+
+        let input = serde_json::to_vec(input)
+            .map_err(|err| Error::Internal(format!("Failed to encode plugin input: {}", err)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // Plugins that do not read their input close stdin early
+            let _ = stdin.write_all(&input);
+        }
+
+        let output = child
+            .wait_with_output()
+            .with_context(|| format!("Failed to run plugin {}", self.name))?;
+        if !output.status.success() {
+            return Err(Error::environment(format!(
+                "Plugin {} failed with {}",
+                self.name, output.status
+            )));
+        }
+        serde_json::from_slice(&output.stdout).map_err(|err| {
+            Error::environment(format!(
+                "Plugin {} returned invalid output: {}",
+                self.name, err
+            ))
+        })
+    }
+
+    pub fn describe(&self) -> Result<Description> {
+        self.call(&["describe"], &serde_json::Value::Null)
+    }
+
+    pub fn generate(&self, generator: &str, context: &ProjectContext) -> Result<Output> {
+        self.call(&["generate", generator], context)
+    }
+
+    pub fn with(&self, option: &str, context: &ProjectContext) -> Result<Output> {
+        self.call(&["with", option], context)
+    }
+}
+
+/// Finds the plugin registering the generator or `--with` option `name`.
+/// A plugin that fails to describe itself is skipped, so it cannot break
+/// the others.
+fn find_plugin(name: &str, extensions: impl Fn(&Description) -> &Vec<Extension>) -> Option<Plugin> {
+    for plugin in discover() {
+        let description = match plugin.describe() {
+            Ok(description) => description,
+            Err(err) => {
+                output::warn(format!("Skipping plugin {}: {}", plugin.name, err));
+                continue;
+            }
+        };
+        if extensions(&description)
+            .iter()
+            .any(|extension| extension.name == name)
+        {
+            return Some(plugin);
+        }
+    }
+    None
+}
+
+/// Runs the `--with` options of a scaffold, before anything is written so
+/// the files they touch can be rolled back.
+pub fn run_with_options(options: &[String], context: &ProjectContext) -> Result<Vec<Output>> {
+    options
+        .iter()
+        .map(|option| {
+            let Some(plugin) = find_plugin(option, |description| &description.with) else {
+                return Err(Error::user(format!(
+                    "No plugin provides --with {}\n👉 Install a {}* executable on your PATH",
+                    option, PREFIX
+                )));
+            };
+            say!("Running --with {} from plugin {}", option, plugin.name);
+            plugin.with(option, context)
+        })
+        .collect()
+}
+
+/// Paths written by `outputs`, relative to the project directory.
+pub fn touched_files(outputs: &[Output]) -> impl Iterator<Item = PathBuf> + '_ {
+    outputs
+        .iter()
+        .flat_map(|output| &output.files)
+        .map(|file| file.path.clone())
+}
+
+/// Prints what `outputs` would write, for `--dry-run`.
+pub fn print_outputs(outputs: &[Output]) {
+    for output in outputs {
+        for dep in &output.dependencies {
+            say!("\nPlugin dependency: {}", dep.name);
+        }
+        for file in &output.files {
+            say!(
+                "\n--- {} ({:?} by plugin) ---",
+                file.path.display(),
+                file.action
+            );
+            output::text(&file.content);
+        }
+    }
+}
+
+/// Applies the file operations and dependencies of a plugin output.
+pub fn apply(project_dir: &Path, output: &Output) -> Result<()> {
+    for file in &output.files {
+        // Plugins only ever write inside the project
+        if file
+            .path
+            .components()
+            .any(|part| !matches!(part, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(Error::environment(format!(
+                "Plugin tried to write outside the project: {}",
+                file.path.display()
+            )));
+        }
+
+        let path = project_dir.join(&file.path);
+        if file.action == Action::Create && path.exists() {
+            return Err(Error::user(format!(
+                "{} already exists",
+                file.path.display()
+            )));
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let content = match file.action {
+            Action::Append => fs::read_to_string(&path).unwrap_or_default() + &file.content,
+            _ => file.content.clone(),
+        };
+        say!("Writing {}", file.path.display());
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", file.path.display()))?;
+        output::file(&path);
+    }
+
+    for dep in &output.dependencies {
+        say!("Adding {}", dep.name);
+    }
+    if !output.dependencies.is_empty() {
+        let specs: Vec<DependencySpec> = output
+            .dependencies
+            .iter()
+            .map(|dep| DependencySpec::parse(&dep.name).with_features(dep.features.as_deref()))
+            .collect();
+        deps::insert(project_dir, &specs)?;
+    }
+    for message in &output.messages {
+        say!("👉 {}", message);
+    }
+    Ok(())
+}
+
+/// `generate <generator>` for generators registered by plugins.
+pub fn generate(args: Vec<String>) -> Result<()> {
+    let Some((generator, args)) = args.split_first() else {
+        return Err(Error::Internal("empty plugin generator".to_string()));
+    };
+    let Some(plugin) = find_plugin(generator, |description| &description.generators) else {
+        return Err(Error::user(format!(
+            "Unknown generator '{}'\n👉 forgeit list shows the generators of installed plugins",
+            generator
+        )));
+    };
+
+    say!("Generating {} with plugin {}", generator, plugin.name);
+    let output = plugin.generate(generator, &ProjectContext::current(args.to_vec())?)?;
+    apply(Path::new("."), &output)?;
+
+    say!("\n✅ Generated {}", generator);
+    Ok(())
+}
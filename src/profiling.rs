@@ -0,0 +1,45 @@
+use std::path::{Path, PathBuf};
+
+use crate::{error::Result, templates};
+
+/// Name of the built-in `--with` option.
+pub const WITH: &str = "profiling";
+
+/// Module of the console subscriber and the flamegraph handler, declared in
+/// main.rs.
+pub const MODULES: [&str; 1] = ["profiling"];
+
+/// Feature of the generated project serving its tasks to tokio-console.
+pub const CONSOLE_FEATURE: &str = "console";
+
+/// Feature of the generated project taking flamegraphs with pprof.
+pub const PPROF_FEATURE: &str = "pprof";
+
+/// `src/profiling/mod.rs`, and the cargo config building with the
+/// `tokio_unstable` cfg the instrumentation of tokio-console needs.
+pub fn files(context: &templates::TemplateContext) -> Result<Vec<(PathBuf, String)>> {
+    Ok(vec![
+        (
+            Path::new("src").join(MODULES[0]).join("mod.rs"),
+            templates::render("profiling/mod.rs", context)?,
+        ),
+        (
+            PathBuf::from(".cargo/config.toml"),
+            templates::render("profiling/config.toml", context)?,
+        ),
+    ])
+}
+
+/// Path of the flamegraph handler with its method router.
+pub fn route(framework: &str) -> (&'static str, &'static str) {
+    match framework {
+        "axum" => (
+            "/debug/pprof/flamegraph",
+            "axum::routing::get(crate::profiling::flamegraph)",
+        ),
+        _ => (
+            "/debug/pprof/flamegraph",
+            "actix_web::web::get().to(crate::profiling::flamegraph)",
+        ),
+    }
+}
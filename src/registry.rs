@@ -0,0 +1,184 @@
+use std::{env, fs, path::PathBuf};
+use toml_edit::DocumentMut;
+
+use crate::{
+    error::{Context, Error, Result},
+    is_crate_name,
+    output::say,
+    remote,
+};
+
+/// Path of the index inside the registry repository.
+const INDEX_FILE: &str = "templates/index.toml";
+
+/// A template listed in the registry index.
+#[derive(Debug)]
+pub struct IndexEntry {
+    pub name: String,
+    pub description: String,
+    pub repository: String,
+    pub branch: Option<String>,
+}
+
+/// Git repository holding the index, this project's own repository unless
+/// `FORGEIT_TEMPLATE_INDEX` points elsewhere.
+fn index_repository() -> String {
+    env::var("FORGEIT_TEMPLATE_INDEX")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| env!("CARGO_PKG_REPOSITORY").to_string())
+}
+
+/// Directory installed templates are cached in,
+/// `~/.cache/rust-backend-scaffolder/templates/` (or below `$XDG_CACHE_HOME`).
+fn cache_dir() -> Option<PathBuf> {
+    let cache_dir = env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".cache")))?;
+    Some(cache_dir.join("rust-backend-scaffolder").join("templates"))
+}
+
+/// Parses the `[[template]]` entries of an index, skipping the ones missing
+/// a usable name or repository.
+fn parse_index(index: &str) -> Result<Vec<IndexEntry>> {
+    let document: DocumentMut = index
+        .parse()
+        .map_err(|err| Error::environment(format!("Failed to parse {}: {}", INDEX_FILE, err)))?;
+    let Some(templates) = document
+        .get("template")
+        .and_then(|item| item.as_array_of_tables())
+    else {
+        return Ok(Vec::new());
+    };
+
+    let text = |table: &toml_edit::Table, key: &str| {
+        table
+            .get(key)
+            .and_then(|item| item.as_str())
+            .map(str::to_string)
+    };
+    Ok(templates
+        .iter()
+        .filter_map(|table| {
+            Some(IndexEntry {
+                name: text(table, "name").filter(|name| is_crate_name(name))?,
+                description: text(table, "description").unwrap_or_default(),
+                repository: text(table, "repository")?,
+                branch: text(table, "branch"),
+            })
+        })
+        .collect())
+}
+
+fn fetch_index() -> Result<Vec<IndexEntry>> {
+    let repository = index_repository();
+    say!("Fetching template index from {}", repository);
+    let checkout = remote::fetch_template(&repository, None)?;
+    let index_path = checkout.path().join(INDEX_FILE);
+    let index = fs::read_to_string(&index_path)
+        .map_err(|_| Error::environment(format!("{} has no {}", repository, INDEX_FILE)))?;
+    parse_index(&index)
+}
+
+/// Directory of the installed template `name`, if there is one.
+pub fn installed_template(name: &str) -> Option<PathBuf> {
+    cache_dir()
+        .map(|dir| dir.join(name))
+        .filter(|dir| is_crate_name(name) && dir.is_dir())
+}
+
+/// Names of the installed templates.
+pub fn installed_templates() -> Vec<String> {
+    let Some(entries) = cache_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    names
+}
+
+pub fn search(query: &str) -> Result<()> {
+    let query = query.to_lowercase();
+    let matches: Vec<IndexEntry> = fetch_index()?
+        .into_iter()
+        .filter(|entry| {
+            entry.name.to_lowercase().contains(&query)
+                || entry.description.to_lowercase().contains(&query)
+        })
+        .collect();
+
+    if matches.is_empty() {
+        say!("No templates matching '{}'", query);
+        return Ok(());
+    }
+
+    say!();
+    for entry in &matches {
+        let installed = if installed_template(&entry.name).is_some() {
+            " (installed)"
+        } else {
+            ""
+        };
+        say!("  - {}{}: {}", entry.name, installed, entry.description);
+    }
+    say!("\n👉 forgeit template install <name>");
+    Ok(())
+}
+
+pub fn install(name: &str) -> Result<()> {
+    let Some(entry) = fetch_index()?.into_iter().find(|entry| entry.name == name) else {
+        return Err(Error::user(format!(
+            "No template '{}' in the index\n👉 forgeit template search <query>",
+            name
+        )));
+    };
+    let Some(target) = cache_dir().map(|dir| dir.join(&entry.name)) else {
+        return Err(Error::environment("Could not find the home directory"));
+    };
+
+    say!("Installing {} from {}", entry.name, entry.repository);
+    let checkout = remote::fetch_template(&entry.repository, entry.branch.as_deref())?;
+
+    // Reinstalling replaces the cached copy with the latest version
+    if target.exists() {
+        fs::remove_dir_all(&target)
+            .with_context(|| format!("Failed to remove {}", target.display()))?;
+    }
+    for file in checkout.files()? {
+        let path = target.join(&file);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::copy(checkout.path().join(&file), &path)
+            .with_context(|| format!("Failed to install {}", file.display()))?;
+    }
+
+    say!("\n✅ Installed template '{}'", entry.name);
+    say!("👉 forgeit scaffold -n my_app -f {}", entry.name);
+    Ok(())
+}
+
+pub fn list() -> Result<()> {
+    let names = installed_templates();
+    if names.is_empty() {
+        say!("No templates installed");
+        say!("👉 forgeit template search <query>");
+        return Ok(());
+    }
+
+    say!("Installed templates:");
+    for (name, dir) in names
+        .iter()
+        .filter_map(|name| Some((name, installed_template(name)?)))
+    {
+        say!("  - {} ({})", name, dir.display());
+    }
+    Ok(())
+}
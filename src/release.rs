@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+
+use crate::{error::Result, templates};
+
+/// Name of the built-in `--with` option.
+pub const WITH: &str = "release";
+
+/// What `--with release` writes, keyed by the built-in template of each file.
+const FILES: [(&str, &str); 3] = [
+    ("release/CHANGELOG.md", "CHANGELOG.md"),
+    ("release/release.toml", "release.toml"),
+    ("release/release.yml", ".github/workflows/release.yml"),
+];
+
+/// The `CHANGELOG.md` in the keep-a-changelog format, the cargo-release
+/// `release.toml` moving its entries under each version, and the workflow
+/// the pushed tag starts: it attaches the binaries to a GitHub release, or
+/// publishes a library.
+pub fn files(context: &templates::TemplateContext, lib: bool) -> Result<Vec<(PathBuf, String)>> {
+    FILES
+        .into_iter()
+        .map(|(template, file)| {
+            Ok((
+                PathBuf::from(file),
+                templates::render_release(template, context, lib)?,
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,152 @@
+use git2::{Repository, build::CheckoutBuilder};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    error::{Context, Error, Result},
+    network,
+};
+
+/// Files of a template: a repository cloned into a temporary directory,
+/// which is removed again once the scaffold is done, or an installed template.
+pub struct Checkout {
+    dir: PathBuf,
+    temporary: bool,
+}
+
+impl Checkout {
+    /// Template files already on disk, kept after the scaffold.
+    pub fn local(dir: PathBuf) -> Self {
+        Checkout {
+            dir,
+            temporary: false,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Files of the template relative to its root, without the `.git`
+    /// directory, so the template's history never ends up in the project.
+    /// Symbolic links are refused: one could pull a file from outside the
+    /// template, like a private key, into the project.
+    pub fn files(&self) -> Result<Vec<PathBuf>> {
+        fn walk(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+            let entries =
+                fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+            for entry in entries {
+                let entry = entry.with_context(|| format!("Failed to read {}", dir.display()))?;
+                let path = entry.path();
+                let file_type = entry
+                    .file_type()
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                if file_type.is_symlink() {
+                    return Err(Error::user(format!(
+                        "The template contains a symbolic link: {}\n👉 Replace it with the file it points to",
+                        path.strip_prefix(root).unwrap_or(&path).display()
+                    )));
+                }
+                if file_type.is_dir() {
+                    if path.file_name().is_some_and(|name| name != ".git") {
+                        walk(root, &path, files)?;
+                    }
+                } else if let Ok(file) = path.strip_prefix(root) {
+                    files.push(file.to_path_buf());
+                }
+            }
+            Ok(())
+        }
+
+        let mut files = Vec::new();
+        walk(&self.dir, &self.dir, &mut files)?;
+        files.sort();
+        Ok(files)
+    }
+}
+
+impl Drop for Checkout {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+/// Clones the template repository at `url` into a temporary directory and
+/// checks out `reference`, a branch, tag or commit, defaulting to the
+/// remote's default branch.
+pub fn fetch_template(url: &str, reference: Option<&str>) -> Result<Checkout> {
+    // Repositories on disk clone without the network
+    if !Path::new(url).exists() && !url.starts_with("file://") {
+        network::require(&format!("Cloning {}", url))?;
+    }
+    // Each checkout of the process gets its own directory
+    static CHECKOUTS: AtomicUsize = AtomicUsize::new(0);
+    let checkout = Checkout {
+        dir: env::temp_dir().join(format!(
+            "forgeit-template-{}-{}",
+            std::process::id(),
+            CHECKOUTS.fetch_add(1, Ordering::Relaxed)
+        )),
+        temporary: true,
+    };
+    if checkout.dir.exists() {
+        fs::remove_dir_all(&checkout.dir).context("Failed to clean up a previous template")?;
+    }
+
+    tracing::debug!("cloning {} into {}", url, checkout.dir.display());
+    let repo = Repository::clone(url, &checkout.dir)
+        .map_err(|err| Error::environment(format!("Failed to clone {}: {}", url, err.message())))?;
+
+    if let Some(reference) = reference {
+        tracing::debug!("checking out {}", reference);
+        // Branches other than the default one only exist as remote branches
+        let object = repo
+            .revparse_single(reference)
+            .or_else(|_| repo.revparse_single(&format!("origin/{}", reference)))
+            .map_err(|_| {
+                Error::user(format!(
+                    "No branch, tag or commit '{}' in {}",
+                    reference, url
+                ))
+            })?;
+        repo.checkout_tree(&object, Some(CheckoutBuilder::new().force()))
+            .map_err(|err| {
+                Error::environment(format!(
+                    "Failed to check out '{}': {}",
+                    reference,
+                    err.message()
+                ))
+            })?;
+    }
+
+    Ok(checkout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_symbolic_links() {
+        let dir = env::temp_dir().join(format!("forgeit-links-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        let checkout = Checkout::local(dir.clone());
+        assert_eq!(
+            checkout.files().unwrap(),
+            vec![PathBuf::from("src/main.rs")]
+        );
+
+        std::os::unix::fs::symlink("/", dir.join("src/root")).unwrap();
+        let err = checkout.files().unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(err, Error::User(_)));
+        assert!(err.to_string().contains("src/root"));
+    }
+}
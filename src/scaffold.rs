@@ -0,0 +1,2637 @@
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+use git2::Repository;
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use toml_edit::{Array, DocumentMut, Item, Table, value};
+
+use crate::{
+    app_state, completions, config,
+    deps::{self, DependencySpec},
+    error::{Context, Error, Result},
+    feature_flags, frontend, generate, git_hooks,
+    github::{self, GithubRepo},
+    hooks::{self, HookProject, Hooks},
+    i18n, is_module_name,
+    license::{self, License},
+    lint_config::{self, LintConfig},
+    manifest::{self, Manifest},
+    musl, network,
+    output::{self, say},
+    package_name, payments, plugin, profiling, registry, release,
+    remote::{self, Checkout},
+    run_cargo, search,
+    signing::Signer,
+    suggest,
+    templates::{self, TaskRunner},
+    uploads,
+};
+
+/// README generated for the project, unless it has one already.
+const README: &str = "README.md";
+
+/// Script of `--deploy systemd` installing the service on a VM.
+const INSTALL_SCRIPT: &str = "deploy/install.sh";
+
+/// Port of generated servers unless `--port` picks another.
+pub const DEFAULT_PORT: u16 = 3000;
+
+/// `--with` options built into forgeit, the others come from plugins.
+pub const BUILTIN_WITH: [(&str, &str); 11] = [
+    (
+        lint_config::WITH,
+        "rustfmt.toml and [lints] in Cargo.toml, unwrap denied in handlers",
+    ),
+    (
+        release::WITH,
+        "CHANGELOG.md, cargo-release config and a tag-triggered release workflow",
+    ),
+    (
+        git_hooks::WITH,
+        "A pre-commit hook running cargo fmt, clippy and test",
+    ),
+    (
+        musl::WITH,
+        "Static musl binaries, a Cross.toml and a distroless Docker image",
+    ),
+    (
+        feature_flags::WITH,
+        "A flags module with env, file or Flipt providers and an extractor gating routes",
+    ),
+    (
+        i18n::WITH,
+        "Fluent translations in locales/, Accept-Language negotiation and localized errors",
+    ),
+    (
+        search::WITH,
+        "A Meilisearch client, index bootstrap, /search and a docker-compose service",
+    ),
+    (
+        payments::WITH,
+        "Stripe Checkout, a signature-verified webhook and a payments migration",
+    ),
+    (
+        uploads::WITH,
+        "Multipart uploads with size and type limits to disk or S3, and downloads",
+    ),
+    (
+        app_state::WITH,
+        "An AppState with the config, database and Redis pools and an HTTP client",
+    ),
+    (
+        profiling::WITH,
+        "tokio-console behind a feature and pprof flamegraphs in debug builds",
+    ),
+];
+
+/// Built-in `--with` options adding modules to the generated server.
+const MODULE_WITH: [(&str, &[&str]); 7] = [
+    (feature_flags::WITH, &feature_flags::MODULES),
+    (i18n::WITH, &i18n::MODULES),
+    (search::WITH, &search::MODULES),
+    (payments::WITH, &payments::MODULES),
+    (uploads::WITH, &uploads::MODULES),
+    (app_state::WITH, &app_state::MODULES),
+    (profiling::WITH, &profiling::MODULES),
+];
+
+/// Rust editions and the first Rust version supporting them.
+const EDITIONS: [(&str, (u64, u64)); 4] = [
+    ("2015", (1, 0)),
+    ("2018", (1, 31)),
+    ("2021", (1, 56)),
+    ("2024", (1, 85)),
+];
+
+/// `major.minor` of a Rust version like `1.85` or `1.85.0`.
+pub fn parse_rust_version(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    match (parts.next(), parts.next()) {
+        (None, _) => Some((major, minor)),
+        (Some(patch), None) if patch.parse::<u64>().is_ok() => Some((major, minor)),
+        _ => None,
+    }
+}
+
+/// Options shared by `scaffold` and `init`.
+#[derive(Args, Debug)]
+pub struct ScaffoldArgs {
+    /// Name of the framework (e.g. axum, actix-web)
+    #[arg(short, long, add = ArgValueCandidates::new(completions::frameworks))]
+    framework: Option<String>,
+
+    /// Preset of the user config to scaffold with
+    #[arg(long, add = ArgValueCandidates::new(completions::presets))]
+    preset: Option<String>,
+
+    /// Database, cache and auth bundle to scaffold with (e.g. postgres-redis-jwt)
+    #[arg(long, add = ArgValueCandidates::new(completions::stacks))]
+    stack: Option<String>,
+
+    /// Additional dependencies to add (e.g. dotenvy)
+    #[arg(short, long)]
+    deps: Option<Vec<String>>,
+
+    /// Database to add sqlx for
+    #[arg(long, value_parser = ["postgres", "mysql", "sqlite"])]
+    database: Option<String>,
+
+    /// Directory layout of the generated project [default: mvc]
+    #[arg(long, value_parser = templates::ARCHITECTURES)]
+    arch: Option<String>,
+
+    /// Module directories to create instead of the preset's (e.g. handlers,repo,dto)
+    #[arg(long, value_delimiter = ',', conflicts_with = "minimal")]
+    modules: Option<Vec<String>>,
+
+    /// Skip module directories and keep everything in main.rs
+    #[arg(long, conflicts_with = "arch")]
+    minimal: bool,
+
+    /// Create a library crate (lib.rs, no HTTP server) instead of a binary
+    #[arg(long, conflicts_with = "arch")]
+    lib: bool,
+
+    /// Scaffold even if the target exists, overwriting the generated files
+    #[arg(long)]
+    force: bool,
+
+    /// Print the planned files and dependencies without writing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Run cargo check in the scaffolded project, or cargo test with --verify=test
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "check",
+        conflicts_with = "dry_run"
+    )]
+    verify: Option<Verify>,
+
+    /// Runtime to deploy to: a server, an AWS Lambda function or a Cloudflare Worker [default: server]
+    #[arg(long, value_enum, conflicts_with_all = ["lib", "template", "docker", "host"])]
+    target: Option<Target>,
+
+    /// Platform to add the deployment files of (e.g. shuttle, fly, systemd)
+    #[arg(long, value_enum, conflicts_with_all = ["lib", "template", "target"])]
+    deploy: Option<Deploy>,
+
+    /// Server-rendered frontend to add to an axum or actix-web server (e.g. askama-htmx)
+    #[arg(long, value_enum, conflicts_with_all = ["lib", "template"])]
+    frontend: Option<Frontend>,
+
+    /// Git repository to scaffold from instead of the built-in templates
+    #[arg(long, conflicts_with_all = ["arch", "modules", "minimal", "lib"])]
+    template: Option<String>,
+
+    /// Branch, tag or commit of the --template repository
+    #[arg(long, requires = "template")]
+    branch: Option<String>,
+
+    /// Options provided by plugins to apply after scaffolding (e.g. company-ci)
+    #[arg(long, value_delimiter = ',', add = ArgValueCandidates::new(completions::with_options))]
+    with: Vec<String>,
+
+    /// Add a Dockerfile, the server then listens on 0.0.0.0
+    #[arg(long, conflicts_with_all = ["lib", "template"])]
+    docker: bool,
+
+    /// Address the server binds to unless HOST is set [default: 127.0.0.1, 0.0.0.0 with --docker]
+    #[arg(long, conflicts_with = "lib")]
+    host: Option<String>,
+
+    /// Port the server listens on unless PORT is set
+    #[arg(long, default_value_t = DEFAULT_PORT, conflicts_with = "lib")]
+    port: u16,
+
+    /// Rust edition of the generated Cargo.toml [default: cargo's]
+    #[arg(long, value_parser = EDITIONS.map(|(edition, _)| edition))]
+    edition: Option<String>,
+
+    /// Minimum supported Rust version, set as rust-version (e.g. 1.85)
+    #[arg(long)]
+    msrv: Option<String>,
+
+    /// Toolchain to pin in rust-toolchain.toml (e.g. stable, 1.85.0)
+    #[arg(long)]
+    toolchain: Option<String>,
+
+    /// Task file with run, test, lint and the targets of the chosen features [default: just]
+    #[arg(long, value_enum)]
+    task_runner: Option<TaskRunner>,
+
+    /// License to write LICENSE for, two for a dual license (e.g. mit,apache-2.0)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    license: Vec<License>,
+
+    /// Copyright holder in the license [default: the git-author name, or git's user.name]
+    #[arg(long, value_name = "NAME")]
+    copyright_holder: Option<String>,
+
+    /// Author of the initial commit, set in the new repository [default: git's user.name and user.email]
+    #[arg(long, value_name = "NAME <EMAIL>")]
+    git_author: Option<String>,
+
+    /// Leave the initial commit unsigned even when git's commit.gpgsign is set
+    #[arg(long)]
+    no_sign: bool,
+
+    /// Create no git repository and no initial commit
+    #[arg(long, conflicts_with_all = ["default_branch", "commit_message", "git_author", "no_sign"])]
+    no_git: bool,
+
+    /// Branch of the initial commit [default: git's init.defaultBranch]
+    #[arg(long, value_name = "BRANCH")]
+    default_branch: Option<String>,
+
+    /// Message of the initial commit
+    #[arg(long, value_name = "MESSAGE", default_value = "Initial commit")]
+    commit_message: String,
+
+    /// Create the GitHub repository OWNER/NAME and push to it, with the token in GITHUB_TOKEN
+    #[arg(long, value_name = "OWNER/NAME", conflicts_with = "no_git")]
+    github: Option<String>,
+
+    /// Make the --github repository public rather than private
+    #[arg(long, requires = "github")]
+    github_public: bool,
+
+    /// Require reviewed pull requests on the branch of the --github repository
+    #[arg(long, requires = "github")]
+    github_protect: bool,
+
+    /// Topic of the --github repository, may be repeated
+    #[arg(long = "github-topic", value_name = "TOPIC", requires = "github")]
+    github_topics: Vec<String>,
+
+    /// Command to run before scaffolding, may be repeated
+    #[arg(long = "pre-hook", value_name = "COMMAND")]
+    pre_hooks: Vec<String>,
+
+    /// Command to run in the project after scaffolding, may be repeated
+    #[arg(long = "post-hook", value_name = "COMMAND")]
+    post_hooks: Vec<String>,
+
+    /// Run the hooks declared in the template's hooks.toml
+    #[arg(long)]
+    hooks: bool,
+}
+
+/// How `--verify` checks the scaffolded project.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Verify {
+    /// cargo check
+    Check,
+    /// cargo test, which builds the tests as well
+    Test,
+}
+
+impl Verify {
+    fn subcommand(self) -> &'static str {
+        match self {
+            Verify::Check => "check",
+            Verify::Test => "test",
+        }
+    }
+}
+
+/// Runtime `--target` deploys the project to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Target {
+    /// A server listening on HOST and PORT
+    #[default]
+    Server,
+    /// An AWS Lambda function running the axum router with lambda_http
+    Lambda,
+    /// A Cloudflare Worker routing requests with the worker crate
+    Workers,
+}
+
+impl Target {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Target::Server => "server",
+            Target::Lambda => "lambda",
+            Target::Workers => "workers",
+        }
+    }
+}
+
+/// Platform `--deploy` prepares the project for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Deploy {
+    /// Shuttle, which provisions the server and its database
+    Shuttle,
+    /// Fly.io, running the Dockerfile with a health check and migrations
+    Fly,
+    /// A plain VM, running the binary as a hardened systemd service
+    Systemd,
+}
+
+impl Deploy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Deploy::Shuttle => "shuttle",
+            Deploy::Fly => "fly",
+            Deploy::Systemd => "systemd",
+        }
+    }
+}
+
+/// Server-rendered frontend `--frontend` adds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Frontend {
+    /// askama templates rendered by the handlers, htmx swapping in their partials
+    AskamaHtmx,
+}
+
+impl Frontend {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Frontend::AskamaHtmx => "askama-htmx",
+        }
+    }
+}
+
+/// Where a template scaffold takes its files from.
+#[derive(Debug)]
+pub enum TemplateSource {
+    /// A repository cloned with `--template`.
+    Git { url: String, branch: Option<String> },
+    /// A template installed with `template install`, picked with `--framework`.
+    Installed { name: String, dir: PathBuf },
+}
+
+/// Resolved scaffold choices.
+#[derive(Debug)]
+pub struct ScaffoldOptions {
+    pub framework: Option<String>,
+    /// Version requirement of `--framework name@version`.
+    pub framework_version: Option<String>,
+    /// Version line of the framework's templates, see [`templates::template_version`].
+    pub template_version: &'static str,
+    pub deps: Vec<String>,
+    pub database: Option<String>,
+    pub arch: String,
+    pub modules: Vec<String>,
+    pub lib: bool,
+    pub force: bool,
+    pub dry_run: bool,
+    pub verify: Option<Verify>,
+    pub target: Target,
+    pub deploy: Option<Deploy>,
+    pub frontend: Option<Frontend>,
+    pub template: Option<TemplateSource>,
+    pub docker: bool,
+    pub host: String,
+    pub port: u16,
+    pub with: Vec<String>,
+    pub hooks: Hooks,
+    pub template_hooks: bool,
+    pub licenses: Vec<License>,
+    pub copyright_holder: Option<String>,
+    pub edition: Option<String>,
+    pub msrv: Option<String>,
+    pub toolchain: Option<String>,
+    pub task_runner: TaskRunner,
+    pub git_author: Option<String>,
+    /// Set by `--with lint-config`.
+    pub lint_config: Option<LintConfig>,
+    /// Set by `--with release`.
+    pub release: bool,
+    /// Set by `--with git-hooks`.
+    pub git_hooks: bool,
+    /// Set by `--with musl`.
+    pub musl: bool,
+    /// Set by `--with feature-flags`.
+    pub feature_flags: bool,
+    /// Set by `--with i18n`.
+    pub i18n: bool,
+    /// Set by `--with search`.
+    pub search: bool,
+    /// Set by `--with payments`.
+    pub payments: bool,
+    /// Set by `--with uploads`.
+    pub uploads: bool,
+    /// Set by `--with app-state`.
+    pub app_state: bool,
+    /// Set by `--with profiling`.
+    pub profiling: bool,
+    /// Whether the initial commit is signed when git's `commit.gpgsign` asks for it.
+    pub sign: bool,
+    /// Cleared by `--no-git`.
+    pub git: bool,
+    pub default_branch: Option<String>,
+    pub commit_message: String,
+    pub github: Option<GithubRepo>,
+}
+
+impl ScaffoldOptions {
+    /// Author of the project `name`: the copyright holder, falling back to
+    /// the git author, then to git's user.name and the project.
+    pub fn author(&self, name: &str) -> String {
+        let given = self.copyright_holder.clone().or_else(|| {
+            let author = self.git_author.as_deref().and_then(config::parse_author);
+            author.map(|(author, _)| author.to_string())
+        });
+        license::holder(given, name)
+    }
+
+    /// Fields set in the `[package]` table of Cargo.toml.
+    fn package_fields(&self) -> Vec<(&'static str, String)> {
+        [("edition", &self.edition), ("rust-version", &self.msrv)]
+            .into_iter()
+            .filter_map(|(key, field)| Some((key, field.clone()?)))
+            .chain(license::package_field(&self.licenses))
+            .collect()
+    }
+}
+
+impl ScaffoldArgs {
+    /// Resolves the preset into the module list to create, rejecting module
+    /// names that are not valid Rust identifiers. Choices missing from the
+    /// command line come from the user config and its `--preset`.
+    pub fn into_options(self) -> Result<ScaffoldOptions> {
+        let config = config::load()?;
+        let mut settings = config.settings(self.preset.as_deref(), self.stack.as_deref())?;
+        let target = self.target.unwrap_or_default();
+        // A framework or layout on the command line replaces a configured template
+        if self.framework.is_some()
+            || self.template.is_some()
+            || self.lib
+            || target == Target::Workers
+        {
+            settings.template = None;
+            settings.branch = None;
+        }
+
+        // A custom module list gets empty modules and the router in main.rs,
+        // since the preset examples depend on the preset's own modules
+        let arch = if self.lib {
+            templates::LIB_ARCH
+        } else {
+            self.arch
+                .as_deref()
+                .or(settings.arch.as_deref())
+                .unwrap_or("mvc")
+        };
+        let (arch, mut modules) = match self.modules {
+            _ if self.minimal => ("flat", Vec::new()),
+            // The examples of the presets are a framework's, a Worker routes in lib.rs
+            None if target == Target::Workers && self.arch.is_none() => ("flat", Vec::new()),
+            Some(modules) => ("flat", modules),
+            None => (
+                arch,
+                templates::get_modules(arch)
+                    .iter()
+                    .map(|module| module.to_string())
+                    .collect(),
+            ),
+        };
+
+        if let Some(invalid) = modules.iter().find(|module| !is_module_name(module)) {
+            return Err(Error::user(format!(
+                "'{}' is not a valid Rust module name",
+                invalid
+            )));
+        }
+
+        // Installed templates are used like frameworks
+        // The configured framework is a server's, libraries and Workers only
+        // get one when asked
+        let frameworkless = self.lib || target == Target::Workers;
+        let framework = self
+            .framework
+            .or(settings.framework.filter(|_| !frameworkless));
+        // `axum@0.6` pins the framework, and picks the templates written for it
+        let (framework, framework_version) = match framework {
+            Some(spec) => match spec.split_once('@') {
+                Some((name, version)) => (Some(name.to_string()), Some(version.to_string())),
+                None => (Some(spec), None),
+            },
+            None => (None, None),
+        };
+        // `none` asks for a project without a framework
+        let bare = framework.as_deref() == Some(BARE_FRAMEWORK);
+        let framework = framework.filter(|_| !bare);
+        let template_version = match &framework {
+            Some(name) => templates::template_version(name, framework_version.as_deref())?,
+            None => "",
+        };
+        let (template, branch) = match self.template {
+            Some(url) => (Some(url), self.branch),
+            None => (settings.template, settings.branch),
+        };
+        let (framework, template) = match (framework, template) {
+            (framework, Some(url)) => (framework, Some(TemplateSource::Git { url, branch })),
+            (Some(name), None) => match registry::installed_template(&name) {
+                Some(dir) => (None, Some(TemplateSource::Installed { name, dir })),
+                None => {
+                    check_framework(&name)?;
+                    (Some(name), None)
+                }
+            },
+            (None, None) if frameworkless || bare => (None, None),
+            (None, None) => {
+                return Err(Error::user(
+                    "No framework given\n👉 Pass --framework, or set framework in the user config",
+                ));
+            }
+        };
+
+        // lambda_http serves the router of axum 0.7 and later, which share its http types
+        if target == Target::Lambda {
+            if framework.as_deref() != Some("axum") {
+                return Err(Error::user(
+                    "--target lambda runs an axum router on lambda_http\n👉 Pass --framework axum",
+                ));
+            }
+            if template_version == "0.6" {
+                return Err(Error::user(
+                    "--target lambda needs axum 0.7 or later, axum 0.6 uses other http types than lambda_http\n👉 Pass --framework axum without a version, or axum@0.7",
+                ));
+            }
+        }
+
+        // The worker crate brings its own router
+        if target == Target::Workers && (framework.is_some() || template.is_some()) {
+            return Err(Error::user(
+                "--target workers routes requests with the Router of the worker crate, a framework would not be used\n👉 Drop --framework",
+            ));
+        }
+
+        // Shuttle has integrations for axum 0.8 and actix-web and only shares
+        // Postgres databases
+        if self.deploy == Some(Deploy::Shuttle) {
+            match framework.as_deref() {
+                Some("axum") if template_version != "0.8" => {
+                    return Err(Error::user(
+                        "--deploy shuttle needs axum 0.8, which shuttle-axum serves\n👉 Pass --framework axum without a version",
+                    ));
+                }
+                Some("axum" | "actix-web") => {}
+                _ => {
+                    return Err(Error::user(
+                        "--deploy shuttle runs axum or actix-web through their Shuttle integration\n👉 Pass --framework axum or --framework actix-web",
+                    ));
+                }
+            }
+        }
+
+        // Fly's health check needs a router to add /healthz to, systemd a
+        // server to keep running
+        if let Some(deploy) = self
+            .deploy
+            .filter(|deploy| matches!(deploy, Deploy::Fly | Deploy::Systemd))
+            && framework.is_none()
+        {
+            return Err(Error::user(format!(
+                "--deploy {} deploys a server, there is none without a framework\n👉 Pass --framework axum or --framework actix-web",
+                deploy.as_str()
+            )));
+        }
+
+        // Listed values add to the configured ones
+        let extend = |mut values: Vec<String>, extra: Vec<String>| {
+            for value in extra {
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+            }
+            values
+        };
+        let edition = self.edition.or(settings.edition);
+        let msrv = self.msrv.or(settings.msrv);
+        // The flag is checked by clap, the configured edition is not
+        if let Some(edition) = &edition
+            && !EDITIONS.iter().any(|(name, _)| name == edition)
+        {
+            return Err(Error::user(format!(
+                "Unknown edition '{}'\n👉 Editions: {}",
+                edition,
+                EDITIONS.map(|(name, _)| name).join(", ")
+            )));
+        }
+        if let Some(msrv) = &msrv {
+            let Some(version) = parse_rust_version(msrv) else {
+                return Err(Error::user(format!(
+                    "'{}' is not a Rust version\n👉 Pass --msrv like 1.85",
+                    msrv
+                )));
+            };
+            // Cargo rejects an edition the rust-version cannot build
+            if let Some((edition, (major, minor))) = EDITIONS
+                .iter()
+                .find(|(name, _)| Some(*name) == edition.as_deref())
+                && version < (*major, *minor)
+            {
+                return Err(Error::user(format!(
+                    "Edition {} needs Rust {}.{}, the --msrv is {}\n👉 Raise --msrv or pick an older --edition",
+                    edition, major, minor, msrv
+                )));
+            }
+        }
+
+        // The flag replaces the configured licenses rather than adding to them
+        let mut licenses = Vec::new();
+        for choice in if self.license.is_empty() {
+            settings.license
+        } else {
+            self.license
+        } {
+            if !licenses.contains(&choice) {
+                licenses.push(choice);
+            }
+        }
+        license::check(&licenses)?;
+        if let Some(branch) = &self.default_branch
+            && !git2::Reference::is_valid_name(&format!("refs/heads/{}", branch))
+        {
+            return Err(Error::user(format!(
+                "'{}' is not a valid branch name",
+                branch
+            )));
+        }
+        let github = match &self.github {
+            Some(spec) => Some(GithubRepo {
+                public: self.github_public,
+                protect: self.github_protect,
+                topics: self.github_topics.clone(),
+                ..GithubRepo::parse(spec)?
+            }),
+            None => None,
+        };
+        let git_author = self.git_author.or(settings.git_author);
+        // The configured author is checked when the config is read
+        if let Some(author) = &git_author
+            && config::parse_author(author).is_none()
+        {
+            return Err(Error::user(format!(
+                "'{}' is not a git author\n👉 Pass --git-author like \"Jane Doe <jane@example.com>\"",
+                author
+            )));
+        }
+
+        // Libraries, Workers and templates have no Dockerfile to add, Fly deploys one
+        let docker = self.docker
+            || self.deploy == Some(Deploy::Fly)
+            || (settings.docker && !frameworkless && template.is_none());
+        let database = self.database.or(settings.database);
+        let with = extend(settings.with, self.with);
+        if target == Target::Workers && database.is_some() {
+            return Err(Error::user(
+                "sqlx does not build for the wasm Workers run\n👉 Drop --database, Workers reach databases through D1 or Hyperdrive bindings",
+            ));
+        }
+        if self.deploy == Some(Deploy::Shuttle) {
+            if docker {
+                return Err(Error::user(
+                    "Shuttle builds and runs the project itself, a Dockerfile would not be used\n👉 Drop --docker, or docker from the user config",
+                ));
+            }
+            if let Some(database) = database
+                .as_deref()
+                .filter(|database| *database != "postgres")
+            {
+                return Err(Error::user(format!(
+                    "Shuttle provisions Postgres databases, not {}\n👉 Pass --database postgres",
+                    database
+                )));
+            }
+        }
+        // Static binaries are built for servers, the other runtimes and
+        // Shuttle build the project their own way
+        let musl = with.iter().any(|option| option == musl::WITH);
+        if musl && (self.lib || target != Target::Server || self.deploy == Some(Deploy::Shuttle)) {
+            return Err(Error::user(format!(
+                "--with musl builds a static server binary, which {} does not run\n👉 Drop --with musl",
+                if self.lib {
+                    "a library".to_string()
+                } else if target != Target::Server {
+                    format!("--target {}", target.as_str())
+                } else {
+                    "--deploy shuttle".to_string()
+                }
+            )));
+        }
+        // The modules are declared in the main.rs of the generated server
+        for (option, added) in MODULE_WITH {
+            if !with.iter().any(|name| name == option) {
+                continue;
+            }
+            if self.lib
+                || template.is_some()
+                || !matches!(framework.as_deref(), Some("axum" | "actix-web"))
+            {
+                return Err(Error::user(format!(
+                    "--with {} adds modules to the axum or actix-web server forgeit generates\n👉 Pass --framework axum or --framework actix-web, or drop --with {}",
+                    option, option
+                )));
+            }
+            add_modules(&mut modules, added);
+        }
+        // tokio-console and the flamegraphs are served next to a server, whose
+        // global tracing subscriber is the console one
+        if with.iter().any(|option| option == profiling::WITH) {
+            if target != Target::Server {
+                return Err(Error::user(format!(
+                    "--with profiling serves tokio-console and flamegraphs from a long-running server, which --target {} does not run\n👉 Drop --with profiling",
+                    target.as_str()
+                )));
+            }
+            if self.deploy == Some(Deploy::Shuttle) {
+                return Err(Error::user(
+                    "--with profiling installs the tracing subscriber of tokio-console, and --deploy shuttle installs its own\n👉 Drop --with profiling",
+                ));
+            }
+        }
+        // The state of the cqrs router of axum is its event store
+        if arch == "cqrs"
+            && framework.as_deref() == Some("axum")
+            && with.iter().any(|option| option == app_state::WITH)
+        {
+            return Err(Error::user(
+                "--with app-state hands its state to the router, which --arch cqrs gives its event store as the state on axum\n👉 Pass another --arch, or drop --with app-state",
+            ));
+        }
+        // The pages are served next to the routes of the generated server
+        if let Some(frontend) = self.frontend {
+            if !matches!(framework.as_deref(), Some("axum" | "actix-web")) {
+                return Err(Error::user(format!(
+                    "--frontend {} adds pages to the axum or actix-web server forgeit generates\n👉 Pass --framework axum or --framework actix-web, or drop --frontend",
+                    frontend.as_str()
+                )));
+            }
+            if target != Target::Server {
+                return Err(Error::user(format!(
+                    "--frontend {} serves its assets from a directory next to the server, --target {} has none\n👉 Drop --target, or --frontend",
+                    frontend.as_str(),
+                    target.as_str()
+                )));
+            }
+            if framework.as_deref() == Some("axum") && template_version == "0.6" {
+                return Err(Error::user(format!(
+                    "--frontend {} serves the assets with tower-http, which needs axum 0.7 or later\n👉 Pass --framework axum without a version, or axum@0.7",
+                    frontend.as_str()
+                )));
+            }
+            add_modules(&mut modules, &frontend::MODULES);
+        }
+        // Containers are only reachable through all interfaces
+        let host = self
+            .host
+            .unwrap_or_else(|| if docker { "0.0.0.0" } else { "127.0.0.1" }.to_string());
+        Ok(ScaffoldOptions {
+            framework,
+            framework_version,
+            template_version,
+            deps: extend(settings.deps, self.deps.unwrap_or_default()),
+            database,
+            arch: arch.to_string(),
+            modules,
+            lib: self.lib,
+            force: self.force,
+            dry_run: self.dry_run,
+            verify: self.verify,
+            target,
+            deploy: self.deploy,
+            frontend: self.frontend,
+            docker,
+            host,
+            port: self.port,
+            template,
+            hooks: Hooks {
+                pre: extend(settings.pre_hooks, self.pre_hooks),
+                post: extend(settings.post_hooks, self.post_hooks),
+            },
+            template_hooks: self.hooks,
+            licenses,
+            copyright_holder: self.copyright_holder.or(settings.copyright_holder),
+            edition,
+            msrv,
+            toolchain: self.toolchain.or(settings.toolchain),
+            task_runner: self
+                .task_runner
+                .or(settings.task_runner)
+                .unwrap_or_default(),
+            git_author,
+            lint_config: with
+                .iter()
+                .any(|option| option == lint_config::WITH)
+                .then_some(config.lint_config),
+            release: with.iter().any(|option| option == release::WITH),
+            git_hooks: with.iter().any(|option| option == git_hooks::WITH),
+            musl,
+            feature_flags: with.iter().any(|option| option == feature_flags::WITH),
+            i18n: with.iter().any(|option| option == i18n::WITH),
+            search: with.iter().any(|option| option == search::WITH),
+            payments: with.iter().any(|option| option == payments::WITH),
+            uploads: with.iter().any(|option| option == uploads::WITH),
+            app_state: with.iter().any(|option| option == app_state::WITH),
+            profiling: with.iter().any(|option| option == profiling::WITH),
+            sign: !self.no_sign,
+            git: !self.no_git,
+            default_branch: self.default_branch,
+            commit_message: self.commit_message,
+            github,
+            with,
+        })
+    }
+}
+
+/// Adds `added` to `modules`, which are kept sorted.
+fn add_modules(modules: &mut Vec<String>, added: &[&str]) {
+    for module in added {
+        if !modules.iter().any(|existing| existing == module) {
+            let at = modules.partition_point(|existing| existing.as_str() < *module);
+            modules.insert(at, module.to_string());
+        }
+    }
+}
+
+pub fn create_module_dir(project_name: &str, module_name: &str, content: &str) -> Result<()> {
+    let module_dir = Path::new(project_name).join("src").join(module_name);
+    fs::create_dir_all(&module_dir)
+        .with_context(|| format!("Failed to create {} directory", module_name))?;
+
+    let mod_path = module_dir.join("mod.rs");
+    fs::write(mod_path, content).with_context(|| format!("Failed to create {}/mod.rs", module_name))
+}
+
+pub fn create_gitignore(project_name: &str, gitignore: &str) -> Result<()> {
+    let gitignore_path = Path::new(project_name).join(".gitignore");
+    fs::write(gitignore_path, gitignore).context("Failed to create .gitignore file")
+}
+
+/// Appends the entries of the generated `gitignore` missing from an existing one.
+fn merge_gitignore(project_dir: &str, gitignore: &str) -> Result<()> {
+    let gitignore_path = Path::new(project_dir).join(".gitignore");
+    let mut content = fs::read_to_string(&gitignore_path).unwrap_or_default();
+    let normalize = |line: &str| line.trim().trim_end_matches('/').to_string();
+    let existing: Vec<String> = content.lines().map(normalize).collect();
+
+    let missing: Vec<&str> = gitignore
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .filter(|line| !existing.contains(&normalize(line)))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&missing.join("\n"));
+    content.push('\n');
+    fs::write(gitignore_path, content).context("Failed to update .gitignore file")
+}
+
+/// Files of `plan` that already exist in `project_dir`. An existing
+/// .gitignore is merged rather than overwritten, so it is not a conflict.
+pub fn find_conflicts(project_dir: &Path, plan: &ScaffoldPlan) -> Vec<PathBuf> {
+    let mut files = vec![PathBuf::from("Cargo.toml")];
+    files.extend(
+        plan.paths()
+            .filter(|file| {
+                !["Cargo.toml", ".gitignore"]
+                    .map(Path::new)
+                    .contains(&file.as_path())
+            })
+            .cloned(),
+    );
+
+    files
+        .into_iter()
+        .filter(|file| project_dir.join(file).exists())
+        .collect()
+}
+
+/// A dependency written into Cargo.toml.
+#[derive(Debug, Serialize)]
+pub struct Dependency {
+    pub name: String,
+    pub features: Option<&'static str>,
+    /// Feature of the project enabling the dependency, which makes it optional.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature: Option<&'static str>,
+}
+
+/// Everything a scaffold adds to the Cargo project created by `cargo new`:
+/// the dependencies and the files, relative to the project directory. Files
+/// of a template that are not text are copied as they are.
+#[derive(Debug)]
+pub struct ScaffoldPlan {
+    pub dependencies: Vec<Dependency>,
+    pub files: Vec<(PathBuf, String)>,
+    pub copies: Vec<(PathBuf, PathBuf)>,
+    /// Fields set in the `[package]` table of Cargo.toml, e.g. `license`.
+    pub package: Vec<(&'static str, String)>,
+    /// `[lints]` table added to Cargo.toml.
+    pub lints: Option<Table>,
+    /// `crate-type` of the `[lib]` table, `cdylib` for a Worker.
+    pub crate_type: Option<&'static str>,
+}
+
+impl ScaffoldPlan {
+    /// Every path the plan writes, relative to the project directory.
+    fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files
+            .iter()
+            .map(|(file, _)| file)
+            .chain(self.copies.iter().map(|(file, _)| file))
+    }
+}
+
+/// `--framework` of a project without a framework.
+pub const BARE_FRAMEWORK: &str = "none";
+
+/// Fails for a framework without a template, which would only get the
+/// hello-world `main.rs`, suggesting the frameworks spelled alike.
+fn check_framework(name: &str) -> Result<()> {
+    let known: Vec<String> = templates::FRAMEWORKS
+        .iter()
+        .map(|framework| framework.name.to_string())
+        .chain(templates::user_frameworks())
+        .chain(registry::installed_templates())
+        .collect();
+    if known.iter().any(|known| known == name) {
+        return Ok(());
+    }
+    let similar = suggest::similar(name, known.iter().map(String::as_str));
+    let guess = match similar.as_slice() {
+        [] => String::new(),
+        names => format!("Did you mean {}? ", suggest::either(names)),
+    };
+    Err(Error::user(format!(
+        "Unknown framework '{}'\n👉 {}Pass one of {}, or {} for a bare project",
+        name,
+        guess,
+        known.join(", "),
+        BARE_FRAMEWORK
+    )))
+}
+
+/// The framework as a dependency, `name@version` when it is pinned.
+fn framework_dependency(options: &ScaffoldOptions) -> Option<String> {
+    let framework = options.framework.as_ref()?;
+    Some(match &options.framework_version {
+        Some(version) => format!("{}@{}", framework, version),
+        None => framework.clone(),
+    })
+}
+
+fn dependency(name: &str, features: Option<&'static str>) -> Dependency {
+    Dependency {
+        name: name.to_string(),
+        features,
+        feature: None,
+    }
+}
+
+/// sqlx with the tokio runtime and the driver of `database`.
+fn sqlx_dependency(database: &str) -> Dependency {
+    let features = match database {
+        "postgres" => "runtime-tokio,postgres",
+        "mysql" => "runtime-tokio,mysql",
+        _ => "runtime-tokio,sqlite",
+    };
+    dependency("sqlx", Some(features))
+}
+
+/// Adds `route` to the router among `files`, below its routes marker.
+fn add_route(files: &mut [(PathBuf, String)], route: &str) {
+    for (_, content) in files.iter_mut() {
+        if let Some(routed) = generate::insert_route(content, route) {
+            *content = routed;
+            return;
+        }
+    }
+}
+
+pub fn plan_scaffold(name: &str, options: &ScaffoldOptions) -> Result<ScaffoldPlan> {
+    let framework = options.framework.as_deref().unwrap_or_default();
+    let arch = options.arch.as_str();
+    let mut dependencies: Vec<Dependency> = framework_dependency(options)
+        .iter()
+        .chain(&options.deps)
+        .map(|name| dependency(name, None))
+        .chain(options.database.as_deref().map(sqlx_dependency))
+        .collect();
+    let mut files = Vec::new();
+    let author = options.author(name);
+    let context = templates::TemplateContext {
+        name,
+        author: &author,
+        framework,
+        framework_version: options.template_version,
+        arch,
+        modules: &options.modules,
+        features: &options.deps,
+        host: &options.host,
+        port: options.port,
+        target: options.target.as_str(),
+        deploy: options.deploy.map(Deploy::as_str),
+        frontend: options.frontend.map(Frontend::as_str),
+        database: options.database.as_deref(),
+        with: &options.with,
+    };
+    let mut manifest = Manifest::new(name, options.framework.as_deref(), arch, &options.deps);
+    manifest.framework_version = options
+        .framework_version
+        .is_some()
+        .then(|| options.template_version.to_string())
+        .filter(|version| !version.is_empty());
+    manifest.database = options.database.clone();
+    manifest.target = Some(options.target)
+        .filter(|target| *target != Target::Server)
+        .map(|target| target.as_str().to_string());
+    manifest.deploy = options.deploy.map(|deploy| deploy.as_str().to_string());
+    manifest.frontend = options
+        .frontend
+        .map(|frontend| frontend.as_str().to_string());
+    manifest.port = Some(options.port).filter(|port| *port != DEFAULT_PORT);
+    manifest.with = options.with.clone();
+
+    if options.lib {
+        // Libraries only declare their modules, there is no server to start
+        files.push((
+            Path::new("src").join("lib.rs"),
+            templates::get_lib_rs(&options.modules),
+        ));
+    } else if options.target == Target::Workers {
+        // The runtime calls the fetch handler of the library compiled to wasm
+        files.push((
+            Path::new("src").join("lib.rs"),
+            templates::render("workers/lib.rs", &context)?,
+        ));
+        dependencies.push(dependency("worker", None));
+    } else {
+        // main.rs based on framework, declaring the module directories
+        files.push((
+            Path::new("src").join("main.rs"),
+            templates::get_main_rs(&context)?,
+        ));
+
+        // Additional dependencies for async frameworks
+        if matches!(framework, "axum" | "actix-web") {
+            dependencies.push(dependency("serde", Some("derive")));
+            dependencies.push(dependency("tokio", Some("full")));
+        }
+        if options.target == Target::Lambda {
+            dependencies.push(dependency("lambda_http", None));
+        }
+        // The Postgres event store keeps the events as JSON rows of a table
+        if arch == "cqrs"
+            && matches!(framework, "axum" | "actix-web")
+            && options.database.as_deref() == Some("postgres")
+        {
+            dependencies.push(dependency("serde_json", None));
+            files.push((
+                Path::new("migrations").join(format!(
+                    "{}_create_events.sql",
+                    chrono::Utc::now().format("%Y%m%d%H%M%S")
+                )),
+                templates::EVENTS_MIGRATION.to_string(),
+            ));
+        }
+        // The runtime starts the router in place of the server in main.rs
+        if options.deploy == Some(Deploy::Shuttle) {
+            dependencies.push(dependency("shuttle-runtime", None));
+            dependencies.push(dependency(&format!("shuttle-{}", framework), None));
+            if options.database.is_some() {
+                dependencies.push(dependency("shuttle-shared-db", Some("postgres")));
+            }
+        }
+    }
+
+    // Module directories
+    let handlers = handler_module(options);
+    for module in options.modules.iter().map(String::as_str) {
+        // The modules of --with options and --frontend are written below
+        if MODULE_WITH.iter().any(|(option, modules)| {
+            options.with.iter().any(|name| name == option) && modules.contains(&module)
+        }) || (options.frontend.is_some() && frontend::MODULES.contains(&module))
+        {
+            continue;
+        }
+        let content = if options.lib {
+            templates::get_lib_module_content(arch, module)
+        } else {
+            templates::get_module_content(framework, arch, module, options.database.as_deref())
+        };
+        let mut content = templates::adapt_routes(content, framework, options.template_version);
+        // main.rs hands the state to the router of the preset
+        if options.app_state
+            && framework == "axum"
+            && templates::get_router_module(arch) == Some(module)
+        {
+            content = app_state::stateful_router(&content);
+        }
+        if let Some(lint_config) = &options.lint_config
+            && handlers == Some(module)
+        {
+            content.insert_str(0, &lint_config.handlers_attribute());
+        }
+        files.push((Path::new("src").join(module).join("mod.rs"), content));
+    }
+
+    // The example flag gates a route of its own, the Flipt client is optional
+    if options.feature_flags {
+        files.push(feature_flags::file(&context)?);
+        add_route(&mut files, feature_flags::route(framework));
+        dependencies.push(Dependency {
+            feature: Some(feature_flags::FLIPT_FEATURE),
+            ..dependency("reqwest", Some("json"))
+        });
+    }
+    // The example handler greets in the negotiated language
+    if options.i18n {
+        files.extend(i18n::files(&context)?);
+        let route =
+            templates::adapt_routes(i18n::route(framework), framework, options.template_version);
+        add_route(&mut files, &route);
+        dependencies.push(dependency("fluent", None));
+        dependencies.push(dependency("unic-langid", None));
+    }
+    // main.rs creates the indexes on startup, compose starts Meilisearch
+    if options.search {
+        files.push(search::file(&context)?);
+        for route in search::routes(framework) {
+            add_route(&mut files, route);
+        }
+        dependencies.push(dependency("meilisearch-sdk", None));
+        let (file, template) = search::COMPOSE;
+        let compose = templates::render(template, &context)?;
+        files.push((PathBuf::from(file), compose.clone()));
+        files.push((manifest::base_path(file), compose));
+        manifest = manifest.own(file, template);
+    }
+    // The webhook records the completed checkouts in the database, if any
+    if options.payments {
+        files.push(payments::file(&context)?);
+        for route in payments::routes(framework) {
+            add_route(&mut files, route);
+        }
+        dependencies.push(dependency(
+            "async-stripe",
+            Some("runtime-tokio-hyper-rustls"),
+        ));
+        if options.database.is_some() {
+            files.push(payments::migration(&context)?);
+        }
+    }
+    // main.rs creates the upload directory or the S3 client on startup
+    if options.uploads {
+        files.push(uploads::file(&context)?);
+        for (path, handler) in uploads::routes(framework) {
+            let path = templates::adapt_path(path, framework, options.template_version);
+            add_route(&mut files, &generate::route_call(&path, handler));
+        }
+        match framework {
+            "axum" => {
+                let axum = dependencies
+                    .iter_mut()
+                    .find(|dep| DependencySpec::parse(&dep.name).name == "axum");
+                if let Some(axum) = axum {
+                    axum.features = Some("multipart");
+                }
+            }
+            _ => dependencies.push(dependency("actix-multipart", None)),
+        }
+        dependencies.push(dependency("futures-util", None));
+        dependencies.push(dependency("serde_json", None));
+        dependencies.push(dependency("uuid", Some("v4")));
+        for name in ["aws-config", "aws-sdk-s3"] {
+            dependencies.push(Dependency {
+                feature: Some(uploads::S3_FEATURE),
+                ..dependency(name, None)
+            });
+        }
+    }
+
+    // main.rs builds the state and hands it to the router or App
+    if options.app_state {
+        files.push(app_state::file(&context)?);
+        add_route(&mut files, app_state::route(framework));
+        dependencies.push(dependency("reqwest", None));
+        if app_state::has_redis(&options.deps) {
+            dependencies.push(dependency("deadpool-redis", None));
+        }
+    }
+
+    // The console subscriber and pprof are optional, the handler answers 404
+    // without the profiler
+    if options.profiling {
+        files.extend(profiling::files(&context)?);
+        let (path, handler) = profiling::route(framework);
+        add_route(&mut files, &generate::route_call(path, handler));
+        dependencies.push(Dependency {
+            feature: Some(profiling::CONSOLE_FEATURE),
+            ..dependency("console-subscriber", None)
+        });
+        dependencies.push(Dependency {
+            feature: Some(profiling::PPROF_FEATURE),
+            ..dependency("pprof", Some("flamegraph"))
+        });
+    }
+
+    // askama compiles the templates into the binary, the assets are served
+    // from the directory
+    if options.frontend.is_some() {
+        files.extend(frontend::files(&context)?);
+        for (path, handler) in frontend::routes(framework) {
+            add_route(&mut files, &generate::route_call(path, handler));
+        }
+        add_route(&mut files, frontend::assets(framework));
+        dependencies.push(dependency("askama", None));
+        dependencies.push(match framework {
+            "axum" => dependency("tower-http", Some("fs")),
+            _ => dependency("actix-files", None),
+        });
+    }
+
+    let gitignore = templates::render(".gitignore", &context)?;
+    files.push((PathBuf::from(".gitignore"), gitignore.clone()));
+    files.push((manifest::base_path(".gitignore"), gitignore));
+    manifest = manifest.own(".gitignore", ".gitignore");
+
+    if options.docker {
+        let dockerfile = templates::render("project/Dockerfile", &context)?;
+        files.push((PathBuf::from("Dockerfile"), dockerfile.clone()));
+        files.push((manifest::base_path("Dockerfile"), dockerfile));
+        manifest = manifest.own("Dockerfile", "project/Dockerfile");
+    }
+
+    // The SAM template deploys the function behind an HTTP API, cargo lambda
+    // runs it locally
+    if options.target == Target::Lambda {
+        for (file, template) in [
+            ("template.yaml", "lambda/template.yaml"),
+            ("CargoLambda.toml", "lambda/CargoLambda.toml"),
+        ] {
+            let content = templates::render(template, &context)?;
+            files.push((PathBuf::from(file), content.clone()));
+            files.push((manifest::base_path(file), content));
+            manifest = manifest.own(file, template);
+        }
+    }
+
+    // The workflow deploys once the tests pass on main
+    if options.deploy == Some(Deploy::Fly) {
+        // `/healthz` answers `ok` for the health checks of the platform
+        add_route(&mut files, templates::health_route(framework));
+        for (file, template) in [
+            ("fly.toml", "fly/fly.toml"),
+            (".github/workflows/ci.yml", "fly/ci.yml"),
+        ] {
+            let content = templates::render(template, &context)?;
+            files.push((PathBuf::from(file), content.clone()));
+            files.push((manifest::base_path(file), content));
+            manifest = manifest.own(file, template);
+        }
+    }
+
+    // The unit runs the binary install.sh copies, with its environment in /etc
+    if options.deploy == Some(Deploy::Systemd) {
+        let unit = format!("deploy/{}.service", name);
+        for (file, template) in [
+            (unit.as_str(), "systemd/service"),
+            (INSTALL_SCRIPT, "systemd/install.sh"),
+        ] {
+            let content = templates::render(template, &context)?;
+            files.push((PathBuf::from(file), content.clone()));
+            files.push((manifest::base_path(file), content));
+            manifest = manifest.own(file, template);
+        }
+    }
+
+    if options.target == Target::Workers {
+        let content = templates::render("workers/wrangler.toml", &context)?;
+        files.push((PathBuf::from("wrangler.toml"), content.clone()));
+        files.push((manifest::base_path("wrangler.toml"), content));
+        manifest = manifest.own("wrangler.toml", "workers/wrangler.toml");
+    }
+
+    if options.deploy == Some(Deploy::Shuttle) {
+        let content = templates::render("shuttle/Shuttle.toml", &context)?;
+        files.push((PathBuf::from("Shuttle.toml"), content.clone()));
+        files.push((manifest::base_path("Shuttle.toml"), content));
+        manifest = manifest.own("Shuttle.toml", "shuttle/Shuttle.toml");
+    }
+
+    files.push((
+        PathBuf::from(README),
+        templates::render_readme(
+            &context,
+            &templates::Readme {
+                lib: options.lib,
+                docker: options.docker,
+                task_runner: options.task_runner,
+                release: options.release,
+                git_hooks: options.git_hooks,
+                services: &[],
+            },
+        )?,
+    ));
+
+    if let Some(file) = options.task_runner.file_name() {
+        let tasks = templates::render_tasks(
+            file,
+            &templates::Tasks {
+                name,
+                author: &author,
+                workspace: false,
+                lib: options.lib,
+                database: options.database.is_some(),
+                docker: options.docker,
+                compose: false,
+                target: options.target.as_str(),
+                deploy: options.deploy.map(Deploy::as_str),
+                musl: options.musl,
+            },
+        )?;
+        files.push((PathBuf::from(file), tasks.clone()));
+        files.push((manifest::base_path(file), tasks));
+        manifest = manifest.own(file, file);
+    }
+    files.push((PathBuf::from(manifest::FILE), manifest.to_toml()));
+    add_toolchain_file(&mut files, options);
+    add_rustfmt_file(&mut files, options);
+    add_license_files(&mut files, name, options)?;
+    add_release_files(&mut files, &context, options)?;
+    add_git_hook_files(&mut files, &context, options)?;
+    add_musl_files(&mut files, &context, options)?;
+
+    Ok(ScaffoldPlan {
+        dependencies,
+        files,
+        copies: Vec::new(),
+        package: options.package_fields(),
+        lints: options
+            .lint_config
+            .as_ref()
+            .map(|lint_config| lint_config.lints.clone()),
+        crate_type: (options.target == Target::Workers).then_some("cdylib"),
+    })
+}
+
+/// Plans a scaffold from the cloned template: every text file is rendered
+/// with the project context, the framework and `--deps` are added on top of
+/// the dependencies the template declares.
+fn plan_template(
+    name: &str,
+    options: &ScaffoldOptions,
+    checkout: &Checkout,
+) -> Result<ScaffoldPlan> {
+    let author = options.author(name);
+    let context = templates::TemplateContext {
+        name,
+        author: &author,
+        framework: options.framework.as_deref().unwrap_or_default(),
+        framework_version: options.template_version,
+        arch: "",
+        modules: &[],
+        features: &options.deps,
+        host: &options.host,
+        port: options.port,
+        target: options.target.as_str(),
+        deploy: options.deploy.map(Deploy::as_str),
+        frontend: options.frontend.map(Frontend::as_str),
+        database: options.database.as_deref(),
+        with: &options.with,
+    };
+    let mut files = Vec::new();
+    let mut copies = Vec::new();
+
+    for file in checkout.files()? {
+        if file == Path::new(hooks::TEMPLATE_FILE) {
+            continue;
+        }
+        let source = checkout.path().join(&file);
+        match fs::read_to_string(&source) {
+            Ok(content) => {
+                let rendered =
+                    templates::render_source(&file.display().to_string(), &content, &context)?;
+                files.push((file, rendered));
+            }
+            Err(_) => copies.push((file, source)),
+        }
+    }
+    // Templates may ship their own manifest, e.g. to record a database
+    if !files
+        .iter()
+        .any(|(file, _)| file == Path::new(manifest::FILE))
+    {
+        let mut manifest = Manifest::new(name, options.framework.as_deref(), "", &options.deps);
+        manifest.database = options.database.clone();
+        files.push((PathBuf::from(manifest::FILE), manifest.to_toml()));
+    }
+    add_toolchain_file(&mut files, options);
+    add_rustfmt_file(&mut files, options);
+    add_license_files(&mut files, name, options)?;
+    add_release_files(&mut files, &context, options)?;
+    add_git_hook_files(&mut files, &context, options)?;
+    add_musl_files(&mut files, &context, options)?;
+
+    Ok(ScaffoldPlan {
+        dependencies: framework_dependency(options)
+            .iter()
+            .chain(&options.deps)
+            .map(|name| dependency(name, None))
+            .chain(options.database.as_deref().map(sqlx_dependency))
+            .collect(),
+        files,
+        copies,
+        package: options.package_fields(),
+        lints: options
+            .lint_config
+            .as_ref()
+            .map(|lint_config| lint_config.lints.clone()),
+        crate_type: None,
+    })
+}
+
+/// Drops the generated README and license files when `project_dir` has its
+/// own, like the ones GitHub creates with a repository. Templates always
+/// write theirs.
+fn keep_readme(project_dir: &Path, mut plan: ScaffoldPlan, template: bool) -> ScaffoldPlan {
+    if template {
+        return plan;
+    }
+    if project_dir.join(README).exists() {
+        plan.files.retain(|(file, _)| file != Path::new(README));
+    }
+    let has_license = fs::read_dir(project_dir).is_ok_and(|entries| {
+        entries
+            .flatten()
+            .any(|entry| license::is_license_file(Path::new(&entry.file_name())))
+    });
+    if has_license {
+        plan.files
+            .retain(|(file, _)| !license::is_license_file(file));
+    }
+    plan
+}
+
+/// Module of the HTTP handlers of a server, the router's module when the
+/// preset has no separate one.
+fn handler_module(options: &ScaffoldOptions) -> Option<&str> {
+    if options.lib {
+        return None;
+    }
+    let module = if options.modules.iter().any(|module| module == "handlers") {
+        "handlers"
+    } else {
+        templates::get_router_module(&options.arch)?
+    };
+    options
+        .modules
+        .iter()
+        .map(String::as_str)
+        .find(|candidate| *candidate == module)
+}
+
+/// Adds the files of `--with release`, replacing the ones of a template.
+fn add_release_files(
+    files: &mut Vec<(PathBuf, String)>,
+    context: &templates::TemplateContext,
+    options: &ScaffoldOptions,
+) -> Result<()> {
+    if !options.release {
+        return Ok(());
+    }
+    let release = release::files(context, options.lib)?;
+    files.retain(|(file, _)| !release.iter().any(|(path, _)| path == file));
+    files.extend(release);
+    Ok(())
+}
+
+/// Adds the hooks of `--with git-hooks`, replacing the ones of a template.
+fn add_git_hook_files(
+    files: &mut Vec<(PathBuf, String)>,
+    context: &templates::TemplateContext,
+    options: &ScaffoldOptions,
+) -> Result<()> {
+    if !options.git_hooks {
+        return Ok(());
+    }
+    let hooks = git_hooks::files(context)?;
+    files.retain(|(file, _)| !hooks.iter().any(|(path, _)| path == file));
+    files.extend(hooks);
+    Ok(())
+}
+
+/// Adds the cargo and cross configs of `--with musl`, replacing the ones of
+/// a template and the cargo config of `--with profiling`, which the musl one
+/// carries the cfg of.
+fn add_musl_files(
+    files: &mut Vec<(PathBuf, String)>,
+    context: &templates::TemplateContext,
+    options: &ScaffoldOptions,
+) -> Result<()> {
+    if !options.musl {
+        return Ok(());
+    }
+    let musl = musl::files(context)?;
+    files.retain(|(file, _)| !musl.iter().any(|(path, _)| path == file));
+    files.extend(musl);
+    Ok(())
+}
+
+/// Adds the license files of `--license`, replacing the ones of a template.
+fn add_license_files(
+    files: &mut Vec<(PathBuf, String)>,
+    name: &str,
+    options: &ScaffoldOptions,
+) -> Result<()> {
+    if options.licenses.is_empty() {
+        return Ok(());
+    }
+    let holder = options.author(name);
+    files.retain(|(file, _)| !license::is_license_file(file));
+    files.extend(license::files(&options.licenses, name, &holder)?);
+    Ok(())
+}
+
+/// Adds the `rustfmt.toml` of `--with lint-config`, replacing the one of a
+/// template.
+fn add_rustfmt_file(files: &mut Vec<(PathBuf, String)>, options: &ScaffoldOptions) {
+    let Some(lint_config) = &options.lint_config else {
+        return;
+    };
+    let path = PathBuf::from("rustfmt.toml");
+    files.retain(|(file, _)| *file != path);
+    files.push((path, lint_config.rustfmt_toml()));
+}
+
+/// Adds the `rust-toolchain.toml` of `--toolchain`, replacing the one of a
+/// template.
+fn add_toolchain_file(files: &mut Vec<(PathBuf, String)>, options: &ScaffoldOptions) {
+    let Some(toolchain) = &options.toolchain else {
+        return;
+    };
+    let path = PathBuf::from("rust-toolchain.toml");
+    files.retain(|(file, _)| *file != path);
+    let mut table = Table::new();
+    table["channel"] = value(toolchain);
+    table["components"] = value(Array::from_iter(["rustfmt", "clippy"]));
+    let mut document = DocumentMut::new();
+    document["toolchain"] = Item::Table(table);
+    files.push((path, document.to_string()));
+}
+
+/// Lets `path`, a script the scaffold wrote, be run directly.
+fn make_executable(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make {} executable", path.display()))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Adds the dependencies and writes the files of `plan` into the Cargo
+/// project at `project_dir`, which `cargo new`/`cargo init` just created.
+/// An existing .gitignore is merged rather than replaced when `merge` is set.
+fn apply_plan(project_dir: &str, name: &str, plan: &ScaffoldPlan, merge: bool) -> Result<()> {
+    add_dependencies(project_dir, name, plan)?;
+    set_package_fields(project_dir, plan)?;
+    write_files(project_dir, plan, merge)
+}
+
+fn add_dependencies(project_dir: &str, name: &str, plan: &ScaffoldPlan) -> Result<()> {
+    if plan.dependencies.is_empty() {
+        return Ok(());
+    }
+    let step = output::step(match plan.dependencies.len() {
+        1 => "Add 1 dependency".to_string(),
+        count => format!("Add {} dependencies", count),
+    });
+    for dep in &plan.dependencies {
+        say!("Adding {} to {}", dep.name, name);
+    }
+    // One write, with every version looked up at once
+    let specs: Vec<DependencySpec> = plan
+        .dependencies
+        .iter()
+        .map(|dep| DependencySpec {
+            optional: dep.feature.is_some() && !always_added(plan, dep),
+            ..DependencySpec::parse(&dep.name).with_features(dep.features)
+        })
+        .collect();
+    declare_features(project_dir, plan)?;
+    deps::insert(Path::new(project_dir), &specs)?;
+    step.done();
+    Ok(())
+}
+
+/// Declares the features enabling the optional dependencies of `plan`,
+/// which cargo would otherwise name after the crates.
+fn declare_features(project_dir: &str, plan: &ScaffoldPlan) -> Result<()> {
+    if plan.dependencies.iter().all(|dep| dep.feature.is_none()) {
+        return Ok(());
+    }
+    let path = Path::new(project_dir).join("Cargo.toml");
+    let manifest = fs::read_to_string(&path).context("Failed to read Cargo.toml")?;
+    let mut document: DocumentMut = manifest
+        .parse()
+        .map_err(|err| Error::user(format!("Failed to parse Cargo.toml: {}", err)))?;
+    if !document.contains_key("features") {
+        document["features"] = Item::Table(Table::new());
+    }
+    for dep in &plan.dependencies {
+        // A feature can enable several dependencies
+        if let Some(feature) = dep.feature {
+            if document["features"].get(feature).is_none() {
+                document["features"][feature] = value(Array::new());
+            }
+            if always_added(plan, dep) {
+                continue;
+            }
+            let name = format!("dep:{}", DependencySpec::parse(&dep.name).name);
+            if let Some(deps) = document["features"][feature].as_array_mut() {
+                deps.push(name);
+            }
+        }
+    }
+    fs::write(&path, document.to_string()).context("Failed to update Cargo.toml")
+}
+
+/// Whether `plan` adds the crate of `dep` without a feature as well, which
+/// leaves the feature of `dep` nothing to enable.
+fn always_added(plan: &ScaffoldPlan, dep: &Dependency) -> bool {
+    let name = DependencySpec::parse(&dep.name).name;
+    plan.dependencies
+        .iter()
+        .any(|other| other.feature.is_none() && DependencySpec::parse(&other.name).name == name)
+}
+
+/// Sets the `[package]` fields, `[lints]` and crate type of the plan in the
+/// Cargo.toml at `project_dir`.
+fn set_package_fields(project_dir: &str, plan: &ScaffoldPlan) -> Result<()> {
+    if plan.package.is_empty() && plan.lints.is_none() && plan.crate_type.is_none() {
+        return Ok(());
+    }
+    let path = Path::new(project_dir).join("Cargo.toml");
+    let manifest = fs::read_to_string(&path).context("Failed to read Cargo.toml")?;
+    let mut document: DocumentMut = manifest
+        .parse()
+        .map_err(|err| Error::user(format!("Failed to parse Cargo.toml: {}", err)))?;
+    // Workspace manifests of a template have no package to set them in
+    let Some(package) = document.get_mut("package").and_then(Item::as_table_mut) else {
+        return Ok(());
+    };
+    for (key, field) in &plan.package {
+        // Cargo takes either the license or a file of the template's
+        match *key {
+            "license" => package.remove("license-file"),
+            "license-file" => package.remove("license"),
+            _ => None,
+        };
+        package[key] = value(field);
+    }
+    if let Some(lints) = &plan.lints {
+        document["lints"] = Item::Table(lints.clone());
+    }
+    if let Some(crate_type) = plan.crate_type {
+        let mut lib = Table::new();
+        lib["crate-type"] = value(Array::from_iter([crate_type]));
+        document["lib"] = Item::Table(lib);
+    }
+    fs::write(&path, document.to_string()).context("Failed to update Cargo.toml")
+}
+
+fn write_files(project_dir: &str, plan: &ScaffoldPlan, merge: bool) -> Result<()> {
+    let step = output::step(match plan.paths().count() {
+        1 => "Render 1 file".to_string(),
+        count => format!("Render {} files", count),
+    });
+    for (file, content) in &plan.files {
+        let path = Path::new(project_dir).join(file);
+        if file == Path::new(".gitignore") {
+            if merge {
+                say!("\nUpdating .gitignore file");
+                merge_gitignore(project_dir, content)?;
+            } else {
+                say!("\nCreating .gitignore file");
+                create_gitignore(project_dir, content)?;
+            }
+            output::file(&path);
+            continue;
+        }
+
+        create_parent_dir(&path)?;
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", file.display()))?;
+        output::file(&path);
+    }
+
+    for (file, source) in &plan.copies {
+        let path = Path::new(project_dir).join(file);
+        create_parent_dir(&path)?;
+        fs::copy(source, &path).with_context(|| format!("Failed to copy {}", file.display()))?;
+        output::file(&path);
+    }
+
+    step.done();
+    Ok(())
+}
+
+fn create_parent_dir(path: &Path) -> Result<()> {
+    match path.parent() {
+        Some(parent) => fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display())),
+        None => Ok(()),
+    }
+}
+
+/// Renders `files` as a tree below `root`, directories first.
+fn print_tree(root: &str, files: &[PathBuf]) {
+    fn print_level(prefix: &str, paths: &[Vec<String>]) {
+        let mut entries: Vec<(&String, bool)> = Vec::new();
+        for path in paths {
+            let is_dir = path.len() > 1;
+            if !entries.iter().any(|(name, _)| *name == &path[0]) {
+                entries.push((&path[0], is_dir));
+            }
+        }
+        entries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        for (index, (name, is_dir)) in entries.iter().enumerate() {
+            let last = index == entries.len() - 1;
+            let branch = if last { "└── " } else { "├── " };
+            if !is_dir {
+                say!("{}{}{}", prefix, branch, name);
+                continue;
+            }
+
+            say!("{}{}{}/", prefix, branch, name);
+            let children: Vec<Vec<String>> = paths
+                .iter()
+                .filter(|path| path.len() > 1 && &path[0] == *name)
+                .map(|path| path[1..].to_vec())
+                .collect();
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            print_level(&child_prefix, &children);
+        }
+    }
+
+    let paths: Vec<Vec<String>> = files
+        .iter()
+        .map(|file| {
+            file.components()
+                .map(|part| part.as_os_str().to_string_lossy().into_owned())
+                .collect()
+        })
+        .collect();
+
+    say!("{}/", root);
+    print_level("", &paths);
+}
+
+/// Prints what a scaffold would do without touching disk or running cargo.
+/// Sets what the scaffold plans in the `result` of `--output json`.
+fn record_plan(project_dir: &Path, plan: &ScaffoldPlan, conflicts: &[PathBuf]) {
+    output::set("project_dir", project_dir);
+    output::set("dependencies", &plan.dependencies);
+    output::set(
+        "package",
+        plan.package
+            .iter()
+            .map(|(key, field)| (key.to_string(), serde_json::json!(field)))
+            .collect::<serde_json::Map<_, _>>(),
+    );
+    let planned: Vec<serde_json::Value> = plan
+        .files
+        .iter()
+        .map(|(file, content)| serde_json::json!({ "path": file, "content": content }))
+        .chain(
+            plan.copies
+                .iter()
+                .map(|(file, _)| serde_json::json!({ "path": file, "content": null })),
+        )
+        .collect();
+    output::set("planned_files", planned);
+    output::set("conflicts", conflicts);
+}
+
+fn print_dry_run(project_dir: &Path, plan: &ScaffoldPlan, conflicts: &[PathBuf]) {
+    output::set("dry_run", true);
+    // The planned files and their content are part of the result
+    if output::is_json() {
+        return;
+    }
+    say!("Dry run, nothing will be written\n");
+
+    say!("Dependencies:");
+    for dep in &plan.dependencies {
+        match dep.features {
+            Some(features) => say!("  - {} (features: {})", dep.name, features),
+            None => say!("  - {}", dep.name),
+        }
+    }
+    if !plan.package.is_empty() || plan.lints.is_some() || plan.crate_type.is_some() {
+        say!("\nSet in Cargo.toml:");
+        for (key, field) in &plan.package {
+            say!("  - {} = \"{}\"", key, field);
+        }
+        if let Some(crate_type) = plan.crate_type {
+            say!("  - lib.crate-type = [\"{}\"]", crate_type);
+        }
+        for (tool, levels) in plan.lints.iter().flat_map(Table::iter) {
+            for (lint, level) in levels
+                .as_table_like()
+                .into_iter()
+                .flat_map(|levels| levels.iter())
+            {
+                say!("  - lints.{}.{} = {}", tool, lint, level.to_string().trim());
+            }
+        }
+    }
+
+    say!("\nFiles:");
+    let mut files: Vec<PathBuf> = plan.paths().cloned().collect();
+    let manifest = PathBuf::from("Cargo.toml");
+    let cargo_manifest = !files.contains(&manifest);
+    if cargo_manifest {
+        files.push(manifest);
+    }
+    print_tree(&project_dir.to_string_lossy(), &files);
+    if cargo_manifest {
+        say!("\nCargo.toml is created by cargo, the dependencies above are added to it.");
+    }
+
+    if !conflicts.is_empty() {
+        say!("\nThese files already exist and would be overwritten:");
+        for file in conflicts {
+            say!("  - {}", project_dir.join(file).display());
+        }
+    }
+
+    // Base copies repeat the files they belong to
+    for (file, content) in plan
+        .files
+        .iter()
+        .filter(|(file, _)| !file.starts_with(manifest::BASE_DIR))
+    {
+        say!("\n--- {} ---", file.display());
+        output::text(content);
+    }
+}
+
+fn cargo_command(subcommand: &str, options: &ScaffoldOptions) -> Command {
+    let mut cmd = Command::new("cargo");
+    cmd.arg(subcommand);
+    if options.lib || options.target == Target::Workers {
+        cmd.arg("--lib");
+    }
+    if !options.git {
+        cmd.args(["--vcs", "none"]);
+    }
+    cmd
+}
+
+/// Lists the files that would be overwritten in `project_dir`, one per line.
+fn format_conflicts(conflicts: &[PathBuf], project_dir: &Path) -> String {
+    conflicts
+        .iter()
+        .map(|file| format!("\n  - {}", project_dir.join(file).display()))
+        .collect()
+}
+
+/// Runs `cargo init` in an existing directory, removing a previous
+/// `Cargo.toml` first since `cargo init` refuses to overwrite it.
+fn init_existing_dir(
+    project_dir: &Path,
+    name: Option<&str>,
+    options: &ScaffoldOptions,
+) -> Result<()> {
+    let manifest = project_dir.join("Cargo.toml");
+    if manifest.exists() {
+        fs::remove_file(&manifest).context("Failed to remove existing Cargo.toml")?;
+    }
+
+    let step = output::step("Initialize the Cargo project");
+    let mut cmd = cargo_command("init", options);
+    cmd.arg(project_dir);
+    if let Some(name) = name {
+        cmd.args(["--name", name]);
+    }
+    run_cargo(&mut cmd, "initialize the Cargo project")?;
+    step.done();
+    Ok(())
+}
+
+/// Undoes a scaffold that did not finish, including one aborted by a panic.
+/// Every tracked path is put back the way it was when the guard was created:
+/// files are restored from their backup and paths that did not exist yet are
+/// removed again, so a failed dependency never leaves a half-built project.
+pub struct Rollback {
+    project_dir: PathBuf,
+    backups: Vec<(PathBuf, Option<Vec<u8>>)>,
+    committed: bool,
+}
+
+impl Rollback {
+    /// Guards a project directory that is about to be created, the whole
+    /// directory is removed on failure.
+    pub fn new_project(project_dir: &Path) -> Self {
+        Rollback {
+            project_dir: project_dir.to_path_buf(),
+            backups: vec![(project_dir.to_path_buf(), None)],
+            committed: false,
+        }
+    }
+
+    /// Guards `files`, relative to the existing `project_dir`. Directories
+    /// among them are only removed when they did not exist yet.
+    pub fn existing_dir(
+        project_dir: &Path,
+        files: impl IntoIterator<Item = PathBuf>,
+    ) -> Result<Self> {
+        let mut backups: Vec<(PathBuf, Option<Vec<u8>>)> = Vec::new();
+        for file in files {
+            // Parent directories created along the way are removed as well
+            for path in file.ancestors().filter(|path| !path.as_os_str().is_empty()) {
+                let path = project_dir.join(path);
+                if backups.iter().any(|(tracked, _)| *tracked == path) {
+                    continue;
+                }
+                if !path.exists() {
+                    backups.push((path, None));
+                } else if path.is_file() {
+                    let content = fs::read(&path)
+                        .with_context(|| format!("Failed to back up {}", path.display()))?;
+                    backups.push((path, Some(content)));
+                }
+            }
+        }
+
+        Ok(Rollback {
+            project_dir: project_dir.to_path_buf(),
+            backups,
+            committed: false,
+        })
+    }
+
+    /// Keeps everything written since the guard was created.
+    pub fn commit(mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for Rollback {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+
+        for (path, backup) in &self.backups {
+            let restored = match backup {
+                Some(content) => fs::write(path, content),
+                None if path.is_dir() => fs::remove_dir_all(path),
+                None if path.exists() => fs::remove_file(path),
+                None => Ok(()),
+            };
+            if restored.is_err() {
+                output::warn(format!("Failed to roll back {}", path.display()));
+            }
+        }
+
+        if self.project_dir.exists() {
+            output::warn(format!(
+                "Rolled back the files changed in {}",
+                self.project_dir.display()
+            ));
+        } else {
+            output::warn(format!(
+                "Rolled back, removed the partially created {}",
+                self.project_dir.display()
+            ));
+        }
+    }
+}
+
+/// Paths a scaffold into the existing `project_dir` may create or change.
+fn touched_files(project_dir: &Path, plan: &ScaffoldPlan) -> Vec<PathBuf> {
+    let mut files = vec![PathBuf::from("Cargo.toml"), PathBuf::from("Cargo.lock")];
+    // `cargo init` creates a repository unless the directory is in one already
+    if !project_dir.join(".git").exists() {
+        files.push(PathBuf::from(".git"));
+    }
+    files.extend(plan.paths().cloned());
+    files
+}
+
+/// Creates a git repository in `project_dir` unless it is already inside one,
+/// like `cargo new` does.
+fn init_git_repo(project_dir: &Path) -> Result<()> {
+    if Repository::discover(project_dir).is_ok() {
+        return Ok(());
+    }
+    let step = output::step("Initialize the git repository");
+    tracing::debug!("initializing a git repository in {}", project_dir.display());
+    Repository::init(project_dir).map_err(|err| {
+        Error::environment(format!("Failed to initialize git: {}", err.message()))
+    })?;
+    step.done();
+    Ok(())
+}
+
+/// Sets the `--git-author` (or the `git-author` of the user config) as the
+/// identity of the repository the scaffold created, so commits in the
+/// project use it.
+fn set_git_author(repo: &Repository, options: &ScaffoldOptions) -> Result<()> {
+    let Some((name, email)) = options.git_author.as_deref().and_then(config::parse_author) else {
+        return Ok(());
+    };
+    tracing::debug!("setting the git author to {} <{}>", name, email);
+    let mut config = repo.config().map_err(git_error)?;
+    config.set_str("user.name", name).map_err(git_error)?;
+    config.set_str("user.email", email).map_err(git_error)
+}
+
+fn git_error(err: git2::Error) -> Error {
+    Error::environment(format!("Git failed: {}", err.message()))
+}
+
+/// Author of the initial commit when git has no user configured.
+const FALLBACK_AUTHOR: (&str, &str) = ("forgeit", "forgeit@localhost");
+
+/// Commits the scaffold in the repository it created, as the user git is
+/// configured with. Only without one does the commit fall back to forgeit.
+fn commit_scaffold(project_dir: &Path, options: &ScaffoldOptions) -> Result<InitialCommit> {
+    let repo = Repository::open(project_dir).map_err(git_error)?;
+    set_git_author(&repo, options)?;
+    let step = output::step("Create the initial commit");
+    // Reads the repository, global and system config, like git commit
+    let signature = match repo.signature() {
+        Ok(signature) => signature,
+        Err(_) => {
+            say!(
+                "👉 Set user.name and user.email with git config, the initial commit is by forgeit"
+            );
+            git2::Signature::now(FALLBACK_AUTHOR.0, FALLBACK_AUTHOR.1).map_err(git_error)?
+        }
+    };
+    let mut index = repo.index().map_err(git_error)?;
+    // The .gitignore keeps target/ and .env out
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .map_err(git_error)?;
+    index.write().map_err(git_error)?;
+    let tree = index
+        .write_tree()
+        .and_then(|tree| repo.find_tree(tree))
+        .map_err(git_error)?;
+    tracing::debug!(
+        "committing as {} <{}>",
+        signature.name().unwrap_or_default(),
+        signature.email().unwrap_or_default()
+    );
+    let config = repo.config().map_err(git_error)?;
+    // cargo leaves HEAD on init.defaultBranch, or on master without one
+    let branch = options
+        .default_branch
+        .clone()
+        .or_else(|| config.get_string("init.defaultBranch").ok())
+        .filter(|branch| !branch.is_empty());
+    if let Some(branch) = &branch {
+        repo.set_head(&format!("refs/heads/{}", branch))
+            .map_err(git_error)?;
+    }
+    let signer = if options.sign {
+        Signer::from_config(&config)?
+    } else {
+        None
+    };
+    let commit = match &signer {
+        None => repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                &options.commit_message,
+                &tree,
+                &[],
+            )
+            .map_err(git_error)?,
+        Some(signer) => {
+            let buffer = repo
+                .commit_create_buffer(&signature, &signature, &options.commit_message, &tree, &[])
+                .map_err(git_error)?;
+            let buffer = buffer.as_str().unwrap_or_default();
+            let committer = format!(
+                "{} <{}>",
+                signature.name().unwrap_or_default(),
+                signature.email().unwrap_or_default()
+            );
+            let commit = repo
+                .commit_signed(buffer, &signer.sign(buffer, &committer)?, None)
+                .map_err(git_error)?;
+            // Unlike commit, commit_signed leaves HEAD alone
+            let head = head_branch(&repo)?;
+            repo.reference(&head, commit, true, "commit (initial): signed")
+                .map_err(git_error)?;
+            commit
+        }
+    };
+    step.done();
+    let head = head_branch(&repo)?;
+    let commit = InitialCommit {
+        id: commit.to_string()[..7].to_string(),
+        branch: head.trim_start_matches("refs/heads/").to_string(),
+        signed: signer.is_some(),
+    };
+    output::set("commit", &commit);
+    Ok(commit)
+}
+
+/// The commit of a scaffold in the repository it created.
+#[derive(Debug, Serialize)]
+struct InitialCommit {
+    /// Abbreviated hash.
+    id: String,
+    branch: String,
+    signed: bool,
+}
+
+/// What the scaffold did in git, for the summary.
+struct GitSummary {
+    had_repo: bool,
+    commit: Option<InitialCommit>,
+    /// Web URL of the `--github` repository.
+    github: Option<String>,
+}
+
+/// Creates the `--github` repository and pushes the initial commit to it.
+fn publish_github(
+    project_dir: &Path,
+    options: &ScaffoldOptions,
+    git: &mut GitSummary,
+) -> Result<()> {
+    let Some(repo) = &options.github else {
+        return Ok(());
+    };
+    let Some(commit) = &git.commit else {
+        return Err(Error::user(
+            "--github pushes the repository the scaffold creates, but the project is inside an existing one\n👉 Push it with git remote add and git push",
+        ));
+    };
+    let hint = "👉 The project is scaffolded and committed, push it once the repository exists";
+    let url = github::publish(project_dir, repo, &commit.branch).map_err(|err| match err {
+        Error::User(message) => Error::User(format!("{}\n{}", message, hint)),
+        Error::Environment(message) => Error::Environment(format!("{}\n{}", message, hint)),
+        internal => internal,
+    })?;
+    output::set("github", &url);
+    git.github = Some(url);
+    Ok(())
+}
+
+/// The branch HEAD points at, which has no commit yet in a new repository.
+fn head_branch(repo: &Repository) -> Result<String> {
+    let head = repo.find_reference("HEAD").map_err(git_error)?;
+    head.symbolic_target()
+        .map(str::to_string)
+        .ok_or_else(|| Error::Internal("HEAD of the new repository is detached".to_string()))
+}
+
+/// Writes a template plan into `project_dir`, running `cargo init` only when
+/// the template has no Cargo.toml of its own.
+fn create_from_template(
+    project_dir: &Path,
+    name: &str,
+    plan: &ScaffoldPlan,
+    options: &ScaffoldOptions,
+    merge: bool,
+) -> Result<()> {
+    let dir = project_dir.to_string_lossy();
+    fs::create_dir_all(project_dir)
+        .with_context(|| format!("Failed to create {}", project_dir.display()))?;
+    write_files(&dir, plan, merge)?;
+    if !project_dir.join("Cargo.toml").exists() {
+        init_existing_dir(project_dir, Some(name), options)?;
+    }
+    add_dependencies(&dir, name, plan)?;
+    set_package_fields(&dir, plan)?;
+    if !options.git {
+        return Ok(());
+    }
+    init_git_repo(project_dir)
+}
+
+/// Writes what the plugins of the `--with` options returned.
+fn apply_plugins(project_dir: &Path, outputs: &[plugin::Output]) -> Result<()> {
+    if outputs.is_empty() {
+        return Ok(());
+    }
+    let step = output::step("Apply the --with plugins");
+    for output in outputs {
+        plugin::apply(project_dir, output)?;
+    }
+    step.done();
+    Ok(())
+}
+
+/// Runs the plugins of the `--with` options, which only return what they
+/// want written, so this happens before anything touches the disk.
+fn run_plugins(
+    name: &str,
+    project_dir: &Path,
+    options: &ScaffoldOptions,
+) -> Result<Vec<plugin::Output>> {
+    let with: Vec<String> = options
+        .with
+        .iter()
+        .filter(|option| !BUILTIN_WITH.iter().any(|(name, _)| name == option))
+        .cloned()
+        .collect();
+    plugin::run_with_options(
+        &with,
+        &plugin::ProjectContext {
+            version: env!("CARGO_PKG_VERSION"),
+            name: name.to_string(),
+            project_dir: project_dir.to_path_buf(),
+            framework: options.framework.clone(),
+            database: options.database.clone(),
+            features: options.deps.clone(),
+            args: Vec::new(),
+        },
+    )
+}
+
+/// Clones the `--template` repository, if any. The checkout holds the files
+/// the plan copies, so it has to outlive the scaffold.
+fn fetch_template(options: &ScaffoldOptions) -> Result<Option<Checkout>> {
+    match &options.template {
+        Some(TemplateSource::Git { url, branch }) => {
+            say!("Fetching template {}", url);
+            remote::fetch_template(url, branch.as_deref()).map(Some)
+        }
+        Some(TemplateSource::Installed { name, dir }) => {
+            say!("Using installed template {}", name);
+            Ok(Some(Checkout::local(dir.clone())))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Hooks of the scaffold: the template's, when `--hooks` allows them, then
+/// the `--pre-hook`/`--post-hook` commands.
+fn collect_hooks(options: &ScaffoldOptions, checkout: Option<&Checkout>) -> Result<Hooks> {
+    let mut hooks = Hooks::default();
+    if let Some(checkout) = checkout {
+        let template_hooks = Hooks::from_template(checkout)?;
+        // Templates are often someone else's code, so their commands only
+        // run when asked for
+        if options.template_hooks {
+            hooks.extend(template_hooks);
+        } else if !template_hooks.is_empty() {
+            say!("👉 The template declares hooks, pass --hooks to run them");
+        }
+    }
+    hooks.extend(Hooks {
+        pre: options.hooks.pre.clone(),
+        post: options.hooks.post.clone(),
+    });
+    Ok(hooks)
+}
+
+/// Builds the scaffolded project with `--verify`, so templates that no
+/// longer match the dependencies they resolved to fail right away. A
+/// failure keeps the project for a look at the errors.
+fn verify_project(project_dir: &Path, options: &ScaffoldOptions) -> Result<()> {
+    let Some(verify) = options.verify else {
+        return Ok(());
+    };
+    let subcommand = verify.subcommand();
+    let step = output::step(format!("Verify the project with cargo {}", subcommand));
+    let mut cmd = Command::new("cargo");
+    cmd.arg(subcommand).current_dir(project_dir);
+    if network::is_offline() {
+        cmd.arg("--offline");
+    }
+    run_cargo(
+        &mut cmd,
+        &format!(
+            "verify the project with cargo {}, it is kept as scaffolded in {}",
+            subcommand,
+            project_dir.display()
+        ),
+    )?;
+    step.done();
+    output::set("verified", subcommand);
+    Ok(())
+}
+
+/// Prints a table of what the scaffold generated.
+fn print_summary(
+    project_dir: &Path,
+    name: &str,
+    plan: &ScaffoldPlan,
+    options: &ScaffoldOptions,
+    plugin_outputs: &[plugin::Output],
+    git: &GitSummary,
+) {
+    // The result event of JSON mode carries the same
+    if output::is_json() {
+        return;
+    }
+    let project = if project_dir == Path::new(name) {
+        name.to_string()
+    } else {
+        format!("{} ({})", name, project_dir.display())
+    };
+    let mut rows = vec![("Project", project)];
+    match &options.template {
+        Some(TemplateSource::Git { url, .. }) => rows.push(("Template", url.clone())),
+        Some(TemplateSource::Installed { name, .. }) => rows.push(("Template", name.clone())),
+        None => {
+            if let Some(framework) = &options.framework {
+                rows.push(("Framework", framework.clone()));
+            }
+            rows.push(("Architecture", options.arch.clone()));
+        }
+    }
+    if options.target != Target::Server {
+        rows.push(("Target", options.target.as_str().to_string()));
+    }
+    if let Some(deploy) = options.deploy {
+        rows.push(("Deploy", deploy.as_str().to_string()));
+    }
+    if let Some(frontend) = options.frontend {
+        rows.push(("Frontend", frontend.as_str().to_string()));
+    }
+    let dependencies: Vec<&str> = plan
+        .dependencies
+        .iter()
+        .map(|dep| dep.name.as_str())
+        .collect();
+    rows.push((
+        "Dependencies",
+        if dependencies.is_empty() {
+            "none".to_string()
+        } else {
+            dependencies.join(", ")
+        },
+    ));
+    let files = plan.paths().count() + plugin::touched_files(plugin_outputs).count();
+    rows.push(("Files", files.to_string()));
+    for (label, key) in [
+        ("Edition", "edition"),
+        ("Rust version", "rust-version"),
+        ("License", "license"),
+        ("License file", "license-file"),
+    ] {
+        if let Some((_, field)) = plan.package.iter().find(|(field, _)| *field == key) {
+            rows.push((label, field.clone()));
+        }
+    }
+    if let Some(toolchain) = &options.toolchain {
+        rows.push(("Toolchain", toolchain.clone()));
+    }
+    if git.had_repo {
+        rows.push(("Git", "existing repository".to_string()));
+    } else if let Some(commit) = &git.commit {
+        rows.push((
+            "Git",
+            format!(
+                "new repository, initial commit {} on {}{}",
+                commit.id,
+                commit.branch,
+                if commit.signed { " (signed)" } else { "" }
+            ),
+        ));
+    }
+    if let Some(url) = &git.github {
+        rows.push(("GitHub", url.clone()));
+    }
+    if let Some(verify) = options.verify {
+        rows.push(("Verified", format!("cargo {} passed", verify.subcommand())));
+    }
+
+    say!();
+    for (label, value) in rows {
+        say!("  {:<14}{}", label, value);
+    }
+    say!();
+}
+
+fn print_next_steps(project_dir: &Path, options: &ScaffoldOptions) {
+    let run = if options.lib {
+        "cargo test"
+    } else if options.target == Target::Lambda {
+        "cargo lambda watch"
+    } else if options.target == Target::Workers {
+        "npx wrangler dev"
+    } else if options.deploy == Some(Deploy::Shuttle) {
+        "shuttle run"
+    } else {
+        "cargo run"
+    };
+    if project_dir == Path::new(".") {
+        say!("👉 {}", run);
+    } else {
+        say!("👉 cd {} && {}", project_dir.display(), run);
+    }
+}
+
+/// Everything a scaffold decides before it writes anything.
+struct Prepared {
+    checkout: Option<Checkout>,
+    plan: ScaffoldPlan,
+    conflicts: Vec<PathBuf>,
+    plugin_outputs: Vec<plugin::Output>,
+    hooks: Hooks,
+    had_repo: bool,
+}
+
+impl Prepared {
+    fn new(name: &str, project_dir: &Path, options: &ScaffoldOptions) -> Result<Self> {
+        let checkout = fetch_template(options)?;
+        let plan = match &checkout {
+            Some(checkout) => plan_template(name, options, checkout)?,
+            None => plan_scaffold(name, options)?,
+        };
+        let plan = keep_readme(project_dir, plan, checkout.is_some());
+        let conflicts = find_conflicts(project_dir, &plan);
+        record_plan(project_dir, &plan, &conflicts);
+        let plugin_outputs = run_plugins(name, project_dir, options)?;
+        let hooks = collect_hooks(options, checkout.as_ref())?;
+        Ok(Prepared {
+            checkout,
+            plan,
+            conflicts,
+            plugin_outputs,
+            hooks,
+            had_repo: project_dir.join(".git").exists(),
+        })
+    }
+
+    fn print_dry_run(&self, project_dir: &Path) {
+        print_dry_run(project_dir, &self.plan, &self.conflicts);
+        plugin::print_outputs(&self.plugin_outputs);
+        self.hooks.print();
+    }
+}
+
+/// Writes a prepared scaffold into `project_dir`, a new directory or, with
+/// `existing`, one that is already there, then runs the hooks, commits,
+/// publishes and verifies it. A failure before the commit rolls it back.
+fn build_project(
+    name: &str,
+    project_dir: &Path,
+    existing: bool,
+    prepared: Prepared,
+    options: &ScaffoldOptions,
+) -> Result<()> {
+    let Prepared {
+        checkout,
+        plan,
+        conflicts: _,
+        plugin_outputs,
+        hooks,
+        had_repo,
+    } = prepared;
+    let project = HookProject {
+        name,
+        dir: project_dir,
+        framework: options.framework.as_deref(),
+        arch: &options.arch,
+    };
+
+    let rollback = if existing {
+        let touched = touched_files(project_dir, &plan)
+            .into_iter()
+            .chain(plugin::touched_files(&plugin_outputs));
+        Rollback::existing_dir(project_dir, touched)?
+    } else {
+        Rollback::new_project(project_dir)
+    };
+
+    // A new project directory does not exist yet, so the pre hooks run where
+    // forgeit was started
+    hooks::run("pre", &hooks.pre, Path::new("."), &project)?;
+    if checkout.is_some() {
+        create_from_template(project_dir, name, &plan, options, existing)?;
+    } else {
+        if existing {
+            // `cargo init` keeps the files already in the directory
+            init_existing_dir(project_dir, Some(name), options)?;
+        } else {
+            // Run `cargo new <path> --name <name>`
+            let step = output::step(format!("Create the Cargo project {}", name));
+            run_cargo(
+                cargo_command("new", options)
+                    .arg(project_dir)
+                    .args(["--name", name]),
+                &format!("create project '{}'", name),
+            )?;
+            step.done();
+        }
+
+        // Keep the entries of an existing .gitignore, e.g. one created by GitHub
+        apply_plan(&project_dir.to_string_lossy(), name, &plan, existing)?;
+    }
+    apply_plugins(project_dir, &plugin_outputs)?;
+    if options.git_hooks {
+        git_hooks::install(project_dir, options.git)?;
+    }
+    if options.deploy == Some(Deploy::Systemd) {
+        make_executable(&project_dir.join(INSTALL_SCRIPT))?;
+    }
+    let mut git = GitSummary {
+        had_repo,
+        commit: None,
+        github: None,
+    };
+    if !had_repo && project_dir.join(".git").exists() {
+        git.commit = Some(commit_scaffold(project_dir, options)?);
+    }
+    rollback.commit();
+    // The project is complete, a failed push leaves it for a manual one
+    publish_github(project_dir, options, &mut git)?;
+    // The project is complete, so a failed post-scaffold hook keeps it
+    hooks::run("post", &hooks.post, project_dir, &project)?;
+    verify_project(project_dir, options)?;
+
+    say!("\n✅ Project '{}' scaffolded successfully!", name);
+    print_summary(project_dir, name, &plan, options, &plugin_outputs, &git);
+    print_next_steps(project_dir, options);
+    Ok(())
+}
+
+pub fn scaffold_project(name: &str, path: Option<&Path>, options: &ScaffoldOptions) -> Result<()> {
+    let project_dir = path.unwrap_or(Path::new(name));
+    let existed = project_dir.exists();
+    let prepared = Prepared::new(name, project_dir, options)?;
+
+    if options.dry_run {
+        prepared.print_dry_run(project_dir);
+        return Ok(());
+    }
+
+    let conflicts = &prepared.conflicts;
+    if existed {
+        if !options.force {
+            let mut message = format!("'{}' already exists", project_dir.display());
+            if !conflicts.is_empty() {
+                message.push_str("\nThese files would be overwritten:");
+                message.push_str(&format_conflicts(conflicts, project_dir));
+            }
+            message.push_str("\n👉 Pass --force to scaffold into it anyway");
+            return Err(Error::User(message));
+        }
+
+        say!(
+            "Scaffolding into existing directory: {}",
+            project_dir.display()
+        );
+        if !conflicts.is_empty() {
+            say!("Overwriting:{}", format_conflicts(conflicts, project_dir));
+        }
+    } else {
+        say!("Creating new Cargo project: {}", name);
+    }
+
+    build_project(name, project_dir, existed, prepared, options)
+}
+
+pub fn init_project(options: &ScaffoldOptions) -> Result<()> {
+    let project_dir = Path::new(".");
+    let name = package_name::derive(project_dir)?;
+    let prepared = Prepared::new(&name, project_dir, options)?;
+
+    if options.dry_run {
+        prepared.print_dry_run(project_dir);
+        return Ok(());
+    }
+
+    let conflicts = &prepared.conflicts;
+    if !conflicts.is_empty() && !options.force {
+        return Err(Error::User(format!(
+            "These files already exist and would be overwritten:{}\n👉 Pass --force to overwrite them",
+            format_conflicts(conflicts, project_dir)
+        )));
+    }
+
+    say!("Initializing Cargo project in the current directory");
+    if !conflicts.is_empty() {
+        say!("Overwriting:{}", format_conflicts(conflicts, project_dir));
+    }
+
+    build_project(&name, project_dir, true, prepared, options)
+}
@@ -0,0 +1,331 @@
+use sqlx::{Connection, MySqlConnection, PgConnection, SqliteConnection};
+
+use crate::error::{Context, Error, Result};
+
+/// Table of sqlx's migrations, which gets no model.
+const MIGRATIONS_TABLE: &str = "_sqlx_migrations";
+
+/// Columns of the tables in the current schema, views left out, in the
+/// order of their definition.
+const POSTGRES_COLUMNS: &str = "SELECT c.table_name::text, c.column_name::text,
+    CASE WHEN c.data_type IN ('ARRAY', 'USER-DEFINED') THEN c.udt_name::text ELSE c.data_type::text END,
+    c.is_nullable = 'YES',
+    EXISTS (
+        SELECT 1 FROM information_schema.table_constraints t
+        JOIN information_schema.key_column_usage k
+            ON k.constraint_name = t.constraint_name AND k.table_schema = t.table_schema
+        WHERE t.constraint_type = 'PRIMARY KEY' AND t.table_schema = c.table_schema
+            AND t.table_name = c.table_name AND k.column_name = c.column_name
+    )
+FROM information_schema.columns c
+JOIN information_schema.tables tb ON tb.table_schema = c.table_schema AND tb.table_name = c.table_name
+WHERE c.table_schema = current_schema() AND tb.table_type = 'BASE TABLE'
+ORDER BY c.table_name, c.ordinal_position";
+
+/// Columns of the tables in the database of the connection, see
+/// [`POSTGRES_COLUMNS`].
+const MYSQL_COLUMNS: &str = "SELECT CAST(c.TABLE_NAME AS CHAR), CAST(c.COLUMN_NAME AS CHAR),
+    CAST(c.COLUMN_TYPE AS CHAR), CAST(c.IS_NULLABLE AS CHAR), CAST(c.COLUMN_KEY AS CHAR)
+FROM information_schema.COLUMNS c
+JOIN information_schema.TABLES t ON t.TABLE_SCHEMA = c.TABLE_SCHEMA AND t.TABLE_NAME = c.TABLE_NAME
+WHERE c.TABLE_SCHEMA = DATABASE() AND t.TABLE_TYPE = 'BASE TABLE'
+ORDER BY c.TABLE_NAME, c.ORDINAL_POSITION";
+
+/// Columns of the tables, SQLite's own left out, see [`POSTGRES_COLUMNS`].
+const SQLITE_COLUMNS: &str = "SELECT m.name, p.name, p.type, p.\"notnull\", p.pk
+FROM sqlite_master m JOIN pragma_table_info(m.name) p
+WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite_%'
+ORDER BY m.name, p.cid";
+
+/// A table of the database.
+#[derive(Debug)]
+pub struct Table {
+    pub name: String,
+    pub columns: Vec<Column>,
+}
+
+impl Table {
+    /// The column of a primary key made of one, `None` without a primary
+    /// key or with a composite one.
+    pub fn primary_key(&self) -> Option<&Column> {
+        let mut keys = self.columns.iter().filter(|column| column.primary_key);
+        match (keys.next(), keys.next()) {
+            (Some(key), None) => Some(key),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Column {
+    pub name: String,
+    /// Type as the database names it, e.g. `timestamp with time zone`.
+    pub sql_type: String,
+    pub nullable: bool,
+    pub primary_key: bool,
+}
+
+/// Crate a column type comes from, with the sqlx feature decoding it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeCrate {
+    pub name: &'static str,
+    /// Features of the crate the models need, `serde` to serialize them.
+    pub features: Option<&'static str>,
+    pub sqlx_feature: &'static str,
+}
+
+const CHRONO: TypeCrate = TypeCrate {
+    name: "chrono",
+    features: Some("serde"),
+    sqlx_feature: "chrono",
+};
+const UUID: TypeCrate = TypeCrate {
+    name: "uuid",
+    features: Some("serde"),
+    sqlx_feature: "uuid",
+};
+const SERDE_JSON: TypeCrate = TypeCrate {
+    name: "serde_json",
+    features: None,
+    sqlx_feature: "json",
+};
+const RUST_DECIMAL: TypeCrate = TypeCrate {
+    name: "rust_decimal",
+    features: None,
+    sqlx_feature: "rust_decimal",
+};
+
+/// Rust type of a column, without the `Option` of nullable ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustType {
+    pub name: String,
+    pub source: Option<TypeCrate>,
+}
+
+impl RustType {
+    fn plain(name: &str) -> Self {
+        RustType {
+            name: name.to_string(),
+            source: None,
+        }
+    }
+
+    fn from(source: TypeCrate, name: &str) -> Self {
+        RustType {
+            name: name.to_string(),
+            source: Some(source),
+        }
+    }
+}
+
+/// Dialect of the database `url` points at: `postgres`, `mysql` or `sqlite`.
+pub fn dialect(url: &str) -> Option<&'static str> {
+    match url.split(':').next()? {
+        "postgres" | "postgresql" => Some("postgres"),
+        "mysql" | "mariadb" => Some("mysql"),
+        "sqlite" => Some("sqlite"),
+        _ => None,
+    }
+}
+
+/// Tables of the `dialect` database at `url` in name order, without the
+/// one of sqlx's migrations.
+pub fn read(url: &str, dialect: &str) -> Result<Vec<Table>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start the runtime of the database driver")?;
+    let columns = runtime.block_on(columns(url, dialect)).map_err(|err| {
+        Error::environment(format!(
+            "Failed to read the schema of the database at {}: {}\n👉 Check that --from-db is reachable and its user can read the schema",
+            without_password(url),
+            err
+        ))
+    })?;
+
+    let mut tables: Vec<Table> = Vec::new();
+    for (table, column) in columns {
+        if table == MIGRATIONS_TABLE {
+            continue;
+        }
+        match tables.last_mut() {
+            Some(last) if last.name == table => last.columns.push(column),
+            _ => tables.push(Table {
+                name: table,
+                columns: vec![column],
+            }),
+        }
+    }
+    Ok(tables)
+}
+
+async fn columns(url: &str, dialect: &str) -> sqlx::Result<Vec<(String, Column)>> {
+    let column = |name, sql_type, nullable: bool, primary_key: bool| Column {
+        name,
+        sql_type,
+        nullable: nullable && !primary_key,
+        primary_key,
+    };
+    match dialect {
+        "postgres" => {
+            let mut connection = PgConnection::connect(url).await?;
+            let rows: Vec<(String, String, String, bool, bool)> = sqlx::query_as(POSTGRES_COLUMNS)
+                .fetch_all(&mut connection)
+                .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(table, name, sql_type, nullable, key)| {
+                    (table, column(name, sql_type, nullable, key))
+                })
+                .collect())
+        }
+        "mysql" => {
+            let mut connection = MySqlConnection::connect(url).await?;
+            let rows: Vec<(String, String, String, String, String)> = sqlx::query_as(MYSQL_COLUMNS)
+                .fetch_all(&mut connection)
+                .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(table, name, sql_type, nullable, key)| {
+                    (
+                        table,
+                        column(name, sql_type, nullable == "YES", key == "PRI"),
+                    )
+                })
+                .collect())
+        }
+        _ => {
+            let mut connection = SqliteConnection::connect(url).await?;
+            let rows: Vec<(String, String, String, i64, i64)> = sqlx::query_as(SQLITE_COLUMNS)
+                .fetch_all(&mut connection)
+                .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(table, name, sql_type, not_null, key)| {
+                    (table, column(name, sql_type, not_null == 0, key > 0))
+                })
+                .collect())
+        }
+    }
+}
+
+/// `url` with the password replaced by `***`, for messages.
+pub fn without_password(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let Some((credentials, host)) = rest.split_once('@') else {
+        return url.to_string();
+    };
+    match credentials.split_once(':') {
+        Some((user, _)) => format!("{}://{}:***@{}", scheme, user, host),
+        None => url.to_string(),
+    }
+}
+
+/// Rust type sqlx decodes a `sql_type` column of `dialect` into, `None` for
+/// types the models do not map.
+pub fn rust_type(dialect: &str, sql_type: &str) -> Option<RustType> {
+    match dialect {
+        "postgres" => postgres_type(&sql_type.to_lowercase()),
+        "mysql" => mysql_type(&sql_type.to_lowercase()),
+        _ => sqlite_type(&sql_type.to_uppercase()),
+    }
+}
+
+/// Postgres names a type by its SQL name in `information_schema` and by
+/// its internal one in arrays, whose names start with `_`.
+fn postgres_type(sql_type: &str) -> Option<RustType> {
+    if let Some(element) = sql_type.strip_prefix('_') {
+        let element = postgres_type(element)?;
+        return Some(RustType {
+            name: format!("Vec<{}>", element.name),
+            ..element
+        });
+    }
+    Some(match sql_type {
+        "smallint" | "int2" => RustType::plain("i16"),
+        "integer" | "int4" => RustType::plain("i32"),
+        "bigint" | "int8" => RustType::plain("i64"),
+        "real" | "float4" => RustType::plain("f32"),
+        "double precision" | "float8" => RustType::plain("f64"),
+        "numeric" => RustType::from(RUST_DECIMAL, "rust_decimal::Decimal"),
+        "boolean" | "bool" => RustType::plain("bool"),
+        "text" | "character varying" | "varchar" | "character" | "bpchar" | "name" => {
+            RustType::plain("String")
+        }
+        "bytea" => RustType::plain("Vec<u8>"),
+        "uuid" => RustType::from(UUID, "uuid::Uuid"),
+        "json" | "jsonb" => RustType::from(SERDE_JSON, "serde_json::Value"),
+        "timestamp with time zone" | "timestamptz" => {
+            RustType::from(CHRONO, "chrono::DateTime<chrono::Utc>")
+        }
+        "timestamp without time zone" | "timestamp" => {
+            RustType::from(CHRONO, "chrono::NaiveDateTime")
+        }
+        "date" => RustType::from(CHRONO, "chrono::NaiveDate"),
+        "time without time zone" | "time" => RustType::from(CHRONO, "chrono::NaiveTime"),
+        _ => return None,
+    })
+}
+
+/// MySQL gives the full column type, e.g. `int unsigned` or `varchar(255)`,
+/// where `tinyint(1)` is how it stores booleans.
+fn mysql_type(sql_type: &str) -> Option<RustType> {
+    if sql_type == "tinyint(1)" {
+        return Some(RustType::plain("bool"));
+    }
+    let base = sql_type.split(['(', ' ']).next().unwrap_or_default();
+    let unsigned = sql_type.contains("unsigned");
+    let integer = |signed: &str, unsigned_type: &str| {
+        RustType::plain(if unsigned { unsigned_type } else { signed })
+    };
+    Some(match base {
+        "bool" | "boolean" => RustType::plain("bool"),
+        "tinyint" => integer("i8", "u8"),
+        "smallint" => integer("i16", "u16"),
+        "mediumint" | "int" | "integer" => integer("i32", "u32"),
+        "bigint" => integer("i64", "u64"),
+        "float" => RustType::plain("f32"),
+        "double" | "real" => RustType::plain("f64"),
+        "decimal" | "numeric" => RustType::from(RUST_DECIMAL, "rust_decimal::Decimal"),
+        "char" | "varchar" | "tinytext" | "text" | "mediumtext" | "longtext" | "enum" => {
+            RustType::plain("String")
+        }
+        "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => {
+            RustType::plain("Vec<u8>")
+        }
+        "json" => RustType::from(SERDE_JSON, "serde_json::Value"),
+        "datetime" => RustType::from(CHRONO, "chrono::NaiveDateTime"),
+        "timestamp" => RustType::from(CHRONO, "chrono::DateTime<chrono::Utc>"),
+        "date" => RustType::from(CHRONO, "chrono::NaiveDate"),
+        "time" => RustType::from(CHRONO, "chrono::NaiveTime"),
+        _ => return None,
+    })
+}
+
+/// SQLite takes any declared type, the names sqlx decodes are checked
+/// first and the rest go by SQLite's affinity rules.
+fn sqlite_type(sql_type: &str) -> Option<RustType> {
+    Some(match sql_type {
+        "BOOLEAN" | "BOOL" => RustType::plain("bool"),
+        "DATETIME" | "TIMESTAMP" => RustType::from(CHRONO, "chrono::NaiveDateTime"),
+        "DATE" => RustType::from(CHRONO, "chrono::NaiveDate"),
+        "TIME" => RustType::from(CHRONO, "chrono::NaiveTime"),
+        _ if sql_type.contains("INT") => RustType::plain("i64"),
+        _ if ["CHAR", "CLOB", "TEXT"]
+            .iter()
+            .any(|name| sql_type.contains(name)) =>
+        {
+            RustType::plain("String")
+        }
+        _ if sql_type.contains("BLOB") => RustType::plain("Vec<u8>"),
+        _ if ["REAL", "FLOA", "DOUB"]
+            .iter()
+            .any(|name| sql_type.contains(name)) =>
+        {
+            RustType::plain("f64")
+        }
+        _ => return None,
+    })
+}
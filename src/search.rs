@@ -0,0 +1,35 @@
+use std::path::{Path, PathBuf};
+
+use crate::{error::Result, templates};
+
+/// Name of the built-in `--with` option.
+pub const WITH: &str = "search";
+
+/// Module of the Meilisearch client and handlers, declared in main.rs.
+pub const MODULES: [&str; 1] = ["search"];
+
+/// docker-compose.yml starting Meilisearch, owned by its template.
+pub const COMPOSE: (&str, &str) = ("docker-compose.yml", "project/docker-compose.yml");
+
+/// `src/search/mod.rs`: the client, the index bootstrap main.rs awaits on
+/// startup and the example handlers indexing and searching articles.
+pub fn file(context: &templates::TemplateContext) -> Result<(PathBuf, String)> {
+    Ok((
+        Path::new("src").join(MODULES[0]).join("mod.rs"),
+        templates::render("search/mod.rs", context)?,
+    ))
+}
+
+/// Routes of the handlers indexing and searching the example articles.
+pub fn routes(framework: &str) -> [&'static str; 2] {
+    match framework {
+        "axum" => [
+            r#".route("/articles", axum::routing::post(crate::search::index))"#,
+            r#".route("/search", axum::routing::get(crate::search::search))"#,
+        ],
+        _ => [
+            r#".route("/articles", actix_web::web::post().to(crate::search::index))"#,
+            r#".route("/search", actix_web::web::get().to(crate::search::search))"#,
+        ],
+    }
+}
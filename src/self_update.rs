@@ -0,0 +1,185 @@
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{
+    env::{self, consts},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    error::{Error, Result},
+    network,
+    output::say,
+};
+
+/// Largest binary `self-update` downloads.
+const MAX_DOWNLOAD: u64 = 200 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// GitHub API URL of the latest release of this project's repository,
+/// unless `FORGEIT_RELEASE_URL` points elsewhere, e.g. at a mirror serving
+/// the same JSON.
+fn release_url() -> String {
+    env::var("FORGEIT_RELEASE_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| {
+            format!(
+                "{}/releases/latest",
+                env!("CARGO_PKG_REPOSITORY")
+                    .replace("https://github.com/", "https://api.github.com/repos/")
+            )
+        })
+}
+
+/// Name of the release binary for this platform, e.g.
+/// `forgeit-x86_64-linux` or `forgeit-x86_64-windows.exe`.
+fn asset_name() -> String {
+    format!(
+        "forgeit-{}-{}{}",
+        consts::ARCH,
+        consts::OS,
+        consts::EXE_SUFFIX
+    )
+}
+
+/// `major.minor.patch` of a version or a `v`-prefixed tag.
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    // Pre-release and build suffixes sort with their release
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|part| part.parse().ok());
+    Some((
+        parts.next()??,
+        parts.next()??,
+        parts.next().unwrap_or(Some(0))?,
+    ))
+}
+
+fn get(url: &str) -> Result<ureq::http::Response<ureq::Body>> {
+    tracing::debug!("fetching {}", url);
+    ureq::get(url)
+        .header("User-Agent", concat!("forgeit/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .map_err(|err| Error::environment(format!("Failed to fetch {}: {}", url, err)))
+}
+
+fn latest_release() -> Result<Release> {
+    let url = release_url();
+    let body = get(&url)?
+        .body_mut()
+        .read_to_string()
+        .map_err(|err| Error::environment(format!("Failed to read {}: {}", url, err)))?;
+    serde_json::from_str(&body).map_err(|err| {
+        Error::environment(format!("Failed to parse the release at {}: {}", url, err))
+    })
+}
+
+fn download(asset: &Asset) -> Result<Vec<u8>> {
+    say!("Downloading {}", asset.browser_download_url);
+    get(&asset.browser_download_url)?
+        .body_mut()
+        .with_config()
+        .limit(MAX_DOWNLOAD)
+        .read_to_vec()
+        .map_err(|err| Error::environment(format!("Failed to download {}: {}", asset.name, err)))
+}
+
+/// Replaces the running executable with `binary`. The new file is written
+/// next to it first so a failed write never leaves a broken forgeit behind.
+fn replace_executable(exe: &Path, binary: &[u8]) -> std::io::Result<()> {
+    let staged = exe.with_file_name(format!(".forgeit-update{}", consts::EXE_SUFFIX));
+    fs::write(&staged, binary)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+    // Windows cannot overwrite a running executable, but it can rename it
+    #[cfg(windows)]
+    fs::rename(exe, exe.with_extension("old"))?;
+    fs::rename(&staged, exe)
+}
+
+pub fn self_update(check: bool) -> Result<()> {
+    network::require("self-update")?;
+    let current = env!("CARGO_PKG_VERSION");
+    let release = latest_release()?;
+    let Some(latest) = parse_version(&release.tag_name) else {
+        return Err(Error::environment(format!(
+            "The latest release is tagged '{}', which is not a version",
+            release.tag_name
+        )));
+    };
+    let latest_name = release.tag_name.trim_start_matches('v');
+    if parse_version(current).is_some_and(|current| current >= latest) {
+        say!("✅ forgeit {} is the latest release", current);
+        return Ok(());
+    }
+    if check {
+        say!("forgeit {} is available, this is {}", latest_name, current);
+        say!("👉 forgeit self-update");
+        return Ok(());
+    }
+
+    let name = asset_name();
+    let checksum_name = format!("{}.sha256", name);
+    let find = |name: &str| release.assets.iter().find(|asset| asset.name == name);
+    let Some(binary_asset) = find(&name) else {
+        return Err(Error::environment(format!(
+            "Release {} has no {} binary\n👉 Install it with cargo install forgeit",
+            latest_name, name
+        )));
+    };
+    let Some(checksum_asset) = find(&checksum_name) else {
+        return Err(Error::environment(format!(
+            "Release {} has no {}, refusing to install a binary that cannot be verified",
+            latest_name, checksum_name
+        )));
+    };
+
+    let binary = download(binary_asset)?;
+    let checksums = String::from_utf8(download(checksum_asset)?).unwrap_or_default();
+    // `sha256sum` output: the hash, then the file name
+    let expected = checksums
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let actual: String = Sha256::digest(&binary)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    if actual != expected {
+        return Err(Error::environment(format!(
+            "The checksum of {} does not match {}, nothing was replaced",
+            name, checksum_name
+        )));
+    }
+    say!("Checksum verified");
+
+    let exe = env::current_exe().map_err(|err| {
+        Error::environment(format!("Failed to find the running executable: {}", err))
+    })?;
+    replace_executable(&exe, &binary).map_err(|err| {
+        Error::environment(format!(
+            "Failed to replace {}: {}\n👉 Run self-update as a user allowed to write it",
+            exe.display(),
+            err
+        ))
+    })?;
+    say!("✅ Updated forgeit {} to {}", current, latest_name);
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use std::{
+    env, fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use crate::{
+    error::{Context, Error, Result},
+    output,
+};
+
+/// How git signs commits, its `gpg.format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    OpenPgp,
+    X509,
+    Ssh,
+}
+
+/// The key commits are signed with, read from git config like `git commit`
+/// does: `commit.gpgsign` turns it on, `gpg.format` picks gpg, gpgsm or
+/// ssh-keygen and `user.signingkey` the key.
+#[derive(Debug)]
+pub struct Signer {
+    format: Format,
+    program: String,
+    key: Option<String>,
+}
+
+impl Signer {
+    /// The signer of `config`, `None` unless `commit.gpgsign` is set.
+    pub fn from_config(config: &git2::Config) -> Result<Option<Signer>> {
+        if !config.get_bool("commit.gpgsign").unwrap_or(false) {
+            return Ok(None);
+        }
+        let text = |key: &str| {
+            config
+                .get_string(key)
+                .ok()
+                .filter(|value| !value.is_empty())
+        };
+        let format = match text("gpg.format").as_deref() {
+            None | Some("openpgp") => Format::OpenPgp,
+            Some("x509") => Format::X509,
+            Some("ssh") => Format::Ssh,
+            Some(other) => {
+                return Err(Error::user(format!(
+                    "Unknown gpg.format '{}' in git config\n👉 Pass --no-sign to commit without a signature",
+                    other
+                )));
+            }
+        };
+        let program = match format {
+            Format::OpenPgp => text("gpg.openpgp.program").or_else(|| text("gpg.program")),
+            Format::X509 => text("gpg.x509.program"),
+            Format::Ssh => text("gpg.ssh.program"),
+        }
+        .unwrap_or_else(|| {
+            match format {
+                Format::OpenPgp => "gpg",
+                Format::X509 => "gpgsm",
+                Format::Ssh => "ssh-keygen",
+            }
+            .to_string()
+        });
+        let key = text("user.signingkey");
+        if format == Format::Ssh && key.is_none() {
+            return Err(Error::user(
+                "commit.gpgsign is set with gpg.format ssh, but user.signingkey is not\n👉 Set user.signingkey, or pass --no-sign",
+            ));
+        }
+        Ok(Some(Signer {
+            format,
+            program,
+            key,
+        }))
+    }
+
+    /// Signs the commit `buffer` of `committer`, returning the armored
+    /// signature for its `gpgsig` header.
+    pub fn sign(&self, buffer: &str, committer: &str) -> Result<String> {
+        let mut cmd = Command::new(&self.program);
+        let mut literal_key = None;
+        match self.format {
+            Format::OpenPgp | Format::X509 => {
+                // Without a key, gpg picks one matching the committer
+                let key = self.key.as_deref().unwrap_or(committer);
+                cmd.args(["--status-fd=2", "-bsau", key]);
+            }
+            Format::Ssh => {
+                let key = self.key.as_deref().unwrap_or_default();
+                cmd.args(["-Y", "sign", "-n", "git", "-f"]);
+                // A public key itself rather than a path signs through the agent
+                let literal = key.strip_prefix("key::").unwrap_or(key);
+                if literal.starts_with("ssh-") || literal.starts_with("ecdsa-") {
+                    let path = env::temp_dir()
+                        .join(format!(".forgeit-signing-key-{}.pub", std::process::id()));
+                    fs::write(&path, literal).context("Failed to write the signing key")?;
+                    cmd.arg(&path).arg("-U");
+                    literal_key = Some(path);
+                } else {
+                    cmd.arg(expand_home(key));
+                }
+            }
+        }
+        tracing::debug!("running {}", output::command_line(&cmd));
+        // gpg may ask for the passphrase on the terminal
+        let result = output::suspend(|| run(cmd, buffer));
+        if let Some(path) = literal_key {
+            fs::remove_file(path).ok();
+        }
+        let signature = result.map_err(|err| {
+            Error::environment(format!(
+                "Failed to sign the initial commit with {}: {}\n👉 Check user.signingkey in git config, or pass --no-sign",
+                self.program, err
+            ))
+        })?;
+        Ok(signature)
+    }
+}
+
+/// Runs the signing program with `buffer` on stdin, returning its stdout.
+fn run(mut cmd: Command, buffer: &str) -> std::result::Result<String, String> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| err.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(buffer.as_bytes())
+            .map_err(|err| err.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|err| err.to_string())?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() || stdout.trim().is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // gpg reports its status lines next to the error
+        let reason = stderr
+            .lines()
+            .filter(|line| !line.starts_with("[GNUPG:]"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        return Err(if reason.trim().is_empty() {
+            output.status.to_string()
+        } else {
+            reason.trim().to_string()
+        });
+    }
+    Ok(stdout)
+}
+
+/// `~/` at the start of a key path, which git expands as well.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
@@ -0,0 +1,231 @@
+use std::{fs, path::Path};
+use toml_edit::{DocumentMut, value};
+
+use crate::{
+    error::{Context, Error, Result},
+    generate, license,
+    manifest::{self, Manifest},
+    musl,
+    output::{self, say},
+    scaffold, templates, workspace,
+};
+
+/// A template-owned file whose template changed since it was generated.
+pub struct Update {
+    pub path: String,
+    /// The file as it was generated, the common ancestor of the merge.
+    pub base: String,
+    /// The file as the current templates generate it.
+    pub current: String,
+}
+
+fn is_workspace(project_dir: &Path) -> bool {
+    fs::read_to_string(project_dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|manifest| manifest.parse::<DocumentMut>().ok())
+        .is_some_and(|manifest| manifest.contains_table("workspace"))
+}
+
+/// Renders the template-owned file `path` with the current templates, `None`
+/// for templates this version of forgeit does not know.
+fn render(
+    project_dir: &Path,
+    manifest: &Manifest,
+    path: &str,
+    template: &str,
+) -> Result<Option<String>> {
+    let name = manifest.name.clone().unwrap_or_default();
+    let author = license::holder(None, &name);
+    let arch = manifest.arch.as_deref().unwrap_or("mvc");
+    let target = manifest
+        .target
+        .as_deref()
+        .unwrap_or(scaffold::Target::Server.as_str());
+    let (host, port) = if is_workspace(project_dir) {
+        ("0.0.0.0", workspace::FIRST_PORT)
+    } else {
+        ("127.0.0.1", manifest.port.unwrap_or(scaffold::DEFAULT_PORT))
+    };
+
+    let content = match template {
+        ".gitignore"
+        | "Dockerfile"
+        | "project/Dockerfile"
+        | "lambda/template.yaml"
+        | "lambda/CargoLambda.toml"
+        | "shuttle/Shuttle.toml"
+        | "fly/fly.toml"
+        | "fly/ci.yml"
+        | "systemd/service"
+        | "systemd/install.sh"
+        | "workers/wrangler.toml"
+        | "project/docker-compose.yml" => {
+            let modules: Vec<String> = templates::get_modules(arch)
+                .iter()
+                .map(|module| module.to_string())
+                .collect();
+            templates::render(
+                template,
+                &templates::TemplateContext {
+                    name: &name,
+                    author: &author,
+                    framework: manifest.framework.as_deref().unwrap_or_default(),
+                    framework_version: templates::template_version(
+                        manifest.framework.as_deref().unwrap_or_default(),
+                        manifest.framework_version.as_deref(),
+                    )?,
+                    arch,
+                    modules: &modules,
+                    features: &manifest.features,
+                    host,
+                    port,
+                    target,
+                    deploy: manifest.deploy.as_deref(),
+                    frontend: manifest.frontend.as_deref(),
+                    database: manifest.database.as_deref(),
+                    with: &manifest.with,
+                },
+            )?
+        }
+        "justfile" | "Makefile" => {
+            let workspace = is_workspace(project_dir);
+            templates::render_tasks(
+                template,
+                &templates::Tasks {
+                    name: &name,
+                    author: &author,
+                    workspace,
+                    lib: arch == templates::LIB_ARCH,
+                    database: manifest.database.is_some(),
+                    docker: workspace || manifest.templates.contains_key("Dockerfile"),
+                    compose: workspace,
+                    target,
+                    deploy: manifest.deploy.as_deref(),
+                    musl: manifest.with.iter().any(|option| option == musl::WITH),
+                },
+            )?
+        }
+        // The services are the workspace's, not part of the template
+        "ci" => {
+            let services = fs::read_to_string(project_dir.join(path))
+                .ok()
+                .and_then(|workflow| workspace::ci_services(&workflow))
+                .unwrap_or_default();
+            workspace::get_ci_workflow(&services)
+        }
+        "middleware" => {
+            let framework = generate::web_framework(project_dir)?;
+            let file_name = Path::new(path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            generate::get_middleware_content(framework, &file_name)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(content))
+}
+
+/// Template-owned files of the project whose templates changed.
+pub fn pending_updates(project_dir: &Path, manifest: &Manifest) -> Result<Vec<Update>> {
+    let mut updates = Vec::new();
+    for (path, template) in &manifest.templates {
+        let Some(current) = render(project_dir, manifest, path, template)? else {
+            continue;
+        };
+        // Files generated before base copies existed merge against nothing
+        let base =
+            fs::read_to_string(project_dir.join(manifest::base_path(path))).unwrap_or_default();
+        if current != base {
+            updates.push(Update {
+                path: path.clone(),
+                base,
+                current,
+            });
+        }
+    }
+    Ok(updates)
+}
+
+pub fn upgrade(dry_run: bool) -> Result<()> {
+    let project_dir = Path::new(".");
+    let Some(manifest) = Manifest::read(project_dir)? else {
+        return Err(Error::user(format!(
+            "No {} in the current directory\n👉 Run upgrade inside a project scaffolded by forgeit",
+            manifest::FILE
+        )));
+    };
+
+    let updates = pending_updates(project_dir, &manifest)?;
+    if updates.is_empty() {
+        say!("✅ The template-owned files are up to date");
+        return Ok(());
+    }
+    say!(
+        "Upgrading templates from forgeit {} to {}",
+        manifest.version,
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let mut upgraded = Vec::new();
+    let mut conflicts = Vec::new();
+    for update in &updates {
+        let file = project_dir.join(&update.path);
+        let Ok(ours) = fs::read_to_string(&file) else {
+            say!("\nSkipping {}, the file was removed", update.path);
+            continue;
+        };
+
+        say!("\n--- {} (template changes) ---", update.path);
+        output::text(
+            &diffy::DiffOptions::new()
+                .set_original_filename(format!("{} (generated)", update.path))
+                .set_modified_filename(format!("{} (current template)", update.path))
+                .create_patch(&update.base, &update.current)
+                .to_string(),
+        );
+
+        // Files nobody edited take the new version as is
+        let merged = if ours == update.base {
+            Ok(update.current.clone())
+        } else {
+            diffy::merge(&update.base, &ours, &update.current)
+        };
+        let content = match merged {
+            Ok(content) => {
+                say!("{}: merged cleanly", update.path);
+                content
+            }
+            Err(content) => {
+                say!("{}: conflicts with local changes", update.path);
+                conflicts.push(update.path.as_str());
+                content
+            }
+        };
+
+        if !dry_run {
+            fs::write(&file, content)
+                .with_context(|| format!("Failed to update {}", update.path))?;
+            output::file(&file);
+            manifest::write_base(project_dir, &update.path, &update.current)?;
+        }
+        upgraded.push(update.path.as_str());
+    }
+
+    if dry_run {
+        say!("\nDry run, nothing was written");
+        return Ok(());
+    }
+    manifest::update(project_dir, |document| {
+        document["version"] = value(env!("CARGO_PKG_VERSION"));
+    })?;
+
+    if !conflicts.is_empty() {
+        return Err(Error::user(format!(
+            "Upgraded with conflicts in:\n  - {}\n👉 Resolve the <<<<<<< markers, then commit",
+            conflicts.join("\n  - ")
+        )));
+    }
+    say!("\n✅ Upgraded {}", upgraded.join(", "));
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::path::{Path, PathBuf};
+
+use crate::{error::Result, templates};
+
+/// Name of the built-in `--with` option.
+pub const WITH: &str = "uploads";
+
+/// Module of the upload and download handlers, declared in main.rs.
+pub const MODULES: [&str; 1] = ["uploads"];
+
+/// Feature of the generated project storing the uploads in S3.
+pub const S3_FEATURE: &str = "s3";
+
+/// `src/uploads/mod.rs`: the limits, the disk and S3 storage, and the
+/// handlers main.rs awaits the bootstrap of.
+pub fn file(context: &templates::TemplateContext) -> Result<(PathBuf, String)> {
+    Ok((
+        Path::new("src").join(MODULES[0]).join("mod.rs"),
+        templates::render("uploads/mod.rs", context)?,
+    ))
+}
+
+/// Paths of the upload and download handlers with their method routers.
+/// The upload route of axum comes with the body limit it raises.
+pub fn routes(framework: &str) -> [(&'static str, &'static str); 2] {
+    match framework {
+        "axum" => [
+            ("/uploads", "crate::uploads::upload_route()"),
+            (
+                "/uploads/{id}",
+                "axum::routing::get(crate::uploads::download)",
+            ),
+        ],
+        _ => [
+            (
+                "/uploads",
+                "actix_web::web::post().to(crate::uploads::upload)",
+            ),
+            (
+                "/uploads/{id}",
+                "actix_web::web::get().to(crate::uploads::download)",
+            ),
+        ],
+    }
+}
@@ -0,0 +1,136 @@
+use dialoguer::{
+    Confirm, Input, MultiSelect, Select,
+    theme::{ColorfulTheme, SimpleTheme, Theme},
+};
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
+
+use crate::{
+    error::{Error, Result},
+    is_crate_name, output, registry, templates,
+};
+
+/// Toggles of the wizard and the `scaffold` arguments they add.
+const TOGGLES: [(&str, &[&str]); 3] = [
+    (
+        "Authentication (jsonwebtoken, argon2)",
+        &["--deps", "jsonwebtoken", "--deps", "argon2"],
+    ),
+    ("Docker (Dockerfile)", &["--docker"]),
+    (
+        "Observability (tracing, tracing-subscriber)",
+        &["--deps", "tracing", "--deps", "tracing-subscriber"],
+    ),
+];
+
+const DATABASES: [&str; 4] = ["none", "postgres", "mysql", "sqlite"];
+
+/// Whether to ask for the options: `scaffold` was run without any arguments
+/// in a terminal. Scripts get clap's usual error about the missing flags.
+pub fn requested() -> bool {
+    let args: Vec<_> = env::args_os().skip(1).collect();
+    args == ["scaffold"] && io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+fn prompt_error(err: dialoguer::Error) -> Error {
+    Error::environment(format!("Failed to read the answer: {}", err))
+}
+
+/// Asks for the scaffold options and returns the equivalent command line,
+/// `None` when the summary is not confirmed.
+pub fn ask() -> Result<Option<Vec<String>>> {
+    let theme: Box<dyn Theme> = if output::no_color_env() {
+        Box::new(SimpleTheme)
+    } else {
+        Box::new(ColorfulTheme::default())
+    };
+    let theme = theme.as_ref();
+    println!("Scaffold a new project, answer a few questions or press Ctrl-C to stop\n");
+
+    let name: String = Input::with_theme(theme)
+        .with_prompt("Project name")
+        .validate_with(|name: &String| {
+            if is_crate_name(name) {
+                Ok(())
+            } else {
+                Err("use letters, digits, '-' and '_', starting with a letter")
+            }
+        })
+        .interact_text()
+        .map_err(prompt_error)?;
+
+    let frameworks: Vec<String> = templates::FRAMEWORKS
+        .iter()
+        .map(|framework| framework.name.to_string())
+        .chain(templates::user_frameworks())
+        .chain(registry::installed_templates())
+        .collect();
+    let framework = &frameworks[Select::with_theme(theme)
+        .with_prompt("Framework")
+        .items(&frameworks)
+        .default(0)
+        .interact()
+        .map_err(prompt_error)?];
+
+    let mut args = vec![
+        "forgeit".to_string(),
+        "scaffold".to_string(),
+        "--name".to_string(),
+        name,
+        "--framework".to_string(),
+        framework.clone(),
+    ];
+
+    // Installed templates bring their own layout
+    let installed = registry::installed_template(framework).is_some();
+    if !installed {
+        let arch = Select::with_theme(theme)
+            .with_prompt("Architecture")
+            .items(templates::ARCHITECTURES)
+            .default(0)
+            .interact()
+            .map_err(prompt_error)?;
+        args.extend([
+            "--arch".to_string(),
+            templates::ARCHITECTURES[arch].to_string(),
+        ]);
+    }
+
+    let database = Select::with_theme(theme)
+        .with_prompt("Database")
+        .items(DATABASES)
+        .default(0)
+        .interact()
+        .map_err(prompt_error)?;
+    if database > 0 {
+        args.extend(["--database".to_string(), DATABASES[database].to_string()]);
+    }
+
+    let toggles: Vec<&(&str, &[&str])> = TOGGLES
+        .iter()
+        .filter(|(_, toggle_args)| !(installed && toggle_args.contains(&"--docker")))
+        .collect();
+    let labels: Vec<&str> = toggles.iter().map(|(label, _)| *label).collect();
+    let selected = MultiSelect::with_theme(theme)
+        .with_prompt("Options (space to toggle, enter to continue)")
+        .items(&labels)
+        .interact()
+        .map_err(prompt_error)?;
+    for index in selected {
+        args.extend(toggles[index].1.iter().map(|arg| arg.to_string()));
+    }
+
+    println!("\nThis runs:\n  {}\n", args.join(" "));
+    let confirmed = Confirm::with_theme(theme)
+        .with_prompt("Scaffold the project?")
+        .default(true)
+        .interact()
+        .map_err(prompt_error)?;
+    if !confirmed {
+        println!("Nothing was scaffolded");
+        return Ok(None);
+    }
+    Ok(Some(args))
+}
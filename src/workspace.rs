@@ -0,0 +1,655 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use toml_edit::{DocumentMut, Item, Value};
+
+use crate::{
+    error::{Context, Error, Result},
+    license,
+    manifest::{self, Manifest},
+    output::{self, say},
+    scaffold::{Rollback, Target, create_gitignore, create_module_dir},
+    templates,
+};
+
+/// Port of the first service, each following service gets the next one.
+pub const FIRST_PORT: u16 = 3000;
+
+/// CI workflow testing every service of the workspace.
+pub const CI_WORKFLOW: &str = ".github/workflows/ci.yml";
+
+/// Version requirement written to `[workspace.dependencies]` for a framework.
+fn get_framework_version(framework: &str) -> &'static str {
+    match framework {
+        "axum" => "0.8",
+        _ => "4",
+    }
+}
+
+/// Versions written to `[workspace.dependencies]`, resolved by the first build.
+fn get_workspace_dependencies(framework: &str) -> String {
+    format!(
+        r#"{framework} = "{}"
+serde = {{ version = "1", features = ["derive"] }}
+tokio = {{ version = "1", features = ["full"] }}
+"#,
+        get_framework_version(framework)
+    )
+}
+
+fn get_workspace_manifest(members: &[String], framework: &str) -> String {
+    let members: Vec<String> = members.iter().map(|m| format!("    \"{}\",", m)).collect();
+
+    format!(
+        r#"[workspace]
+resolver = "3"
+members = [
+{}
+]
+
+[workspace.package]
+version = "0.1.0"
+edition = "2024"
+
+[workspace.dependencies]
+{}"#,
+        members.join("\n"),
+        get_workspace_dependencies(framework)
+    )
+}
+
+fn get_service_manifest(service: &str, framework: &str) -> String {
+    format!(
+        r#"[package]
+name = "{service}"
+version.workspace = true
+edition.workspace = true
+
+[dependencies]
+common = {{ path = "../common" }}
+{framework}.workspace = true
+serde.workspace = true
+tokio.workspace = true
+"#
+    )
+}
+
+const COMMON_MANIFEST: &str = r#"[package]
+name = "common"
+version.workspace = true
+edition.workspace = true
+
+[dependencies]
+serde.workspace = true
+"#;
+
+const COMMON_LIB: &str = r#"//! Code shared by every service in the workspace.
+
+use serde::{Deserialize, Serialize};
+
+/// Response body returned by every service's health check.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Health {
+    pub service: String,
+    pub status: String,
+}
+
+impl Health {
+    pub fn ok(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+            status: "ok".to_string(),
+        }
+    }
+}
+"#;
+
+/// Multi-stage build shared by all services, selected with the `SERVICE` build arg.
+fn get_compose_service(service: &str, port: u16) -> String {
+    format!(
+        r#"  {service}:
+    build:
+      context: .
+      args:
+        SERVICE: {service}
+    ports:
+      - "{port}:{port}"
+"#
+    )
+}
+
+pub fn get_ci_workflow(services: &[String]) -> String {
+    format!(
+        r#"name: CI
+
+on:
+  push:
+    branches: [main]
+  pull_request:
+
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    strategy:
+      matrix:
+        service: [{}]
+    steps:
+      - uses: actions/checkout@v4
+      - uses: dtolnay/rust-toolchain@stable
+      - run: cargo test -p ${{{{ matrix.service }}}}
+"#,
+        services.join(", ")
+    )
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    output::file(path);
+    Ok(())
+}
+
+fn create_service_crate(workspace: &str, service: &str, framework: &str, port: u16) -> Result<()> {
+    let crate_dir = Path::new(workspace).join(service);
+    fs::create_dir_all(crate_dir.join("src"))
+        .with_context(|| format!("Failed to create {} crate", service))?;
+
+    write_file(
+        &crate_dir.join("Cargo.toml"),
+        &get_service_manifest(service, framework),
+    )?;
+
+    // Services listen on all interfaces so they are reachable from docker-compose
+    let arch = "mvc";
+    let modules: Vec<String> = templates::get_modules(arch)
+        .iter()
+        .map(|module| module.to_string())
+        .collect();
+    let framework_version = templates::template_version(framework, None)?;
+    let author = license::holder(None, service);
+    let main_rs = templates::get_main_rs(&templates::TemplateContext {
+        name: service,
+        author: &author,
+        framework,
+        framework_version,
+        arch,
+        modules: &modules,
+        features: &[],
+        host: "0.0.0.0",
+        port,
+        target: Target::Server.as_str(),
+        deploy: None,
+        frontend: None,
+        database: None,
+        with: &[],
+    })?;
+    write_file(&crate_dir.join("src").join("main.rs"), &main_rs)?;
+    write_file(
+        &crate_dir.join(manifest::FILE),
+        &Manifest::new(service, Some(framework), arch, &[]).to_toml(),
+    )?;
+
+    let crate_path = crate_dir.to_string_lossy();
+    for module in &modules {
+        create_module_dir(
+            &crate_path,
+            module,
+            templates::get_module_content(framework, arch, module, None),
+        )?;
+    }
+    Ok(())
+}
+
+pub fn scaffold_workspace(
+    name: &str,
+    services: &[String],
+    framework: &str,
+    task_runner: templates::TaskRunner,
+) -> Result<()> {
+    if Path::new(name).exists() {
+        return Err(Error::user(format!("'{}' already exists", name)));
+    }
+
+    say!("Creating new Cargo workspace: {}", name);
+    let rollback = Rollback::new_project(Path::new(name));
+    fs::create_dir_all(name).with_context(|| format!("Failed to create {} directory", name))?;
+
+    let mut members = services.to_vec();
+    members.push("common".to_string());
+    write_file(
+        &Path::new(name).join("Cargo.toml"),
+        &get_workspace_manifest(&members, framework),
+    )?;
+
+    let mut compose = String::from("services:\n");
+    for (port, service) in (FIRST_PORT..).zip(services) {
+        say!(
+            "Adding {} service '{}' on port {}",
+            framework,
+            service,
+            port
+        );
+        create_service_crate(name, service, framework, port)?;
+        compose.push_str(&get_compose_service(service, port));
+    }
+
+    say!("Adding shared 'common' crate");
+    let common_dir = Path::new(name).join("common");
+    fs::create_dir_all(common_dir.join("src")).context("Failed to create common crate")?;
+    write_file(&common_dir.join("Cargo.toml"), COMMON_MANIFEST)?;
+    write_file(&common_dir.join("src").join("lib.rs"), COMMON_LIB)?;
+
+    // Workspace-wide files are rendered with the settings of the first service
+    let author = license::holder(None, name);
+    let context = templates::TemplateContext {
+        name,
+        author: &author,
+        framework,
+        framework_version: templates::template_version(framework, None)?,
+        arch: "mvc",
+        modules: &[],
+        features: &[],
+        host: "0.0.0.0",
+        port: FIRST_PORT,
+        target: Target::Server.as_str(),
+        deploy: None,
+        frontend: None,
+        database: None,
+        with: &[],
+    };
+    say!("Creating Dockerfile, docker-compose.yml and CI workflow");
+    let dockerfile = templates::render("Dockerfile", &context)?;
+    write_file(&Path::new(name).join("Dockerfile"), &dockerfile)?;
+    write_file(&Path::new(name).join("docker-compose.yml"), &compose)?;
+
+    let workflows_dir = Path::new(name).join(".github").join("workflows");
+    fs::create_dir_all(&workflows_dir).context("Failed to create .github/workflows directory")?;
+    let workflow = get_ci_workflow(services);
+    write_file(&workflows_dir.join("ci.yml"), &workflow)?;
+
+    let gitignore = templates::render(".gitignore", &context)?;
+    create_gitignore(name, &gitignore)?;
+
+    let ports: Vec<(String, u16)> = services.iter().cloned().zip(FIRST_PORT..).collect();
+    let readme = templates::render_readme(
+        &context,
+        &templates::Readme {
+            lib: false,
+            docker: true,
+            task_runner,
+            release: false,
+            git_hooks: false,
+            services: &ports,
+        },
+    )?;
+    write_file(&Path::new(name).join("README.md"), &readme)?;
+
+    // `upgrade` keeps the workspace-wide files current
+    let workspace_dir = Path::new(name);
+    let mut manifest = Manifest::new(name, Some(framework), "mvc", &[])
+        .own("Dockerfile", "Dockerfile")
+        .own(CI_WORKFLOW, "ci")
+        .own(".gitignore", ".gitignore");
+    let tasks = match task_runner.file_name() {
+        Some(file) => {
+            let content = templates::render_tasks(
+                file,
+                &templates::Tasks {
+                    name,
+                    author: &author,
+                    workspace: true,
+                    lib: false,
+                    database: false,
+                    docker: true,
+                    compose: true,
+                    target: Target::Server.as_str(),
+                    deploy: None,
+                    musl: false,
+                },
+            )?;
+            write_file(&workspace_dir.join(file), &content)?;
+            manifest = manifest.own(file, file);
+            Some((file, content))
+        }
+        None => None,
+    };
+    write_file(&workspace_dir.join(manifest::FILE), &manifest.to_toml())?;
+    manifest::write_base(workspace_dir, "Dockerfile", &dockerfile)?;
+    manifest::write_base(workspace_dir, CI_WORKFLOW, &workflow)?;
+    manifest::write_base(workspace_dir, ".gitignore", &gitignore)?;
+    if let Some((file, content)) = &tasks {
+        manifest::write_base(workspace_dir, file, content)?;
+    }
+    rollback.commit();
+
+    say!("\n✅ Workspace '{}' scaffolded successfully!", name);
+    say!("👉 cd {} && docker compose up --build", name);
+    Ok(())
+}
+
+/// Next free port after the highest one published in docker-compose.yml.
+fn next_compose_port(compose: &str) -> u16 {
+    compose
+        .lines()
+        .filter_map(|line| {
+            let mapping = line.trim().strip_prefix("- \"")?;
+            mapping.split(':').next()?.parse::<u16>().ok()
+        })
+        .max()
+        .map_or(FIRST_PORT, |port| port + 1)
+}
+
+/// Services in the `service: [...]` matrix of the CI workflow.
+pub fn ci_services(workflow: &str) -> Option<Vec<String>> {
+    let services = workflow.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("service: [")
+            .and_then(|rest| rest.strip_suffix(']'))
+    })?;
+    Some(
+        services
+            .split(',')
+            .map(str::trim)
+            .filter(|service| !service.is_empty())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Adds `service` to the `service: [...]` matrix of the CI workflow.
+fn add_to_ci_matrix(workflow: &str, service: &str) -> Option<String> {
+    let mut updated = false;
+    let lines: Vec<String> = workflow
+        .lines()
+        .map(|line| {
+            let Some(services) = line
+                .trim()
+                .strip_prefix("service: [")
+                .and_then(|rest| rest.strip_suffix(']'))
+            else {
+                return line.to_string();
+            };
+
+            updated = true;
+            let indent = &line[..line.len() - line.trim_start().len()];
+            match services.trim() {
+                "" => format!("{}service: [{}]", indent, service),
+                services => format!("{}service: [{}, {}]", indent, services, service),
+            }
+        })
+        .collect();
+
+    updated.then(|| lines.join("\n") + "\n")
+}
+
+/// Reads the workspace manifest in the current directory.
+fn read_workspace_manifest() -> Result<DocumentMut> {
+    let Ok(manifest) = fs::read_to_string("Cargo.toml") else {
+        return Err(Error::user("No Cargo.toml found in the current directory"));
+    };
+    let document: DocumentMut = manifest
+        .parse()
+        .map_err(|err| Error::user(format!("Failed to parse ./Cargo.toml: {}", err)))?;
+
+    if document
+        .get("workspace")
+        .and_then(|workspace| workspace.get("members"))
+        .and_then(Item::as_array)
+        .is_none()
+    {
+        return Err(Error::user("./Cargo.toml is not a workspace"));
+    }
+    Ok(document)
+}
+
+/// Adds `name` to the workspace members. Returns `false` when a member or
+/// directory of that name already exists.
+pub fn add_workspace_member(document: &mut DocumentMut, name: &str) -> Result<bool> {
+    // `read_workspace_manifest` only returns manifests with a members array
+    let members = document["workspace"]["members"]
+        .as_array_mut()
+        .ok_or_else(|| Error::Internal("workspace members is not an array".to_string()))?;
+
+    if members.iter().any(|member| member.as_str() == Some(name)) || Path::new(name).exists() {
+        return Ok(false);
+    }
+    members.push(name);
+
+    // Keep one member per line when the list is already laid out that way
+    let multiline = members
+        .iter()
+        .next()
+        .and_then(|first| first.decor().prefix()?.as_str())
+        .is_some_and(|prefix| prefix.contains('\n'));
+    if multiline && let Some(added) = members.iter_mut().last() {
+        added.decor_mut().set_prefix("\n    ");
+        members.set_trailing("\n");
+        members.set_trailing_comma(true);
+    }
+    Ok(true)
+}
+
+pub fn add_service(name: &str, framework: Option<&str>) -> Result<()> {
+    let manifest_path = Path::new("Cargo.toml");
+    let mut document = read_workspace_manifest()?;
+
+    if !add_workspace_member(&mut document, name)? {
+        return Err(Error::user(format!("Service '{}' already exists", name)));
+    }
+
+    // Default to the framework the workspace was scaffolded with, or the
+    // one the other services use
+    let recorded = Manifest::read(Path::new("."))?
+        .and_then(|manifest| manifest.framework)
+        .filter(|framework| matches!(framework.as_str(), "axum" | "actix-web"));
+    let workspace_deps = &mut document["workspace"]["dependencies"];
+    let framework = framework.or(recorded.as_deref()).unwrap_or_else(|| {
+        ["axum", "actix-web"]
+            .into_iter()
+            .find(|framework| workspace_deps.get(framework).is_some())
+            .unwrap_or("axum")
+    });
+    if workspace_deps.get(framework).is_none() {
+        workspace_deps[framework] = Item::Value(Value::from(get_framework_version(framework)));
+    }
+
+    let compose_path = Path::new("docker-compose.yml");
+    let workflow_path = Path::new(CI_WORKFLOW);
+    let base_path = manifest::base_path(CI_WORKFLOW);
+    // A failed step leaves no member behind that does not build
+    let rollback = Rollback::existing_dir(
+        Path::new("."),
+        [
+            manifest_path,
+            compose_path,
+            workflow_path,
+            &base_path,
+            Path::new(name),
+        ]
+        .map(PathBuf::from),
+    )?;
+
+    fs::write(manifest_path, document.to_string()).context("Failed to update Cargo.toml")?;
+    output::file(manifest_path);
+
+    let compose = fs::read_to_string(compose_path).unwrap_or_else(|_| "services:\n".to_string());
+    let port = next_compose_port(&compose);
+
+    say!("Adding {} service '{}' on port {}", framework, name, port);
+    create_service_crate(".", name, framework, port)?;
+    write_file(
+        compose_path,
+        &format!("{}{}", compose, get_compose_service(name, port)),
+    )?;
+
+    match fs::read_to_string(workflow_path)
+        .ok()
+        .and_then(|workflow| add_to_ci_matrix(&workflow, name))
+    {
+        Some(workflow) => write_file(workflow_path, &workflow)?,
+        None => say!("No CI service matrix found, skipping {}", CI_WORKFLOW),
+    }
+    // The new service is not a template change `upgrade` should report
+    if let Some(base) = fs::read_to_string(&base_path)
+        .ok()
+        .and_then(|base| add_to_ci_matrix(&base, name))
+    {
+        write_file(&base_path, &base)?;
+    }
+    rollback.commit();
+
+    say!("\n✅ Service '{}' added to the workspace!", name);
+    say!("👉 cargo run -p {}", name);
+    Ok(())
+}
+
+fn get_shared_manifest(name: &str) -> String {
+    format!(
+        r#"[package]
+name = "{name}"
+version.workspace = true
+edition.workspace = true
+
+[dependencies]
+serde.workspace = true
+"#
+    )
+}
+
+const SHARED_LIB: &str = r#"pub mod dto;
+pub mod error;
+
+pub use error::{Error, Result};
+"#;
+
+const SHARED_ERROR: &str = r#"use std::fmt;
+
+/// Error type shared by the services depending on this crate.
+#[derive(Debug)]
+pub enum Error {
+    NotFound(String),
+    Invalid(String),
+    Internal(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(what) => write!(f, "{what} not found"),
+            Error::Invalid(reason) => write!(f, "invalid request: {reason}"),
+            Error::Internal(reason) => write!(f, "internal error: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+"#;
+
+const SHARED_DTO: &str = r#"use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// Envelope for paginated list responses.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: u32,
+    pub total: u64,
+}
+
+/// Body returned for failed requests.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+impl From<&Error> for ErrorResponse {
+    fn from(error: &Error) -> Self {
+        Self {
+            error: error.to_string(),
+        }
+    }
+}
+"#;
+
+/// Adds `{ path = "../<dependency>" }` to the `[dependencies]` of a member crate.
+fn add_path_dependency(member: &str, dependency: &str) -> Result<()> {
+    let manifest_path = Path::new(member).join("Cargo.toml");
+    let Ok(manifest) = fs::read_to_string(&manifest_path) else {
+        output::warn(format!(
+            "Skipping '{}': no {}",
+            member,
+            manifest_path.display()
+        ));
+        return Ok(());
+    };
+    let mut document: DocumentMut = manifest.parse().map_err(|err| {
+        Error::user(format!(
+            "Failed to parse {}: {}",
+            manifest_path.display(),
+            err
+        ))
+    })?;
+
+    let mut path = toml_edit::InlineTable::new();
+    path.insert("path", Value::from(format!("../{}", dependency)));
+    document["dependencies"][dependency] = Item::Value(Value::InlineTable(path));
+
+    write_file(&manifest_path, &document.to_string())
+}
+
+pub fn generate_shared_crate(name: &str, services: Option<Vec<String>>) -> Result<()> {
+    let mut document = read_workspace_manifest()?;
+
+    // Default to every binary member of the workspace
+    let services = services.unwrap_or_else(|| {
+        document["workspace"]["members"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .filter(|member| Path::new(member).join("src").join("main.rs").exists())
+            .map(str::to_string)
+            .collect()
+    });
+
+    if !add_workspace_member(&mut document, name)? {
+        return Err(Error::user(format!("Crate '{}' already exists", name)));
+    }
+    // A failed step leaves no member behind that does not build
+    let rollback = Rollback::existing_dir(
+        Path::new("."),
+        [PathBuf::from("Cargo.toml"), PathBuf::from(name)]
+            .into_iter()
+            .chain(
+                services
+                    .iter()
+                    .map(|service| Path::new(service).join("Cargo.toml")),
+            ),
+    )?;
+    fs::write("Cargo.toml", document.to_string()).context("Failed to update Cargo.toml")?;
+    output::file(Path::new("Cargo.toml"));
+
+    say!("Creating shared crate '{}'", name);
+    let src_dir = Path::new(name).join("src");
+    fs::create_dir_all(&src_dir).with_context(|| format!("Failed to create {} crate", name))?;
+    write_file(
+        &Path::new(name).join("Cargo.toml"),
+        &get_shared_manifest(name),
+    )?;
+    write_file(&src_dir.join("lib.rs"), SHARED_LIB)?;
+    write_file(&src_dir.join("error.rs"), SHARED_ERROR)?;
+    write_file(&src_dir.join("dto.rs"), SHARED_DTO)?;
+
+    for service in &services {
+        say!("Adding {} to {}", name, service);
+        add_path_dependency(service, name)?;
+    }
+    rollback.commit();
+
+    say!("\n✅ Shared crate '{}' added to the workspace!", name);
+    Ok(())
+}